@@ -0,0 +1,63 @@
+use std::{error::Error, path::Path};
+
+use glam::Vec2;
+use tridify_rs::*;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    //Create app and main window.
+    let mut app = Tridify::new();
+    let window = app.create_window()?;
+    let gpu_ctx = window.ctx();
+
+    let texture = Texture::from_path(gpu_ctx, Path::new(r#"examples/texture_cube/texture.png"#));
+    let mut canvas = Canvas::new(gpu_ctx);
+
+    window.set_render_loop(move |gpu, frame_ctx| {
+        let time = frame_ctx.elapsed_time as f32;
+
+        //Record the shapes of this frame, in pixels from the top left corner.
+        canvas.rect(
+            &Rect::new(Vec2::new(20.0, 20.0), Vec2::new(200.0, 120.0)),
+            Color::BLUE_NAVY,
+        );
+        canvas.rounded_rect(
+            &Rect::new(Vec2::new(240.0, 20.0), Vec2::new(200.0, 120.0)),
+            24.0,
+            Color::BLUE_TEAL,
+        );
+        canvas.circle(
+            Vec2::new(120.0, 260.0),
+            80.0 + time.sin() * 20.0,
+            Color::RED,
+        );
+        canvas.sprite(
+            &texture,
+            &Rect::new(Vec2::new(240.0, 180.0), Vec2::new(160.0, 160.0)),
+            Color::WHITE,
+        );
+        let wave = (0..32)
+            .map(|i| {
+                Vec2::new(
+                    20.0 + i as f32 * 14.0,
+                    420.0 + (time + i as f32 * 0.5).sin() * 30.0,
+                )
+            })
+            .collect::<Vec<_>>();
+        canvas.polyline(&wave, 6.0, LineJoin::Round, false, Color::YELLOW);
+        canvas.line(
+            Vec2::new(20.0, 480.0),
+            Vec2::new(460.0, 480.0),
+            2.0,
+            Color::WHITE,
+        );
+
+        let mut pass_builder = gpu.create_render_builder();
+        let mut render_pass = pass_builder.build_render_pass(RenderOptions::default());
+        render_pass.render_canvas(gpu, &mut canvas);
+        render_pass.finish();
+        pass_builder.finish_render(gpu);
+    });
+
+    // Start program.
+    app.start(());
+}
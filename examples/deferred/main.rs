@@ -0,0 +1,80 @@
+use std::error::Error;
+
+use glam::{Quat, Vec3};
+use tridify_rs::*;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    //Create app and main window.
+    let mut app = Tridify::new();
+    let window = app.create_window()?;
+    let gpu_ctx = window.ctx();
+
+    let camera = Camera::new(
+        Transform::from_look_at(Vec3::new(0.0, 8.0, -14.0), Vec3::ZERO, Vec3::Y),
+        Projection::default(),
+    );
+    let camera_buf = camera.build_buffer(gpu_ctx);
+
+    //Deferred renderer creates the G-buffer and the lighting pass.
+    let mut renderer = DeferredRenderer::new(gpu_ctx)?;
+    renderer.set_camera(gpu_ctx, &camera);
+
+    //Geometry brushes write surface data into the G-buffer instead of colors.
+    let mut brush = renderer.geometry_brush(gpu_ctx, GEOMETRY_SHADER_TEMPLATE.to_string())?;
+    brush.bind(0, 0, camera_buf);
+
+    //Ground with a grid of cubes on top.
+    let mut batch = ShapeBatch::new();
+    batch.add_square(Vec3::ZERO, Vec3::Z, Vec3::Y, 20.0, 20.0, Color::SILVER);
+    for x in -2..=2 {
+        for z in -2..=2 {
+            let center = Vec3::new(x as f32 * 3.0, 0.5, z as f32 * 3.0);
+            batch.add_cube(center, Quat::IDENTITY, Vec3::ONE, Color::WHITE);
+        }
+    }
+    let shapes = batch.bake_buffers(gpu_ctx);
+
+    let colors = [Color::RED, Color::GREEN, Color::BLUE, Color::YELLOW];
+    window.set_render_loop(move |gpu, frame_ctx| {
+        //Lots of small lights orbiting around the scene.
+        let time = frame_ctx.elapsed_time as f32;
+        let lights = (0..64)
+            .map(|i| {
+                let angle = i as f32 * 0.4 + time * 0.5;
+                let dist = 2.0 + (i % 8) as f32;
+                PointLight {
+                    position: Vec3::new(angle.cos() * dist, 1.0, angle.sin() * dist),
+                    radius: 4.0,
+                    color: colors[i % colors.len()],
+                    intensity: 3.0,
+                }
+            })
+            .collect::<Vec<_>>();
+        renderer.set_lights(gpu, &lights);
+
+        //G-buffer needs to match the window size.
+        if renderer.gbuffer().size() != gpu.get_wnd_size() {
+            renderer.resize(gpu, gpu.get_wnd_size());
+        }
+
+        let mut pass_builder = gpu.create_render_builder();
+        let mut geometry_pass = renderer.build_geometry_pass(&mut pass_builder);
+        geometry_pass.render_shapes(gpu, &mut brush, &shapes);
+        geometry_pass.finish();
+
+        //Cycle between the lit scene and each G-buffer channel every few seconds.
+        let mut render_pass = pass_builder.build_render_pass(RenderOptions::default());
+        match (time / 3.0) as u32 % 5 {
+            0 => renderer.render_lighting(&mut render_pass),
+            1 => renderer.render_debug(gpu, &mut render_pass, GBufferView::Albedo),
+            2 => renderer.render_debug(gpu, &mut render_pass, GBufferView::Normal),
+            3 => renderer.render_debug(gpu, &mut render_pass, GBufferView::Material),
+            _ => renderer.render_debug(gpu, &mut render_pass, GBufferView::Depth),
+        }
+        render_pass.finish();
+        pass_builder.finish_render(gpu);
+    });
+
+    // Start program.
+    app.start(());
+}
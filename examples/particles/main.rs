@@ -0,0 +1,51 @@
+use std::error::Error;
+
+use glam::Vec3;
+use tridify_rs::*;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    //Create app and main window.
+    let mut app = Tridify::new();
+    let window = app.create_window()?;
+    let gpu_ctx = window.ctx();
+
+    //Fountain of particles going up from a cone and fading from yellow to red.
+    let mut particles = ParticleSystem::new(
+        gpu_ctx,
+        ParticleSystemDesc {
+            emission_rate: 2000.0,
+            shape: EmitterShape::Cone {
+                angle: 0.3,
+                radius: 0.2,
+            },
+            speed: 6.0..9.0,
+            lifetime: 1.5..2.5,
+            drag: 0.3,
+            start_size: 0.15,
+            end_size: 0.05,
+            color_over_life: vec![(0.0, Color::YELLOW), (0.5, Color::RED), (1.0, Color::CLEAR)],
+            ..Default::default()
+        },
+    )?;
+    particles.set_camera(&Camera::new(
+        Transform::from_look_at(Vec3::new(0.0, 4.0, -12.0), Vec3::Y * 3.0, Vec3::Y),
+        Projection::default(),
+    ));
+
+    window.set_render_loop(move |gpu, frame_ctx| {
+        let mut pass_builder = gpu.create_render_builder();
+
+        //Simulate particles on the GPU before drawing them.
+        let mut compute_pass = pass_builder.build_compute_pass();
+        particles.simulate(gpu, &mut compute_pass, frame_ctx.delta_time as f32);
+        compute_pass.finish();
+
+        let mut render_pass = pass_builder.build_render_pass(RenderOptions::default());
+        render_pass.render_particles(&particles);
+        render_pass.finish();
+        pass_builder.finish_render(gpu);
+    });
+
+    // Start program.
+    app.start(());
+}
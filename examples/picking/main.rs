@@ -0,0 +1,84 @@
+use std::error::Error;
+
+use glam::{Quat, Vec2, Vec3};
+use tridify_rs::*;
+use winit::event::{Event, WindowEvent};
+
+fn main() -> Result<(), Box<dyn Error>> {
+    //Create app and main window.
+    let mut app = Tridify::new();
+    let window = app.create_window()?;
+    let gpu_ctx = window.ctx();
+
+    let mut camera = Camera::default();
+    let mut orbit = OrbitController::new(Vec3::new(0.0, 8.0, -12.0), Vec3::ZERO);
+
+    //A grid of cubes stored as scene nodes, so their IDs can be drawn by the picker.
+    let mut scene = Scene::new(gpu_ctx);
+    let brush = Brush::from_source(
+        BrushDesc::default(),
+        gpu_ctx,
+        SCENE_SHADER_TEMPLATE.to_string(),
+    )?;
+    let brush = scene.add_brush(gpu_ctx, brush);
+    let cube = scene.add_mesh(
+        ShapeBatch::new()
+            .add_cube(Vec3::ZERO, Quat::IDENTITY, Vec3::ONE, Color::BLUE_TEAL)
+            .bake_buffers(gpu_ctx),
+    );
+    for x in -3..=3 {
+        for z in -3..=3 {
+            let position = Vec3::new(x as f32 * 1.5, 0.0, z as f32 * 1.5);
+            scene.add_node(
+                Node::new(&format!("cube {x} {z}"))
+                    .with_transform(Transform::from_pos(position))
+                    .with_mesh(cube, brush),
+                None,
+            );
+        }
+    }
+
+    let picker = GpuPicker::new(gpu_ctx);
+    let mut debug = DebugDraw::new(gpu_ctx);
+    let mut cursor = None;
+
+    window.set_render_loop(move |gpu, frame_ctx| {
+        orbit.update_frame(&mut camera, frame_ctx);
+        for event in frame_ctx.input_events {
+            match event {
+                Event::WindowEvent {
+                    event: WindowEvent::CursorMoved { position, .. },
+                    ..
+                } => cursor = Some(Vec2::new(position.x as f32, position.y as f32)),
+                Event::WindowEvent {
+                    event: WindowEvent::CursorLeft { .. },
+                    ..
+                } => cursor = None,
+                _ => {}
+            }
+        }
+
+        //Highlight the cube under the cursor, read from the IDs drawn the previous frame.
+        let hovered = cursor.and_then(|x| picker.pick_node(gpu, x));
+        if let Some(node) = hovered {
+            let center = scene.world_matrix(node).transform_point3(Vec3::ZERO);
+            debug.aabb(
+                &Aabb::new(center - Vec3::splat(0.55), center + Vec3::splat(0.55)),
+                Color::YELLOW,
+            );
+        }
+
+        let mut pass_builder = gpu.create_render_builder();
+        let mut picking_pass = pass_builder.build_picking_pass(&picker, &camera);
+        picking_pass.render_scene_ids(gpu, &picker, &scene);
+        picking_pass.finish();
+        let mut render_pass = pass_builder.build_render_pass(RenderOptions::default());
+        render_pass.render_scene(gpu, &scene, &camera);
+        render_pass.render_debug(gpu, &mut debug, &camera);
+        render_pass.finish();
+        pass_builder.finish_render(gpu);
+    });
+
+    // Start program.
+    app.start(());
+}
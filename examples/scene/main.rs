@@ -0,0 +1,106 @@
+use std::{error::Error, rc::Rc};
+
+use glam::{Quat, Vec3};
+use tridify_rs::*;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    //Create app and main window.
+    let mut app = Tridify::new();
+    let window = app.create_window()?;
+    let gpu_ctx = window.ctx();
+
+    //Drag with the left mouse button to rotate, with the right one to pan and scroll to zoom.
+    let mut camera = Camera::default();
+    let mut orbit = OrbitController::new(Vec3::new(0.0, 10.0, -15.0), Vec3::ZERO);
+
+    //Scenes store meshes and brushes, nodes reference them by id.
+    let mut scene = Scene::new(gpu_ctx);
+    let brush = Brush::from_source(
+        BrushDesc::default(),
+        gpu_ctx,
+        SCENE_SHADER_TEMPLATE.to_string(),
+    )?;
+    let brush = scene.add_brush(gpu_ctx, brush);
+    let cube = |color| {
+        ShapeBatch::new()
+            .add_cube(Vec3::ZERO, Quat::IDENTITY, Vec3::ONE, color)
+            .bake_buffers(gpu_ctx)
+    };
+    let sun_mesh = scene.add_mesh(cube(Color::YELLOW));
+    let planet_mesh = scene.add_mesh(cube(Color::BLUE));
+    let moon_mesh = scene.add_mesh(cube(Color::SILVER));
+
+    //Children move along with their parents.
+    let sun = scene.add_node(
+        Node::new("sun")
+            .with_transform(Transform::new(Vec3::ZERO, Quat::IDENTITY, Vec3::ONE * 2.0))
+            .with_mesh(sun_mesh, brush),
+        None,
+    );
+    let planet_orbit = scene.add_node(Node::new("planet orbit"), Some(sun));
+    let planet = scene.add_node(
+        Node::new("planet")
+            .with_transform(Transform::new(
+                Vec3::X * 3.0,
+                Quat::IDENTITY,
+                Vec3::ONE * 0.5,
+            ))
+            .with_mesh(planet_mesh, brush),
+        Some(planet_orbit),
+    );
+    let moon_orbit = scene.add_node(Node::new("moon orbit"), Some(planet));
+    scene.add_node(
+        Node::new("moon")
+            .with_transform(Transform::new(
+                Vec3::X * 1.5,
+                Quat::IDENTITY,
+                Vec3::ONE * 0.4,
+            ))
+            .with_mesh(moon_mesh, brush),
+        Some(moon_orbit),
+    );
+
+    //Keyframed clips write into the nodes with the same name as their tracks.
+    let pulse = AnimationClip::new("pulse").with_track(
+        "sun",
+        AnimationTrack::Scale(
+            Track::new(Interpolation::CubicSpline)
+                .with_key(0.0, Vec3::ONE * 2.0)
+                .with_key(1.5, Vec3::ONE * 2.3),
+        ),
+    );
+    let mut animation = AnimationPlayer::new();
+    animation.mode = PlaybackMode::PingPong;
+    animation.play(Rc::new(pulse));
+
+    //Gizmos are recorded every frame and cleared after drawing them.
+    let mut debug = DebugDraw::new(gpu_ctx);
+
+    window.set_render_loop(move |gpu, frame_ctx| {
+        orbit.update_frame(&mut camera, frame_ctx);
+        animation.update(frame_ctx);
+        animation.apply_to_scene(&mut scene);
+        let time = frame_ctx.elapsed_time as f32;
+        scene.set_transform(
+            planet_orbit,
+            Transform::new(Vec3::ZERO, Quat::from_rotation_y(time), Vec3::ONE),
+        );
+        scene.set_transform(
+            moon_orbit,
+            Transform::new(Vec3::ZERO, Quat::from_rotation_y(time * 3.0), Vec3::ONE),
+        );
+
+        debug.grid(Vec3::new(0.0, -2.0, 0.0), 1.0, 20, Color::GRAY);
+        debug.axes(&Transform::default(), 4.0);
+
+        let mut pass_builder = gpu.create_render_builder();
+        let mut render_pass = pass_builder.build_render_pass(RenderOptions::default());
+        render_pass.render_scene(gpu, &scene, &camera);
+        render_pass.render_debug(gpu, &mut debug, &camera);
+        render_pass.finish();
+        pass_builder.finish_render(gpu);
+    });
+
+    // Start program.
+    app.start(());
+}
@@ -0,0 +1,108 @@
+use std::{error::Error, path::Path};
+
+use glam::{Quat, Vec2, Vec3};
+use tridify_rs::*;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let font_path = std::env::args()
+        .nth(1)
+        .ok_or("Usage: cargo run --example text -- <font.ttf>")?;
+    let font = Font::from_path(Path::new(&font_path))?;
+
+    //Create app and main window.
+    let mut app = Tridify::new();
+    let window = app.create_window()?;
+    let gpu_ctx = window.ctx();
+
+    //Bitmap glyphs for 2D text drawn with a canvas.
+    let mut ui_atlas = GlyphAtlas::new(gpu_ctx, font.clone(), GlyphMode::Bitmap);
+    let mut canvas = Canvas::new(gpu_ctx);
+
+    //Distance field glyphs stay crisp when drawn in 3D at any distance.
+    let mut world_atlas = GlyphAtlas::new(
+        gpu_ctx,
+        font.clone(),
+        GlyphMode::Sdf {
+            size: 48.0,
+            spread: 6.0,
+        },
+    );
+    let style = TextStyle {
+        size: 48.0,
+        align: TextAlign::Center,
+        ..Default::default()
+    };
+    let layout = font.layout("Hello\nworld", &style);
+    let mut batch = ShapeBatch::new();
+    //Center the text on the origin, 48 pixels being one unit tall.
+    let scale = 1.0 / 48.0;
+    batch.add_text(
+        gpu_ctx,
+        &mut world_atlas,
+        &layout,
+        &Transform::new(
+            Vec3::new(-layout.size.x, layout.size.y, 0.0) * scale / 2.0,
+            Quat::IDENTITY,
+            Vec3::splat(scale),
+        ),
+        Color::WHITE,
+    );
+
+    let mut scene = Scene::new(gpu_ctx);
+    let mut brush = Brush::from_source(
+        BrushDesc {
+            blend: wgpu::BlendState::ALPHA_BLENDING,
+            ..Default::default()
+        },
+        gpu_ctx,
+        SDF_TEXT_SHADER_TEMPLATE.to_string(),
+    )?;
+    brush.bind(1, 0, world_atlas.texture().clone());
+    brush.bind(1, 1, Sampler::new_default(gpu_ctx));
+    let brush = scene.add_brush(gpu_ctx, brush);
+    let mesh = scene.add_mesh(batch.bake_buffers(gpu_ctx));
+    let text = scene.add_node(Node::new("text").with_mesh(mesh, brush), None);
+    let camera = Camera::new(
+        Transform::from_look_at(Vec3::new(0.0, 0.0, -4.0), Vec3::ZERO, Vec3::Y),
+        Projection::default(),
+    );
+
+    window.set_render_loop(move |gpu, frame_ctx| {
+        let time = frame_ctx.elapsed_time as f32;
+        scene.set_transform(
+            text,
+            Transform::new(Vec3::ZERO, Quat::from_rotation_y(time.sin()), Vec3::ONE),
+        );
+
+        canvas.text(
+            gpu,
+            &mut ui_atlas,
+            &format!("Elapsed time: {:.1}s", time),
+            Vec2::new(20.0, 20.0),
+            &TextStyle::default(),
+            Color::WHITE,
+        );
+        canvas.text(
+            gpu,
+            &mut ui_atlas,
+            "Long lines are broken at the last word that fits in the max width.",
+            Vec2::new(20.0, 60.0),
+            &TextStyle {
+                size: 24.0,
+                max_width: Some(300.0),
+                ..Default::default()
+            },
+            Color::YELLOW,
+        );
+
+        let mut pass_builder = gpu.create_render_builder();
+        let mut render_pass = pass_builder.build_render_pass(RenderOptions::default());
+        render_pass.render_scene(gpu, &scene, &camera);
+        render_pass.render_canvas(gpu, &mut canvas);
+        render_pass.finish();
+        pass_builder.finish_render(gpu);
+    });
+
+    // Start program.
+    app.start(());
+}
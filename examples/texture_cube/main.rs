@@ -19,7 +19,7 @@ fn main() -> Result<(), Box<dyn Error>> {
         Transform::from_look_at(Vec3::NEG_Z * 10.0 + Vec3::Y * 10.0, Vec3::ZERO, Vec3::Y),
         Projection::default(),
     );
-    let mut camera_buf = camera.build_buffer(gpu_ctx);
+    let camera_buf = camera.build_buffer(gpu_ctx);
 
     //Create brush to draw the shapes.
     let mut brush = Brush::from_source(
@@ -49,7 +49,7 @@ fn main() -> Result<(), Box<dyn Error>> {
         let mvp = camera.build_camera_matrix() * model;
 
         //Updating the gpu buffer will update all brushes binded as well.
-        camera_buf.write(gpu, bytemuck::cast_slice(&mvp.to_cols_array()));
+        camera_buf.write_slice(gpu, bytemuck::cast_slice(&mvp.to_cols_array()));
 
         //Render frame as usual.
         let mut pass_builder = gpu.create_render_builder();
@@ -0,0 +1,719 @@
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+use glam::{Quat, Vec2, Vec3, Vec4};
+
+use crate::{Color, FrameContext, NodeTree, Scene, Transform};
+
+/// Value that can be animated by a [`Track`].
+pub trait Animatable: Copy {
+    /// Value with no effect in weighted sums, used as the default cubic spline tangent.
+    const ZERO: Self;
+
+    /// Interpolate from `a` to `b` with `t` going from 0 to 1.
+    fn interpolate(a: Self, b: Self, t: f32) -> Self;
+
+    /// Sum of the values multiplied by their weights, used by cubic splines.
+    fn weighted_sum(values: &[(Self, f32)]) -> Self;
+}
+
+macro_rules! impl_animatable_vector {
+    ($($ty:ty),*) => {
+        $(
+            impl Animatable for $ty {
+                const ZERO: Self = <$ty>::ZERO;
+
+                fn interpolate(a: Self, b: Self, t: f32) -> Self { a.lerp(b, t) }
+
+                fn weighted_sum(values: &[(Self, f32)]) -> Self {
+                    values.iter().fold(Self::ZERO, |sum, (value, weight)| sum + *value * *weight)
+                }
+            }
+        )*
+    };
+}
+impl_animatable_vector!(Vec2, Vec3, Vec4);
+
+impl Animatable for f32 {
+    const ZERO: Self = 0.0;
+
+    fn interpolate(a: Self, b: Self, t: f32) -> Self { a + (b - a) * t }
+
+    fn weighted_sum(values: &[(Self, f32)]) -> Self {
+        values.iter().map(|(value, weight)| value * weight).sum()
+    }
+}
+
+/// Rotations are interpolated spherically and cubic splines are normalized.
+impl Animatable for Quat {
+    const ZERO: Self = Quat::from_xyzw(0.0, 0.0, 0.0, 0.0);
+
+    fn interpolate(a: Self, b: Self, t: f32) -> Self { a.slerp(b, t) }
+
+    fn weighted_sum(values: &[(Self, f32)]) -> Self {
+        let sum = values.iter().fold(Vec4::ZERO, |sum, (value, weight)| {
+            sum + Vec4::from(*value) * *weight
+        });
+        Quat::from_vec4(sum).normalize()
+    }
+}
+
+impl Animatable for Color {
+    const ZERO: Self = Color::CLEAR;
+
+    fn interpolate(a: Self, b: Self, t: f32) -> Self {
+        Self::new(
+            f32::interpolate(a.r, b.r, t),
+            f32::interpolate(a.g, b.g, t),
+            f32::interpolate(a.b, b.b, t),
+            f32::interpolate(a.a, b.a, t),
+        )
+    }
+
+    fn weighted_sum(values: &[(Self, f32)]) -> Self {
+        let sum = values.iter().fold(Vec4::ZERO, |sum, (value, weight)| {
+            sum + Vec4::new(value.r, value.g, value.b, value.a) * *weight
+        });
+        Self::new(sum.x, sum.y, sum.z, sum.w)
+    }
+}
+
+/// How values are computed between two keyframes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    #[default]
+    Linear,
+    /// Keep the value of the previous keyframe.
+    Step,
+    /// Hermite spline using the keyframe tangents, as in glTF.
+    CubicSpline,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Keyframe<T> {
+    /// Time in seconds from the start of the clip.
+    pub time: f32,
+    pub value: T,
+    /// Rate of change arriving at the keyframe in value units per second, used by
+    /// [`Interpolation::CubicSpline`].
+    pub in_tangent: T,
+    /// Rate of change leaving the keyframe in value units per second, used by
+    /// [`Interpolation::CubicSpline`].
+    pub out_tangent: T,
+}
+
+/// Keyframes of a single value sorted by time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track<T> {
+    keyframes: Vec<Keyframe<T>>,
+    pub interpolation: Interpolation,
+}
+
+impl<T: Animatable> Track<T> {
+    pub fn new(interpolation: Interpolation) -> Self {
+        Self {
+            keyframes: Vec::new(),
+            interpolation,
+        }
+    }
+
+    /// Add a keyframe with zero tangents.
+    pub fn with_key(self, time: f32, value: T) -> Self {
+        self.with_cubic_key(time, value, T::ZERO, T::ZERO)
+    }
+
+    /// Add a keyframe with the tangents used by [`Interpolation::CubicSpline`].
+    pub fn with_cubic_key(mut self, time: f32, value: T, in_tangent: T, out_tangent: T) -> Self {
+        self.insert(Keyframe {
+            time,
+            value,
+            in_tangent,
+            out_tangent,
+        });
+        self
+    }
+
+    /// Add a keyframe keeping them sorted by time. Keyframes at the same time are kept in
+    /// insertion order.
+    pub fn insert(&mut self, keyframe: Keyframe<T>) {
+        let index = self.keyframes.partition_point(|x| x.time <= keyframe.time);
+        self.keyframes.insert(index, keyframe);
+    }
+
+    pub fn keyframes(&self) -> &[Keyframe<T>] { &self.keyframes }
+
+    /// Time of the last keyframe.
+    pub fn duration(&self) -> f32 { self.keyframes.last().map_or(0.0, |x| x.time) }
+
+    /// Value at the given time. Times outside the keyframes keep the first or last value.
+    ///
+    /// ```
+    /// use tridify_rs::{Interpolation, Track};
+    ///
+    /// let track = Track::new(Interpolation::Linear)
+    ///     .with_key(0.0, 0.0)
+    ///     .with_key(2.0, 10.0);
+    /// assert_eq!(track.sample(0.5), Some(2.5));
+    /// assert_eq!(track.sample(3.0), Some(10.0));
+    ///
+    /// let mut step = track.clone();
+    /// step.interpolation = Interpolation::Step;
+    /// assert_eq!(step.sample(1.9), Some(0.0));
+    /// ```
+    ///
+    /// Cubic splines follow the Hermite basis, with tangents scaled by the time between keys:
+    ///
+    /// ```
+    /// use tridify_rs::{Interpolation, Track};
+    ///
+    /// let track = Track::new(Interpolation::CubicSpline)
+    ///     .with_cubic_key(0.0, 0.0, 0.0, 1.0)
+    ///     .with_cubic_key(2.0, 1.0, 0.5, 0.0);
+    /// assert_eq!(track.sample(0.0), Some(0.0));
+    /// //h00 * 0 + h10 * 2 * 1 + h01 * 1 + h11 * 2 * 0.5 at t = 0.5.
+    /// assert_eq!(track.sample(1.0), Some(0.25 + 0.5 - 0.125));
+    /// //Same at t = 0.25.
+    /// assert_eq!(track.sample(0.5), Some(0.28125 + 0.15625 - 0.046875));
+    /// assert_eq!(track.sample(2.0), Some(1.0));
+    /// ```
+    ///
+    /// Rotations are interpolated spherically:
+    ///
+    /// ```
+    /// use std::f32::consts::FRAC_PI_2;
+    ///
+    /// use glam::Quat;
+    /// use tridify_rs::{Interpolation, Track};
+    ///
+    /// let track = Track::new(Interpolation::Linear)
+    ///     .with_key(0.0, Quat::IDENTITY)
+    ///     .with_key(1.0, Quat::from_rotation_y(FRAC_PI_2));
+    /// let half = track.sample(0.5).unwrap();
+    /// assert!(half.abs_diff_eq(Quat::from_rotation_y(FRAC_PI_2 / 2.0), 1e-6));
+    ///
+    /// let mut cubic = track.clone();
+    /// cubic.interpolation = Interpolation::CubicSpline;
+    /// let half = cubic.sample(0.5).unwrap();
+    /// assert!(half.is_normalized());
+    /// assert!(half.abs_diff_eq(Quat::from_rotation_y(FRAC_PI_2 / 2.0), 1e-6));
+    /// ```
+    pub fn sample(&self, time: f32) -> Option<T> {
+        let next = self.keyframes.partition_point(|x| x.time <= time);
+        if next == 0 {
+            return self.keyframes.first().map(|x| x.value);
+        }
+        if next == self.keyframes.len() {
+            return self.keyframes.last().map(|x| x.value);
+        }
+        let (a, b) = (&self.keyframes[next - 1], &self.keyframes[next]);
+        let span = b.time - a.time;
+        let t = (time - a.time) / span;
+        Some(match self.interpolation {
+            Interpolation::Linear => T::interpolate(a.value, b.value, t),
+            Interpolation::Step => a.value,
+            Interpolation::CubicSpline => {
+                let (t2, t3) = (t * t, t * t * t);
+                T::weighted_sum(&[
+                    (a.value, 2.0 * t3 - 3.0 * t2 + 1.0),
+                    (a.out_tangent, (t3 - 2.0 * t2 + t) * span),
+                    (b.value, -2.0 * t3 + 3.0 * t2),
+                    (b.in_tangent, (t3 - t2) * span),
+                ])
+            }
+        })
+    }
+}
+
+/// Keyframes of a property, see [`AnimationClip::with_track`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnimationTrack {
+    Translation(Track<Vec3>),
+    Rotation(Track<Quat>),
+    Scale(Track<Vec3>),
+    Color(Track<Color>),
+    Float(Track<f32>),
+}
+
+impl AnimationTrack {
+    pub fn duration(&self) -> f32 {
+        match self {
+            Self::Translation(track) | Self::Scale(track) => track.duration(),
+            Self::Rotation(track) => track.duration(),
+            Self::Color(track) => track.duration(),
+            Self::Float(track) => track.duration(),
+        }
+    }
+}
+
+/// Track animating the property of a target, like a scene node name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Channel {
+    pub target: String,
+    pub track: AnimationTrack,
+}
+
+/// Set of tracks played together.
+///
+/// ```
+/// use glam::Vec3;
+/// use tridify_rs::{AnimationClip, AnimationTrack, Interpolation, Track, Transform};
+///
+/// let clip = AnimationClip::new("jump").with_track(
+///     "player",
+///     AnimationTrack::Translation(
+///         Track::new(Interpolation::Linear)
+///             .with_key(0.0, Vec3::ZERO)
+///             .with_key(0.5, Vec3::Y)
+///             .with_key(1.0, Vec3::ZERO),
+///     ),
+/// );
+/// assert_eq!(clip.duration, 1.0);
+///
+/// let mut transform = Transform::default();
+/// clip.sample(0.25).apply_to_transform("player", &mut transform);
+/// assert_eq!(transform.position(), Vec3::new(0.0, 0.5, 0.0));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationClip {
+    pub name: String,
+    /// Length in seconds, the time of the last keyframe of all tracks unless changed.
+    pub duration: f32,
+    pub channels: Vec<Channel>,
+}
+
+impl AnimationClip {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            duration: 0.0,
+            channels: Vec::new(),
+        }
+    }
+
+    /// Add a track animating a property of the target, extending the duration to its last
+    /// keyframe.
+    pub fn with_track(mut self, target: &str, track: AnimationTrack) -> Self {
+        self.duration = self.duration.max(track.duration());
+        self.channels.push(Channel {
+            target: target.to_string(),
+            track,
+        });
+        self
+    }
+
+    /// Values of all tracks at the given time.
+    pub fn sample(&self, time: f32) -> AnimationPose {
+        let mut pose = AnimationPose::default();
+        for channel in self.channels.iter() {
+            let target = channel.target.clone();
+            match &channel.track {
+                AnimationTrack::Translation(track) => pose
+                    .translations
+                    .extend(track.sample(time).map(|x| (target, x))),
+                AnimationTrack::Rotation(track) => pose
+                    .rotations
+                    .extend(track.sample(time).map(|x| (target, x))),
+                AnimationTrack::Scale(track) => {
+                    pose.scales.extend(track.sample(time).map(|x| (target, x)))
+                }
+                AnimationTrack::Color(track) => {
+                    pose.colors.extend(track.sample(time).map(|x| (target, x)))
+                }
+                AnimationTrack::Float(track) => {
+                    pose.floats.extend(track.sample(time).map(|x| (target, x)))
+                }
+            }
+        }
+        pose
+    }
+}
+
+/// Animated values by target, sampled from clips.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AnimationPose {
+    pub translations: HashMap<String, Vec3>,
+    pub rotations: HashMap<String, Quat>,
+    pub scales: HashMap<String, Vec3>,
+    pub colors: HashMap<String, Color>,
+    pub floats: HashMap<String, f32>,
+}
+
+impl AnimationPose {
+    /// Interpolate towards another pose by `weight`, from 0 to 1. Values only present in one
+    /// of the poses are kept as they are.
+    pub fn blend(&self, other: &AnimationPose, weight: f32) -> AnimationPose {
+        AnimationPose {
+            translations: blend_values(&self.translations, &other.translations, weight),
+            rotations: blend_values(&self.rotations, &other.rotations, weight),
+            scales: blend_values(&self.scales, &other.scales, weight),
+            colors: blend_values(&self.colors, &other.colors, weight),
+            floats: blend_values(&self.floats, &other.floats, weight),
+        }
+    }
+
+    /// Replace the animated parts of a transform. Parts without tracks are not modified.
+    pub fn apply_to_transform(&self, target: &str, transform: &mut Transform) {
+        let (mut scale, mut rotation, mut position) =
+            transform.affine().to_scale_rotation_translation();
+        let mut animated = false;
+        for (map, value) in [
+            (&self.translations, &mut position),
+            (&self.scales, &mut scale),
+        ] {
+            if let Some(x) = map.get(target) {
+                *value = *x;
+                animated = true;
+            }
+        }
+        if let Some(x) = self.rotations.get(target) {
+            rotation = *x;
+            animated = true;
+        }
+        if animated {
+            *transform = Transform::new(position, rotation, scale);
+        }
+    }
+
+    /// Update the transform of every scene node named like an animated target.
+    pub fn apply_to_scene(&self, scene: &mut Scene) { self.apply_to_nodes(scene.nodes_mut()); }
+
+    /// Update the transform of every node named like an animated target, once per target.
+    ///
+    /// ```
+    /// use glam::{Quat, Vec3};
+    /// use tridify_rs::{AnimationClip, AnimationTrack, Interpolation, Node, NodeTree, Track, Transform};
+    ///
+    /// let key = |value| AnimationTrack::Scale(Track::new(Interpolation::Step).with_key(0.0, value));
+    /// let rotation = Quat::from_rotation_z(0.5);
+    /// let clip = AnimationClip::new("pose")
+    ///     .with_track("arm", key(Vec3::new(1.0, 2.0, 3.0)))
+    ///     .with_track(
+    ///         "arm",
+    ///         AnimationTrack::Rotation(Track::new(Interpolation::Step).with_key(0.0, rotation)),
+    ///     )
+    ///     .with_track("missing", key(Vec3::ONE));
+    ///
+    /// let mut tree = NodeTree::new();
+    /// let arm = tree.add_node(Node::new("arm").with_transform(Transform::from_pos(Vec3::X)), None);
+    /// clip.sample(0.0).apply_to_nodes(&mut tree);
+    /// let transform = tree.node(arm).transform();
+    /// assert_eq!(transform.position(), Vec3::X);
+    /// assert!(transform.rotation().abs_diff_eq(rotation, 1e-6));
+    /// assert!(transform.scale().abs_diff_eq(Vec3::new(1.0, 2.0, 3.0), 1e-5));
+    /// ```
+    pub fn apply_to_nodes(&self, nodes: &mut NodeTree) {
+        let targets: HashSet<&String> = self
+            .translations
+            .keys()
+            .chain(self.rotations.keys())
+            .chain(self.scales.keys())
+            .collect();
+        for target in targets {
+            if let Some(id) = nodes.find(target) {
+                let mut transform = *nodes.node(id).transform();
+                self.apply_to_transform(target, &mut transform);
+                nodes.set_transform(id, transform);
+            }
+        }
+    }
+
+    pub fn color(&self, target: &str) -> Option<Color> { self.colors.get(target).copied() }
+    pub fn float(&self, target: &str) -> Option<f32> { self.floats.get(target).copied() }
+}
+
+fn blend_values<T: Animatable>(
+    a: &HashMap<String, T>, b: &HashMap<String, T>, weight: f32,
+) -> HashMap<String, T> {
+    let mut values = a.clone();
+    for (target, value) in b.iter() {
+        let blended = match a.get(target) {
+            Some(x) => T::interpolate(*x, *value, weight),
+            None => *value,
+        };
+        values.insert(target.clone(), blended);
+    }
+    values
+}
+
+/// What happens when playback reaches the end of a clip.
+///
+/// ```
+/// use std::rc::Rc;
+///
+/// use tridify_rs::{AnimationClip, AnimationPlayer, AnimationTrack, Interpolation, Track};
+///
+/// let track = Track::new(Interpolation::Linear).with_key(0.0, 0.0).with_key(1.0, 1.0);
+/// let clip = AnimationClip::new("ramp").with_track("value", AnimationTrack::Float(track));
+/// //Looping is the default mode.
+/// let mut player = AnimationPlayer::new();
+/// player.play(Rc::new(clip));
+/// player.advance(1.25);
+/// assert_eq!(player.time(), 0.25);
+/// assert_eq!(player.pose().float("value"), Some(0.25));
+/// assert!(!player.is_finished());
+///
+/// //Also when playing backwards past the start.
+/// player.speed = -1.0;
+/// player.advance(1.5);
+/// assert_eq!(player.time(), 0.75);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlaybackMode {
+    /// Stop at the last frame.
+    Once,
+    #[default]
+    Loop,
+    /// Play backwards after reaching the end, then forwards again.
+    PingPong,
+}
+
+/// Clip being played with its own playback time.
+#[derive(Debug, Clone)]
+struct ClipState {
+    clip: Rc<AnimationClip>,
+    /// Seconds played, before applying the playback mode.
+    elapsed: f32,
+}
+
+impl ClipState {
+    fn new(clip: Rc<AnimationClip>) -> Self { Self { clip, elapsed: 0.0 } }
+
+    /// Move playback time. Clips played once stop at their ends, so reversing starts right away.
+    fn advance(&mut self, played: f32, mode: PlaybackMode) {
+        self.elapsed += played;
+        if mode == PlaybackMode::Once {
+            self.elapsed = self.elapsed.clamp(0.0, self.clip.duration.max(0.0));
+        }
+    }
+
+    fn time(&self, mode: PlaybackMode) -> f32 {
+        let duration = self.clip.duration;
+        if duration <= 0.0 {
+            return 0.0;
+        }
+        match mode {
+            PlaybackMode::Once => self.elapsed.clamp(0.0, duration),
+            PlaybackMode::Loop => self.elapsed.rem_euclid(duration),
+            PlaybackMode::PingPong => {
+                let time = self.elapsed.rem_euclid(duration * 2.0);
+                match time > duration {
+                    true => duration * 2.0 - time,
+                    false => time,
+                }
+            }
+        }
+    }
+}
+
+/// Second clip blended over the current one.
+#[derive(Debug, Clone)]
+struct BlendState {
+    target: ClipState,
+    weight: f32,
+    /// Seconds left to finish a cross fade, `None` when the weight is set manually.
+    fade: Option<(f32, f32)>,
+}
+
+/// Plays animation clips, blending between two of them. Time only moves with
+/// [`AnimationPlayer::advance`], so results are the same for the same sequence of delta times.
+///
+/// ```
+/// use std::rc::Rc;
+///
+/// use tridify_rs::{
+///     AnimationClip, AnimationPlayer, AnimationTrack, Interpolation, PlaybackMode, Track,
+/// };
+///
+/// let fade = |from, to| {
+///     let track = Track::new(Interpolation::Linear).with_key(0.0, from).with_key(1.0, to);
+///     Rc::new(AnimationClip::new("fade").with_track("alpha", AnimationTrack::Float(track)))
+/// };
+/// let mut player = AnimationPlayer::new();
+/// player.mode = PlaybackMode::PingPong;
+/// player.play(fade(0.0, 1.0));
+/// player.advance(1.5);
+/// assert_eq!(player.pose().float("alpha"), Some(0.5));
+///
+/// player.cross_fade(fade(10.0, 10.0), 1.0);
+/// player.advance(0.25);
+/// assert_eq!(player.pose().float("alpha"), Some(0.25 + (10.0 - 0.25) * 0.25));
+/// ```
+#[derive(Debug, Clone)]
+pub struct AnimationPlayer {
+    current: Option<ClipState>,
+    blend: Option<BlendState>,
+    /// Factor applied to the delta time. Negative values play backwards.
+    pub speed: f32,
+    pub mode: PlaybackMode,
+    pub paused: bool,
+}
+
+impl AnimationPlayer {
+    pub fn new() -> Self {
+        Self {
+            current: None,
+            blend: None,
+            speed: 1.0,
+            mode: PlaybackMode::Loop,
+            paused: false,
+        }
+    }
+
+    /// Play a clip from the start, stopping any blending.
+    pub fn play(&mut self, clip: Rc<AnimationClip>) {
+        self.current = Some(ClipState::new(clip));
+        self.blend = None;
+    }
+
+    /// Start playing a clip, blending into it over `duration` seconds. The clip replaces the
+    /// current one when the fade ends.
+    pub fn cross_fade(&mut self, clip: Rc<AnimationClip>, duration: f32) {
+        if self.current.is_none() || duration <= 0.0 {
+            self.play(clip);
+            return;
+        }
+        self.blend = Some(BlendState {
+            target: ClipState::new(clip),
+            weight: 0.0,
+            fade: Some((duration, duration)),
+        });
+    }
+
+    /// Play a second clip blended over the current one with a fixed weight, from 0 to 1, until
+    /// [`AnimationPlayer::play`] or [`AnimationPlayer::cross_fade`] are called.
+    ///
+    /// ```
+    /// use std::rc::Rc;
+    ///
+    /// use tridify_rs::{AnimationClip, AnimationPlayer, AnimationTrack, Interpolation, Track};
+    ///
+    /// let clip = |from, to| {
+    ///     let track = Track::new(Interpolation::Linear).with_key(0.0, from).with_key(1.0, to);
+    ///     Rc::new(AnimationClip::new("clip").with_track("value", AnimationTrack::Float(track)))
+    /// };
+    /// let mut player = AnimationPlayer::new();
+    /// player.play(clip(0.0, 1.0));
+    /// player.blend_with(clip(10.0, 10.0), 0.25);
+    /// player.advance(0.5);
+    /// assert_eq!(player.pose().float("value"), Some(0.5 + (10.0 - 0.5) * 0.25));
+    ///
+    /// //The weight stays fixed while playing until changed.
+    /// player.advance(10.0);
+    /// assert_eq!(player.pose().float("value"), Some(0.5 + (10.0 - 0.5) * 0.25));
+    /// player.set_blend_weight(1.0);
+    /// assert_eq!(player.pose().float("value"), Some(10.0));
+    /// player.set_blend_weight(0.0);
+    /// assert_eq!(player.pose().float("value"), Some(0.5));
+    /// ```
+    pub fn blend_with(&mut self, clip: Rc<AnimationClip>, weight: f32) {
+        self.blend = Some(BlendState {
+            target: ClipState::new(clip),
+            weight,
+            fade: None,
+        });
+    }
+
+    /// Change the weight of the clip added with [`AnimationPlayer::blend_with`].
+    pub fn set_blend_weight(&mut self, weight: f32) {
+        if let Some(blend) = self.blend.as_mut() {
+            blend.weight = weight;
+            blend.fade = None;
+        }
+    }
+
+    /// Move playback forward by `delta_time` seconds.
+    pub fn advance(&mut self, delta_time: f32) {
+        if self.paused {
+            return;
+        }
+        let played = delta_time * self.speed;
+        if let Some(current) = self.current.as_mut() {
+            current.advance(played, self.mode);
+        }
+        let Some(blend) = self.blend.as_mut() else {
+            return;
+        };
+        blend.target.advance(played, self.mode);
+        if let Some((left, duration)) = blend.fade.as_mut() {
+            //Fades last the same time regardless of the playback speed.
+            *left -= delta_time;
+            blend.weight = (1.0 - *left / *duration).clamp(0.0, 1.0);
+            if *left <= 0.0 {
+                self.current = self.blend.take().map(|x| x.target);
+            }
+        }
+    }
+
+    /// Advance playback by the frame delta time.
+    pub fn update(&mut self, frame_ctx: &FrameContext) {
+        self.advance(frame_ctx.delta_time as f32);
+    }
+
+    /// Playback time of the current clip in seconds, after applying the playback mode.
+    pub fn time(&self) -> f32 { self.current.as_ref().map_or(0.0, |x| x.time(self.mode)) }
+
+    pub fn clip(&self) -> Option<&Rc<AnimationClip>> { self.current.as_ref().map(|x| &x.clip) }
+
+    /// Whether a clip played with [`PlaybackMode::Once`] reached its end.
+    ///
+    /// ```
+    /// use std::rc::Rc;
+    ///
+    /// use tridify_rs::{
+    ///     AnimationClip, AnimationPlayer, AnimationTrack, Interpolation, PlaybackMode, Track,
+    /// };
+    ///
+    /// let track = Track::new(Interpolation::Linear).with_key(0.0, 0.0).with_key(1.0, 1.0);
+    /// let clip = AnimationClip::new("ramp").with_track("value", AnimationTrack::Float(track));
+    /// let mut player = AnimationPlayer::new();
+    /// player.mode = PlaybackMode::Once;
+    /// player.play(Rc::new(clip));
+    /// player.advance(0.5);
+    /// assert!(!player.is_finished());
+    /// player.advance(0.75);
+    /// assert!(player.is_finished());
+    /// assert_eq!(player.time(), 1.0);
+    /// assert_eq!(player.pose().float("value"), Some(1.0));
+    ///
+    /// //Overshooting the end is discarded, so playing backwards for the clip duration finishes
+    /// //at the start.
+    /// player.speed = -1.0;
+    /// player.advance(0.5);
+    /// assert!(!player.is_finished());
+    /// assert_eq!(player.time(), 0.5);
+    /// player.advance(0.5);
+    /// assert!(player.is_finished());
+    /// assert_eq!(player.time(), 0.0);
+    /// assert_eq!(player.pose().float("value"), Some(0.0));
+    /// ```
+    pub fn is_finished(&self) -> bool {
+        self.mode == PlaybackMode::Once
+            && self.blend.is_none()
+            && self
+                .current
+                .as_ref()
+                .is_some_and(|x| match self.speed < 0.0 {
+                    true => x.elapsed <= 0.0,
+                    false => x.elapsed >= x.clip.duration,
+                })
+    }
+
+    /// Values of the playing clips at the current time.
+    pub fn pose(&self) -> AnimationPose {
+        let sample = |state: &ClipState| state.clip.sample(state.time(self.mode));
+        let pose = self.current.as_ref().map(sample).unwrap_or_default();
+        match self.blend.as_ref() {
+            Some(blend) => pose.blend(&sample(&blend.target), blend.weight),
+            None => pose,
+        }
+    }
+
+    /// Write the current pose into the scene nodes named like the animated targets.
+    pub fn apply_to_scene(&self, scene: &mut Scene) { self.pose().apply_to_scene(scene); }
+}
+
+impl Default for AnimationPlayer {
+    fn default() -> Self { Self::new() }
+}
@@ -1,36 +1,134 @@
-use glam::Mat4;
+use glam::{Affine3A, Mat4, Quat, UVec2, Vec2, Vec3};
 
-use crate::{GpuBuffer, GpuCtx, ToGpuBuf, Transform};
+use crate::{Frustum, GpuCtx, GpuLayout, GpuStructWriter, Ray, Rect, ToGpuBuf, Transform};
 
-/// Projection representation using field of view and aspect ratio.
-pub struct Projection {
-    pub aspect: f32,
-    pub fov: f32,
-    pub znear: f32,
-    pub zfar: f32,
+/// How the camera view is projected into the screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// Perspective using vertical field of view in degrees and aspect ratio.
+    Perspective {
+        aspect: f32,
+        fov: f32,
+        znear: f32,
+        zfar: f32,
+    },
+    /// Parallel projection showing `height` world units vertically, centered on the camera.
+    Orthographic {
+        aspect: f32,
+        height: f32,
+        znear: f32,
+        zfar: f32,
+    },
+    /// Perspective without far plane. Depth goes from 1 at the near plane to 0 at infinity,
+    /// keeping precision for distant objects. Brushes need [`wgpu::CompareFunction::Greater`]
+    /// depth tests and passes need to clear depth to 0, see [`crate::RenderOptions`].
+    InfiniteReverseZ { aspect: f32, fov: f32, znear: f32 },
+    /// Orthographic projection where a world unit is a pixel of a viewport with the given size,
+    /// centered on the camera. Pixel edges lie on whole units, so sprites placed at whole
+    /// positions are not blurred.
+    Pixel { size: UVec2, znear: f32, zfar: f32 },
 }
 impl Projection {
+    /// Perspective projection, see [`Projection::Perspective`].
     pub fn new(aspect: f32, fov: f32, znear: f32, zfar: f32) -> Self {
-        Self {
+        Self::Perspective {
             aspect,
             fov,
             znear,
             zfar,
         }
     }
+
+    /// Orthographic projection, see [`Projection::Orthographic`].
+    pub fn orthographic(aspect: f32, height: f32, znear: f32, zfar: f32) -> Self {
+        Self::Orthographic {
+            aspect,
+            height,
+            znear,
+            zfar,
+        }
+    }
+
+    /// Infinite perspective projection, see [`Projection::InfiniteReverseZ`].
+    pub fn infinite_reverse_z(aspect: f32, fov: f32, znear: f32) -> Self {
+        Self::InfiniteReverseZ { aspect, fov, znear }
+    }
+
+    /// Pixel perfect 2D projection for a viewport of the given size, showing everything from
+    /// -1000 to 1000 in Z.
+    pub fn pixel(size: UVec2) -> Self {
+        Self::Pixel {
+            size,
+            znear: -1000.0,
+            zfar: 1000.0,
+        }
+    }
+
     pub fn build_matrix(&self) -> Mat4 {
-        Mat4::perspective_lh(self.fov.to_radians(), self.aspect, self.znear, self.zfar)
+        match *self {
+            Self::Perspective {
+                aspect,
+                fov,
+                znear,
+                zfar,
+            } => Mat4::perspective_lh(fov.to_radians(), aspect, znear, zfar),
+            Self::Orthographic {
+                aspect,
+                height,
+                znear,
+                zfar,
+            } => {
+                let half = Vec2::new(height * aspect, height) / 2.0;
+                Mat4::orthographic_lh(-half.x, half.x, -half.y, half.y, znear, zfar)
+            }
+            Self::InfiniteReverseZ { aspect, fov, znear } => {
+                Mat4::perspective_infinite_reverse_lh(fov.to_radians(), aspect, znear)
+            }
+            Self::Pixel { size, znear, zfar } => {
+                //Odd sizes leave the extra pixel on the right and top sides.
+                let min = -(size / 2).as_vec2();
+                let max = min + size.as_vec2();
+                Mat4::orthographic_lh(min.x, max.x, min.y, max.y, znear, zfar)
+            }
+        }
     }
-}
-impl Default for Projection {
-    fn default() -> Self {
-        Self {
-            aspect: 16.0 / 9.0,
-            fov: 65.0,
-            zfar: 100.0,
-            znear: 0.1,
+
+    /// Update the projection for a viewport with a new size, usually after the window is resized.
+    pub fn resize(&mut self, size: UVec2) {
+        let new_aspect = size.x as f32 / size.y.max(1) as f32;
+        match self {
+            Self::Perspective { aspect, .. }
+            | Self::Orthographic { aspect, .. }
+            | Self::InfiniteReverseZ { aspect, .. } => *aspect = new_aspect,
+            Self::Pixel { size: old_size, .. } => *old_size = size,
+        }
+    }
+
+    /// Distance to the near plane.
+    pub fn znear(&self) -> f32 {
+        match *self {
+            Self::Perspective { znear, .. }
+            | Self::Orthographic { znear, .. }
+            | Self::InfiniteReverseZ { znear, .. }
+            | Self::Pixel { znear, .. } => znear,
         }
     }
+
+    /// Distance to the far plane, infinite for [`Projection::InfiniteReverseZ`].
+    pub fn zfar(&self) -> f32 {
+        match *self {
+            Self::Perspective { zfar, .. }
+            | Self::Orthographic { zfar, .. }
+            | Self::Pixel { zfar, .. } => zfar,
+            Self::InfiniteReverseZ { .. } => f32::INFINITY,
+        }
+    }
+
+    /// Whether depth is stored from 1 at the near plane to 0 at the far plane.
+    pub fn is_reverse_z(&self) -> bool { matches!(self, Self::InfiniteReverseZ { .. }) }
+}
+impl Default for Projection {
+    fn default() -> Self { Self::new(16.0 / 9.0, 65.0, 0.1, 100.0) }
 }
 
 /// Representation of camera to simplify matrices calculation
@@ -44,16 +142,165 @@ impl Camera {
     pub fn new(view: Transform, proj: Projection) -> Self { Self { view, proj } }
 
     pub fn build_camera_matrix(&self) -> Mat4 {
-        let mat = self.proj.build_matrix() * self.view.build_matrix();
-        mat
+        self.proj.build_matrix() * self.view.build_matrix()
+    }
+
+    /// Planes of the volume visible by the camera in world space.
+    ///
+    /// ```
+    /// use glam::Vec3;
+    /// use tridify_rs::{Aabb, Camera, Projection, Transform};
+    ///
+    /// let camera = Camera::new(
+    ///     Transform::from_look_at(Vec3::new(0.0, 0.0, -10.0), Vec3::ZERO, Vec3::Y),
+    ///     Projection::default(),
+    /// );
+    /// let frustum = camera.frustum();
+    /// assert!(frustum.intersects_aabb(&Aabb::new(Vec3::splat(-1.0), Vec3::ONE)));
+    /// let behind = Aabb::new(Vec3::new(-1.0, -1.0, -15.0), Vec3::new(1.0, 1.0, -12.0));
+    /// assert!(!frustum.intersects_aabb(&behind));
+    /// ```
+    pub fn frustum(&self) -> Frustum { Frustum::from_matrix(&self.build_camera_matrix()) }
+
+    /// World space ray going through a pixel of the viewport the camera draws into, starting at
+    /// the near plane. Pixels are measured from the top left corner of the window.
+    ///
+    /// ```
+    /// use glam::{Vec2, Vec3};
+    /// use tridify_rs::{Aabb, Camera, Projection, Rect, Transform};
+    ///
+    /// let camera = Camera::new(
+    ///     Transform::from_look_at(Vec3::new(0.0, 0.0, -10.0), Vec3::ZERO, Vec3::Y),
+    ///     Projection::default(),
+    /// );
+    /// let viewport = Rect::new(Vec2::ZERO, Vec2::new(1280.0, 720.0));
+    /// let ray = camera.screen_ray(Vec2::new(640.0, 360.0), &viewport);
+    /// let cube = Aabb::new(Vec3::splat(-1.0), Vec3::ONE);
+    /// let distance = ray.intersect_aabb(&cube).unwrap();
+    /// assert!((ray.at(distance) - Vec3::new(0.0, 0.0, -1.0)).length() < 1e-3);
+    /// ```
+    pub fn screen_ray(&self, pixel: Vec2, viewport: &Rect) -> Ray {
+        let uv = (pixel - viewport.pos) / viewport.size;
+        let ndc = Vec2::new(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0);
+        //Reverse-Z has the far plane at infinity, so any closer depth gives the direction.
+        let (near, far) = match self.proj.is_reverse_z() {
+            true => (1.0, 0.5),
+            false => (0.0, 1.0),
+        };
+        let inverse = self.build_camera_matrix().inverse();
+        let start = inverse.project_point3(ndc.extend(near));
+        let end = inverse.project_point3(ndc.extend(far));
+        Ray::new(start, end - start)
     }
 }
 
 impl ToGpuBuf for Camera {
-    fn build_buffer(&self, wnd: &GpuCtx) -> crate::GpuBuffer {
-        GpuBuffer::init(
-            wnd,
-            bytemuck::cast_slice(&self.build_camera_matrix().to_cols_array()),
+    fn to_gpu_bytes(&self, layout: GpuLayout) -> Vec<u8> {
+        GpuStructWriter::new(layout)
+            .field(&self.build_camera_matrix())
+            .finish()
+    }
+}
+
+/// Camera for 2D worlds on the XY plane with Y pointing up, drawn into a viewport of the window.
+/// A world unit is a pixel at zoom 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera2D {
+    /// World position shown at the center of the viewport.
+    pub position: Vec2,
+    /// Pixels per world unit.
+    pub zoom: f32,
+    /// Counter-clockwise rotation in radians.
+    pub rotation: f32,
+    /// Area of the window drawn into, in pixels from the top left corner.
+    pub viewport: Rect,
+}
+
+impl Camera2D {
+    /// Camera centered on the origin drawing into the given viewport.
+    pub fn new(viewport: Rect) -> Self {
+        Self {
+            position: Vec2::ZERO,
+            zoom: 1.0,
+            rotation: 0.0,
+            viewport,
+        }
+    }
+
+    /// Camera centered on the origin drawing into the whole window.
+    pub fn from_window(gpu: &GpuCtx) -> Self {
+        Self::new(Rect::new(Vec2::ZERO, gpu.get_wnd_size().as_vec2()))
+    }
+
+    /// Make the viewport cover the whole window, usually after it is resized.
+    pub fn fit_window(&mut self, gpu: &GpuCtx) {
+        self.viewport = Rect::new(Vec2::ZERO, gpu.get_wnd_size().as_vec2());
+    }
+
+    /// Move the camera by a distance in screen pixels, so dragging the mouse by `delta` moves
+    /// the world along with it.
+    pub fn pan(&mut self, delta: Vec2) {
+        let delta = Vec2::new(-delta.x, delta.y) / self.zoom;
+        self.position += Vec2::from_angle(self.rotation).rotate(delta);
+    }
+
+    /// Multiply the zoom by `factor`, keeping the world point below `screen_pos` in place.
+    pub fn zoom_at(&mut self, screen_pos: Vec2, factor: f32) {
+        let anchor = self.screen_to_world(screen_pos);
+        self.zoom *= factor;
+        self.position += anchor - self.screen_to_world(screen_pos);
+    }
+
+    /// Camera drawing the same view, usable anywhere a [`Camera`] is needed.
+    pub fn to_camera(&self) -> Camera {
+        let view = Affine3A::from_scale_rotation_translation(
+            Vec3::new(self.zoom, self.zoom, 1.0),
+            Quat::from_rotation_z(-self.rotation),
+            Vec3::ZERO,
+        ) * Affine3A::from_translation(-self.position.extend(0.0));
+        Camera::new(
+            Transform::from_affine(view),
+            Projection::pixel(self.viewport.size.as_uvec2()),
         )
     }
+
+    pub fn build_camera_matrix(&self) -> Mat4 { self.to_camera().build_camera_matrix() }
+
+    /// Convert a position in window pixels, from the top left corner, to world coordinates.
+    pub fn screen_to_world(&self, screen_pos: Vec2) -> Vec2 {
+        let uv = (screen_pos - self.viewport.pos) / self.viewport.size;
+        let ndc = Vec2::new(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0);
+        let world = self
+            .build_camera_matrix()
+            .inverse()
+            .project_point3(ndc.extend(0.0));
+        Vec2::new(world.x, world.y)
+    }
+
+    /// Convert a world position to window pixels from the top left corner.
+    ///
+    /// ```
+    /// use glam::Vec2;
+    /// use tridify_rs::{Camera2D, Rect};
+    ///
+    /// let camera = Camera2D::new(Rect::new(Vec2::ZERO, Vec2::new(800.0, 600.0)));
+    /// assert_eq!(camera.world_to_screen(Vec2::new(10.0, 10.0)), Vec2::new(410.0, 290.0));
+    /// ```
+    pub fn world_to_screen(&self, world_pos: Vec2) -> Vec2 {
+        let ndc = self
+            .build_camera_matrix()
+            .project_point3(world_pos.extend(0.0));
+        let uv = Vec2::new(ndc.x + 1.0, 1.0 - ndc.y) / 2.0;
+        self.viewport.pos + uv * self.viewport.size
+    }
+
+    /// Smallest world rect containing everything visible in the viewport.
+    pub fn visible_rect(&self) -> Rect {
+        let (min, max) = (self.viewport.min(), self.viewport.max());
+        let corners = [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)]
+            .map(|x| self.screen_to_world(x));
+        let min = corners.iter().fold(Vec2::splat(f32::MAX), |a, b| a.min(*b));
+        let max = corners.iter().fold(Vec2::splat(f32::MIN), |a, b| a.max(*b));
+        Rect::from_min_max(min, max)
+    }
 }
@@ -0,0 +1,580 @@
+use std::collections::HashSet;
+
+use glam::{Vec2, Vec3};
+use winit::event::{
+    ElementState, Event, KeyboardInput, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent,
+};
+
+use crate::{Camera, Camera2D, FrameContext, Transform};
+
+/// Pixels of touchpad scrolling counted as a mouse wheel line.
+const PIXELS_PER_LINE: f32 = 20.0;
+
+/// Moves a camera from user input. Events are fed with [`CameraController::handle_event`] and
+/// applied to the camera on [`CameraController::update`], so controllers can be driven by
+/// synthetic events.
+///
+/// ```
+/// use glam::Vec3;
+/// use tridify_rs::{Camera, CameraController, OrbitController};
+/// use winit::event::{
+///     DeviceId, Event, ModifiersState, MouseScrollDelta, TouchPhase, WindowEvent,
+/// };
+/// use winit::window::WindowId;
+///
+/// let mut camera = Camera::default();
+/// let mut orbit = OrbitController::new(Vec3::new(0.0, 0.0, -10.0), Vec3::ZERO);
+/// orbit.smoothing = 0.0;
+///
+/// #[allow(deprecated)]
+/// let scroll = Event::WindowEvent {
+///     window_id: unsafe { WindowId::dummy() },
+///     event: WindowEvent::MouseWheel {
+///         device_id: unsafe { DeviceId::dummy() },
+///         delta: MouseScrollDelta::LineDelta(0.0, 1.0),
+///         phase: TouchPhase::Moved,
+///         modifiers: ModifiersState::empty(),
+///     },
+/// };
+/// orbit.handle_event(&scroll);
+/// orbit.update(&mut camera, 1.0 / 60.0);
+/// assert!((orbit.distance - 9.0).abs() < 1e-4);
+/// assert!((orbit.eye() - Vec3::new(0.0, 0.0, -9.0)).length() < 1e-4);
+/// ```
+pub trait CameraController<C = Camera> {
+    /// Record an input event to apply on the next update.
+    fn handle_event(&mut self, event: &Event<()>);
+
+    /// Apply the recorded input and move the camera towards the result, `delta_time` seconds
+    /// after the previous update.
+    fn update(&mut self, camera: &mut C, delta_time: f32);
+
+    /// Handle the events of a frame and update the camera with its delta time.
+    fn update_frame(&mut self, camera: &mut C, frame_ctx: &FrameContext) {
+        for event in frame_ctx.input_events {
+            self.handle_event(event);
+        }
+        self.handle_event(frame_ctx.winit_event);
+        self.update(camera, frame_ctx.delta_time as f32);
+    }
+}
+
+/// Rotates around a target point with the left mouse button, pans it with the right or middle
+/// button and zooms with the mouse wheel.
+///
+/// ```
+/// use glam::Vec3;
+/// use tridify_rs::{Camera, CameraController, OrbitController};
+/// use winit::dpi::PhysicalPosition;
+/// use winit::event::{
+///     DeviceId, ElementState, Event, ModifiersState, MouseButton, MouseScrollDelta, TouchPhase,
+///     WindowEvent,
+/// };
+/// use winit::window::WindowId;
+///
+/// let mut camera = Camera::default();
+/// let mut orbit = OrbitController::new(Vec3::new(0.0, 0.0, -10.0), Vec3::ZERO);
+/// orbit.smoothing = 0.0;
+/// (orbit.min_pitch, orbit.max_pitch) = (-0.25, 0.5);
+/// (orbit.min_distance, orbit.max_distance) = (2.0, 20.0);
+///
+/// let device_id = unsafe { DeviceId::dummy() };
+/// #[allow(deprecated)]
+/// let (press, moved, scroll) = (
+///     WindowEvent::MouseInput {
+///         device_id,
+///         state: ElementState::Pressed,
+///         button: MouseButton::Left,
+///         modifiers: ModifiersState::empty(),
+///     },
+///     |y| WindowEvent::CursorMoved {
+///         device_id,
+///         position: PhysicalPosition::new(0.0, y),
+///         modifiers: ModifiersState::empty(),
+///     },
+///     |lines| WindowEvent::MouseWheel {
+///         device_id,
+///         delta: MouseScrollDelta::LineDelta(0.0, lines),
+///         phase: TouchPhase::Moved,
+///         modifiers: ModifiersState::empty(),
+///     },
+/// );
+/// let mut send = |event| {
+///     orbit.handle_event(&Event::WindowEvent {
+///         window_id: unsafe { WindowId::dummy() },
+///         event,
+///     })
+/// };
+/// //Dragging far down and zooming far out stop at the maximum pitch and distance.
+/// send(press);
+/// send(moved(0.0));
+/// send(moved(1000.0));
+/// send(scroll(-100.0));
+/// orbit.update(&mut camera, 1.0 / 60.0);
+/// assert_eq!((orbit.pitch, orbit.distance), (0.5, 20.0));
+/// assert!((orbit.eye().y - 20.0 * 0.5f32.sin()).abs() < 1e-4);
+///
+/// let mut send = |event| {
+///     orbit.handle_event(&Event::WindowEvent {
+///         window_id: unsafe { WindowId::dummy() },
+///         event,
+///     })
+/// };
+/// send(moved(-1000.0));
+/// send(scroll(100.0));
+/// orbit.update(&mut camera, 1.0 / 60.0);
+/// assert_eq!((orbit.pitch, orbit.distance), (-0.25, 2.0));
+/// ```
+pub struct OrbitController {
+    /// Point looked at.
+    pub target: Vec3,
+    /// Distance from the target to the camera.
+    pub distance: f32,
+    /// Rotation around the Y axis in radians. At 0 the camera looks towards +Z.
+    pub yaw: f32,
+    /// Elevation in radians, positive values look down on the target.
+    pub pitch: f32,
+    /// Radians rotated per dragged pixel.
+    pub rotate_speed: f32,
+    /// Fraction of the distance panned per dragged pixel.
+    pub pan_speed: f32,
+    /// Fraction of the distance zoomed per mouse wheel line.
+    pub zoom_speed: f32,
+    /// Seconds taken to cover most of the way to the input position, 0 to move instantly.
+    pub smoothing: f32,
+    pub min_pitch: f32,
+    pub max_pitch: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    current: OrbitState,
+    input: ControllerInput,
+}
+
+#[derive(Clone, Copy)]
+struct OrbitState {
+    target: Vec3,
+    distance: f32,
+    yaw: f32,
+    pitch: f32,
+}
+
+impl OrbitController {
+    /// Orbit around `target` starting from the camera position `eye`.
+    pub fn new(eye: Vec3, target: Vec3) -> Self {
+        let offset = eye - target;
+        let distance = offset.length();
+        let dir = offset.normalize_or_zero();
+        let state = OrbitState {
+            target,
+            distance,
+            yaw: dir.x.atan2(-dir.z),
+            pitch: dir.y.asin(),
+        };
+        Self {
+            target,
+            distance,
+            yaw: state.yaw,
+            pitch: state.pitch,
+            rotate_speed: 0.005,
+            pan_speed: 0.002,
+            zoom_speed: 0.1,
+            smoothing: 0.05,
+            min_pitch: -89f32.to_radians(),
+            max_pitch: 89f32.to_radians(),
+            min_distance: 0.1,
+            max_distance: 1000.0,
+            current: state,
+            input: ControllerInput::default(),
+        }
+    }
+
+    /// Current camera position, including smoothing.
+    pub fn eye(&self) -> Vec3 {
+        let OrbitState {
+            target,
+            distance,
+            yaw,
+            pitch,
+        } = self.current;
+        target + orbit_offset(yaw, pitch) * distance
+    }
+}
+
+impl CameraController for OrbitController {
+    fn handle_event(&mut self, event: &Event<()>) { self.input.handle_event(event); }
+
+    fn update(&mut self, camera: &mut Camera, delta_time: f32) {
+        let (drag, scroll) = self.input.take();
+        if self.input.is_pressed(MouseButton::Left) {
+            self.yaw -= drag.x * self.rotate_speed;
+            self.pitch += drag.y * self.rotate_speed;
+        } else if self.input.is_pressed(MouseButton::Right)
+            || self.input.is_pressed(MouseButton::Middle)
+        {
+            let forward = -orbit_offset(self.yaw, self.pitch);
+            let right = Vec3::Y.cross(forward).normalize_or_zero();
+            let up = forward.cross(right);
+            self.target += (up * drag.y - right * drag.x) * self.pan_speed * self.distance;
+        }
+        self.distance *= (1.0 - self.zoom_speed).powf(scroll);
+        self.pitch = self.pitch.clamp(self.min_pitch, self.max_pitch);
+        self.distance = self.distance.clamp(self.min_distance, self.max_distance);
+
+        let t = smoothing_factor(self.smoothing, delta_time);
+        let current = &mut self.current;
+        current.target = current.target.lerp(self.target, t);
+        current.distance += (self.distance - current.distance) * t;
+        current.yaw += (self.yaw - current.yaw) * t;
+        current.pitch += (self.pitch - current.pitch) * t;
+        camera.view = Transform::from_look_at(self.eye(), self.current.target, Vec3::Y);
+    }
+}
+
+/// Moves freely with WASD, goes down and up with Q and E and looks around dragging with the right
+/// mouse button. Holding shift moves faster.
+///
+/// ```
+/// use glam::Vec3;
+/// use tridify_rs::{Camera, CameraController, FlyController};
+/// use winit::event::{
+///     DeviceId, ElementState, Event, KeyboardInput, ModifiersState, VirtualKeyCode, WindowEvent,
+/// };
+/// use winit::window::WindowId;
+///
+/// #[allow(deprecated)]
+/// fn key(key: VirtualKeyCode, state: ElementState) -> Event<'static, ()> {
+///     Event::WindowEvent {
+///         window_id: unsafe { WindowId::dummy() },
+///         event: WindowEvent::KeyboardInput {
+///             device_id: unsafe { DeviceId::dummy() },
+///             input: KeyboardInput {
+///                 scancode: 0,
+///                 state,
+///                 virtual_keycode: Some(key),
+///                 modifiers: ModifiersState::empty(),
+///             },
+///             is_synthetic: false,
+///         },
+///     }
+/// }
+///
+/// let mut camera = Camera::default();
+/// let mut fly = FlyController::new(Vec3::ZERO, Vec3::Z);
+/// fly.smoothing = 0.0;
+/// fly.handle_event(&key(VirtualKeyCode::W, ElementState::Pressed));
+/// fly.handle_event(&key(VirtualKeyCode::D, ElementState::Pressed));
+/// fly.update(&mut camera, 1.0);
+/// //Moving diagonally is as fast as moving along a single axis.
+/// assert!(fly.eye().abs_diff_eq(Vec3::new(1.0, 0.0, 1.0).normalize() * 5.0, 1e-5));
+///
+/// fly.handle_event(&key(VirtualKeyCode::W, ElementState::Released));
+/// fly.handle_event(&key(VirtualKeyCode::D, ElementState::Released));
+/// fly.handle_event(&key(VirtualKeyCode::Q, ElementState::Pressed));
+/// fly.handle_event(&key(VirtualKeyCode::LShift, ElementState::Pressed));
+/// fly.position = Vec3::ZERO;
+/// fly.update(&mut camera, 0.5);
+/// assert!(fly.eye().abs_diff_eq(Vec3::new(0.0, -10.0, 0.0), 1e-5));
+///
+/// //Keys held when the window loses focus never get their release event.
+/// fly.handle_event(&Event::WindowEvent {
+///     window_id: unsafe { WindowId::dummy() },
+///     event: WindowEvent::Focused(false),
+/// });
+/// fly.update(&mut camera, 1.0);
+/// assert!(fly.eye().abs_diff_eq(Vec3::new(0.0, -10.0, 0.0), 1e-5));
+/// ```
+pub struct FlyController {
+    pub position: Vec3,
+    /// Rotation around the Y axis in radians. At 0 the camera looks towards +Z.
+    pub yaw: f32,
+    /// Elevation in radians, positive values look up.
+    pub pitch: f32,
+    /// World units moved per second.
+    pub move_speed: f32,
+    /// Factor applied to the move speed while shift is held.
+    pub boost: f32,
+    /// Radians rotated per dragged pixel.
+    pub look_speed: f32,
+    /// Seconds taken to cover most of the way to the input position, 0 to move instantly.
+    pub smoothing: f32,
+    pub min_pitch: f32,
+    pub max_pitch: f32,
+    current: FlyState,
+    input: ControllerInput,
+}
+
+#[derive(Clone, Copy)]
+struct FlyState {
+    position: Vec3,
+    yaw: f32,
+    pitch: f32,
+}
+
+impl FlyController {
+    /// Fly from the camera position `eye` looking at `target`.
+    pub fn new(eye: Vec3, target: Vec3) -> Self {
+        let dir = (target - eye).normalize_or_zero();
+        let state = FlyState {
+            position: eye,
+            yaw: dir.x.atan2(dir.z),
+            pitch: dir.y.asin(),
+        };
+        Self {
+            position: eye,
+            yaw: state.yaw,
+            pitch: state.pitch,
+            move_speed: 5.0,
+            boost: 4.0,
+            look_speed: 0.003,
+            smoothing: 0.05,
+            min_pitch: -89f32.to_radians(),
+            max_pitch: 89f32.to_radians(),
+            current: state,
+            input: ControllerInput::default(),
+        }
+    }
+
+    /// Current camera position, including smoothing.
+    pub fn eye(&self) -> Vec3 { self.current.position }
+}
+
+impl CameraController for FlyController {
+    fn handle_event(&mut self, event: &Event<()>) { self.input.handle_event(event); }
+
+    fn update(&mut self, camera: &mut Camera, delta_time: f32) {
+        let (drag, _) = self.input.take();
+        if self.input.is_pressed(MouseButton::Right) {
+            self.yaw += drag.x * self.look_speed;
+            self.pitch -= drag.y * self.look_speed;
+        }
+        self.pitch = self.pitch.clamp(self.min_pitch, self.max_pitch);
+
+        let forward = fly_forward(self.yaw, self.pitch);
+        let right = Vec3::Y.cross(forward).normalize_or_zero();
+        let axis = |positive, negative| {
+            self.input.is_key_down(positive) as i32 as f32
+                - self.input.is_key_down(negative) as i32 as f32
+        };
+        let direction = forward * axis(VirtualKeyCode::W, VirtualKeyCode::S)
+            + right * axis(VirtualKeyCode::D, VirtualKeyCode::A)
+            + Vec3::Y * axis(VirtualKeyCode::E, VirtualKeyCode::Q);
+        let boosted = self.input.is_key_down(VirtualKeyCode::LShift)
+            || self.input.is_key_down(VirtualKeyCode::RShift);
+        let speed = self.move_speed * if boosted { self.boost } else { 1.0 };
+        self.position += direction.normalize_or_zero() * speed * delta_time;
+
+        let t = smoothing_factor(self.smoothing, delta_time);
+        let current = &mut self.current;
+        current.position = current.position.lerp(self.position, t);
+        current.yaw += (self.yaw - current.yaw) * t;
+        current.pitch += (self.pitch - current.pitch) * t;
+        camera.view = Transform::from_look_to(
+            current.position,
+            fly_forward(current.yaw, current.pitch),
+            Vec3::Y,
+        );
+    }
+}
+
+/// Pans a [`Camera2D`] dragging with the left or middle mouse button and zooms towards the cursor
+/// with the mouse wheel.
+///
+/// ```
+/// use glam::Vec2;
+/// use tridify_rs::{Camera2D, CameraController, PanZoomController, Rect};
+/// use winit::dpi::PhysicalPosition;
+/// use winit::event::{DeviceId, Event, ModifiersState, MouseScrollDelta, TouchPhase, WindowEvent};
+/// use winit::window::WindowId;
+///
+/// let mut camera = Camera2D::new(Rect::new(Vec2::ZERO, Vec2::new(800.0, 600.0)));
+/// let mut controller = PanZoomController::new(&camera);
+/// controller.smoothing = 0.0;
+/// controller.zoom_speed = 2.0;
+/// controller.max_zoom = 8.0;
+///
+/// let device_id = unsafe { DeviceId::dummy() };
+/// #[allow(deprecated)]
+/// let (moved, scroll) = (
+///     WindowEvent::CursorMoved {
+///         device_id,
+///         position: PhysicalPosition::new(600.0, 300.0),
+///         modifiers: ModifiersState::empty(),
+///     },
+///     |lines| WindowEvent::MouseWheel {
+///         device_id,
+///         delta: MouseScrollDelta::LineDelta(0.0, lines),
+///         phase: TouchPhase::Moved,
+///         modifiers: ModifiersState::empty(),
+///     },
+/// );
+/// let window = |event| Event::WindowEvent {
+///     window_id: unsafe { WindowId::dummy() },
+///     event,
+/// };
+/// let cursor = Vec2::new(600.0, 300.0);
+/// let anchor = camera.screen_to_world(cursor);
+/// controller.handle_event(&window(moved));
+/// controller.handle_event(&window(scroll(1.0)));
+/// controller.update(&mut camera, 1.0 / 60.0);
+/// //The world point below the cursor stays in place.
+/// assert!((camera.zoom - 2.0).abs() < 1e-5);
+/// assert!(camera.screen_to_world(cursor).abs_diff_eq(anchor, 1e-3));
+///
+/// controller.handle_event(&window(scroll(10.0)));
+/// controller.update(&mut camera, 1.0 / 60.0);
+/// assert!((camera.zoom - 8.0).abs() < 1e-5);
+/// assert!(camera.screen_to_world(cursor).abs_diff_eq(anchor, 1e-3));
+/// ```
+pub struct PanZoomController {
+    /// World position shown at the center of the viewport.
+    pub position: Vec2,
+    /// Pixels per world unit.
+    pub zoom: f32,
+    /// Factor applied to the zoom per mouse wheel line.
+    pub zoom_speed: f32,
+    /// Seconds taken to cover most of the way to the input position, 0 to move instantly.
+    pub smoothing: f32,
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+    input: ControllerInput,
+}
+
+impl PanZoomController {
+    /// Start from the current position and zoom of the camera.
+    pub fn new(camera: &Camera2D) -> Self {
+        Self {
+            position: camera.position,
+            zoom: camera.zoom,
+            zoom_speed: 1.1,
+            smoothing: 0.05,
+            min_zoom: 0.01,
+            max_zoom: 100.0,
+            input: ControllerInput::default(),
+        }
+    }
+}
+
+impl CameraController<Camera2D> for PanZoomController {
+    fn handle_event(&mut self, event: &Event<()>) { self.input.handle_event(event); }
+
+    fn update(&mut self, camera: &mut Camera2D, delta_time: f32) {
+        let (drag, scroll) = self.input.take();
+        let mut target = Camera2D {
+            position: self.position,
+            zoom: self.zoom,
+            ..*camera
+        };
+        if self.input.is_pressed(MouseButton::Left) || self.input.is_pressed(MouseButton::Middle) {
+            target.pan(drag);
+        }
+        let zoom = (target.zoom * self.zoom_speed.powf(scroll)).clamp(self.min_zoom, self.max_zoom);
+        match self.input.cursor {
+            Some(cursor) => target.zoom_at(cursor, zoom / target.zoom),
+            None => target.zoom = zoom,
+        }
+        self.position = target.position;
+        self.zoom = target.zoom;
+
+        //Zoom is interpolated in log space so zooming in and out feel the same.
+        let t = smoothing_factor(self.smoothing, delta_time);
+        camera.position = camera.position.lerp(self.position, t);
+        camera.zoom *= (self.zoom / camera.zoom).powf(t);
+    }
+}
+
+/// Input state shared by the controllers.
+#[derive(Default)]
+struct ControllerInput {
+    /// Cursor position in pixels from the top left corner of the window.
+    cursor: Option<Vec2>,
+    /// Cursor movement since the last update.
+    drag: Vec2,
+    /// Mouse wheel lines since the last update, positive away from the user.
+    scroll: f32,
+    buttons: HashSet<MouseButton>,
+    keys: HashSet<VirtualKeyCode>,
+}
+
+impl ControllerInput {
+    fn handle_event(&mut self, event: &Event<()>) {
+        let Event::WindowEvent { event, .. } = event else {
+            return;
+        };
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                let position = Vec2::new(position.x as f32, position.y as f32);
+                if let Some(cursor) = self.cursor {
+                    self.drag += position - cursor;
+                }
+                self.cursor = Some(position);
+            }
+            WindowEvent::CursorLeft { .. } => self.cursor = None,
+            WindowEvent::MouseWheel { delta, .. } => {
+                self.scroll += match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    MouseScrollDelta::PixelDelta(pos) => pos.y as f32 / PIXELS_PER_LINE,
+                }
+            }
+            WindowEvent::MouseInput { state, button, .. } => match state {
+                ElementState::Pressed => _ = self.buttons.insert(*button),
+                ElementState::Released => _ = self.buttons.remove(button),
+            },
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state,
+                        virtual_keycode: Some(key),
+                        ..
+                    },
+                ..
+            } => match state {
+                ElementState::Pressed => _ = self.keys.insert(*key),
+                ElementState::Released => _ = self.keys.remove(key),
+            },
+            //Releases are not received while the window is unfocused.
+            WindowEvent::Focused(false) => {
+                self.buttons.clear();
+                self.keys.clear();
+            }
+            _ => {}
+        }
+    }
+
+    /// Return the drag and scroll accumulated since the last call.
+    fn take(&mut self) -> (Vec2, f32) {
+        (
+            std::mem::take(&mut self.drag),
+            std::mem::take(&mut self.scroll),
+        )
+    }
+
+    fn is_pressed(&self, button: MouseButton) -> bool { self.buttons.contains(&button) }
+    fn is_key_down(&self, key: VirtualKeyCode) -> bool { self.keys.contains(&key) }
+}
+
+/// Interpolation factor covering most of the distance in `smoothing` seconds regardless of the
+/// frame rate.
+fn smoothing_factor(smoothing: f32, delta_time: f32) -> f32 {
+    if smoothing <= 0.0 {
+        1.0
+    } else {
+        1.0 - (-delta_time / smoothing).exp()
+    }
+}
+
+/// Direction from the orbit target to the camera.
+fn orbit_offset(yaw: f32, pitch: f32) -> Vec3 {
+    Vec3::new(
+        pitch.cos() * yaw.sin(),
+        pitch.sin(),
+        -pitch.cos() * yaw.cos(),
+    )
+}
+
+fn fly_forward(yaw: f32, pitch: f32) -> Vec3 {
+    Vec3::new(
+        pitch.cos() * yaw.sin(),
+        pitch.sin(),
+        pitch.cos() * yaw.cos(),
+    )
+}
@@ -6,12 +6,12 @@ use bytemuck::{Pod, Zeroable};
 ///
 /// #Examples
 /// ``` rust
-/// use ldrawy::Color;
+/// use tridify_rs::Color;
 /// let white_color = Color::WHITE;
 /// let white_color = Color::new(1.0, 1.0, 1.0, 1.0);
 /// ```
 #[repr(C)]
-#[derive(Clone, Copy, Debug, Default, Pod, Zeroable)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
 pub struct Color {
     pub r: f32,
     pub g: f32,
@@ -1,13 +1,261 @@
 use glam::*;
 
-#[derive(Default)]
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
 pub struct Rect {
     /// Bottom left from the rect
     pub pos: Vec2,
     pub size: Vec2,
 }
 impl Rect {
+    /// Rect covering the whole texture in UV coordinates.
+    pub const UNIT: Rect = Rect {
+        pos: Vec2::ZERO,
+        size: Vec2::ONE,
+    };
+
     pub fn new(pos: Vec2, size: Vec2) -> Self { Self { pos, size } }
     pub fn from_min_max(min: Vec2, max: Vec2) -> Self { Self::new(min, max - min) }
     pub fn center(&self) -> Vec2 { self.pos + self.size / 2.0 }
+    pub fn min(&self) -> Vec2 { self.pos }
+    pub fn max(&self) -> Vec2 { self.pos + self.size }
+}
+
+/// Axis-aligned bounding box.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self { Self { min, max } }
+
+    /// Smallest box containing all points, or `None` when there are no points.
+    pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Self::new(first, first), |aabb, x| {
+            Self::new(aabb.min.min(x), aabb.max.max(x))
+        }))
+    }
+
+    pub fn center(&self) -> Vec3 { (self.min + self.max) / 2.0 }
+    pub fn size(&self) -> Vec3 { self.max - self.min }
+    /// Half the size of the box on each axis.
+    pub fn extents(&self) -> Vec3 { self.size() / 2.0 }
+
+    /// Smallest box containing both boxes.
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Self::new(self.min.min(other.min), self.max.max(other.max))
+    }
+
+    pub fn contains(&self, point: Vec3) -> bool {
+        point.cmpge(self.min).all() && point.cmple(self.max).all()
+    }
+
+    /// Smallest box containing this box transformed by the matrix.
+    pub fn transformed(&self, matrix: &Mat4) -> Aabb {
+        let center = matrix.transform_point3(self.center());
+        let extents = self.extents();
+        //Each axis of the new box is the sum of the absolute projections of the old extents.
+        let half = Vec3::new(
+            matrix.row(0).truncate().abs().dot(extents),
+            matrix.row(1).truncate().abs().dot(extents),
+            matrix.row(2).truncate().abs().dot(extents),
+        );
+        Self::new(center - half, center + half)
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+impl BoundingSphere {
+    pub fn new(center: Vec3, radius: f32) -> Self { Self { center, radius } }
+}
+
+/// Volume visible by a camera, as six planes with their normals pointing inside. Planes are
+/// stored as the normal in `xyz` and the distance to the origin in `w`.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct Frustum {
+    /// Left, right, bottom, top, near and far planes. Near and far are swapped for reverse-Z.
+    pub planes: [Vec4; 6],
+}
+impl Frustum {
+    /// Extract the planes from a view projection matrix with depth from 0 to 1, see
+    /// [`crate::Camera::build_camera_matrix`]. Reverse-Z matrices swap the near and far planes
+    /// and infinite ones have a far plane containing everything.
+    pub fn from_matrix(matrix: &Mat4) -> Self {
+        let (x, y, z, w) = (matrix.row(0), matrix.row(1), matrix.row(2), matrix.row(3));
+        let planes = [w + x, w - x, w + y, w - y, z, w - z].map(|plane| {
+            let length = plane.truncate().length();
+            match length > 0.0 {
+                true => plane / length,
+                false => plane,
+            }
+        });
+        Self { planes }
+    }
+
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.truncate().dot(point) + plane.w >= 0.0)
+    }
+
+    /// Whether the sphere is at least partially inside.
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.truncate().dot(sphere.center) + plane.w >= -sphere.radius)
+    }
+
+    /// Whether the box is at least partially inside. Boxes close to the frustum corners may be
+    /// reported as inside while being outside.
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            //Corner furthest along the plane normal.
+            let normal = plane.truncate();
+            let corner = Vec3::select(normal.cmpge(Vec3::ZERO), aabb.max, aabb.min);
+            normal.dot(corner) + plane.w >= 0.0
+        })
+    }
+}
+
+/// Half-line starting at `origin` going towards `direction`. Distances along the ray are measured
+/// in multiples of the direction length.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3,
+}
+impl Ray {
+    /// Ray with its direction normalized, so distances along it are in world units.
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize_or_zero(),
+        }
+    }
+
+    /// Point at the given distance along the ray.
+    pub fn at(&self, distance: f32) -> Vec3 { self.origin + self.direction * distance }
+
+    /// Ray transformed by the matrix. The direction is not normalized, so distances along the
+    /// transformed ray match the ones along the original ray. Useful to test against meshes in
+    /// their local space using the inverse of their model matrix.
+    pub fn transformed(&self, matrix: &Mat4) -> Self {
+        Self {
+            origin: matrix.transform_point3(self.origin),
+            direction: matrix.transform_vector3(self.direction),
+        }
+    }
+
+    /// Distance to the first point of the box hit by the ray, 0 when the origin is inside.
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        let (mut near, mut far) = (0.0f32, f32::INFINITY);
+        for axis in 0..3 {
+            let (origin, direction) = (self.origin[axis], self.direction[axis]);
+            let (min, max) = (aabb.min[axis], aabb.max[axis]);
+            if direction == 0.0 {
+                //Parallel to the slab, either always inside it or never.
+                if origin < min || origin > max {
+                    return None;
+                }
+                continue;
+            }
+            let t0 = (min - origin) / direction;
+            let t1 = (max - origin) / direction;
+            near = near.max(t0.min(t1));
+            far = far.min(t0.max(t1));
+        }
+        (near <= far).then_some(near)
+    }
+
+    /// Distance to the first point of the sphere hit by the ray, 0 when the origin is inside.
+    ///
+    /// ```
+    /// use glam::Vec3;
+    /// use tridify_rs::{BoundingSphere, Ray};
+    ///
+    /// let sphere = BoundingSphere::new(Vec3::new(0.0, 0.0, 10.0), 2.0);
+    /// assert_eq!(Ray::new(Vec3::ZERO, Vec3::Z).intersect_sphere(&sphere), Some(8.0));
+    /// assert_eq!(Ray::new(Vec3::ZERO, Vec3::X).intersect_sphere(&sphere), None);
+    /// //Spheres behind the origin are missed.
+    /// assert_eq!(Ray::new(Vec3::ZERO, -Vec3::Z).intersect_sphere(&sphere), None);
+    /// let inside = Ray::new(Vec3::new(0.0, 1.0, 9.0), Vec3::X);
+    /// assert_eq!(inside.intersect_sphere(&sphere), Some(0.0));
+    /// ```
+    pub fn intersect_sphere(&self, sphere: &BoundingSphere) -> Option<f32> {
+        let offset = self.origin - sphere.center;
+        let a = self.direction.length_squared();
+        let b = offset.dot(self.direction);
+        let c = offset.length_squared() - sphere.radius * sphere.radius;
+        let discriminant = b * b - a * c;
+        if discriminant < 0.0 || a == 0.0 {
+            return None;
+        }
+        let far = (-b + discriminant.sqrt()) / a;
+        let near = (-b - discriminant.sqrt()) / a;
+        (far >= 0.0).then_some(near.max(0.0))
+    }
+
+    /// Distance to the point of the triangle hit by the ray. Both faces of the triangle are hit.
+    ///
+    /// ```
+    /// use glam::Vec3;
+    /// use tridify_rs::Ray;
+    ///
+    /// let triangle = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)].map(|(x, y)| Vec3::new(x, y, 5.0));
+    /// let ray = Ray::new(Vec3::new(0.25, 0.25, 0.0), Vec3::Z);
+    /// assert_eq!(ray.intersect_triangle(triangle), Some(5.0));
+    /// let back = [triangle[0], triangle[2], triangle[1]];
+    /// assert_eq!(ray.intersect_triangle(back), Some(5.0));
+    /// assert_eq!(Ray::new(Vec3::new(1.0, 1.0, 0.0), Vec3::Z).intersect_triangle(triangle), None);
+    /// assert_eq!(Ray::new(ray.origin, -Vec3::Z).intersect_triangle(triangle), None);
+    /// //Rays along the plane of the triangle miss it.
+    /// let along = Ray::new(Vec3::new(-1.0, 0.25, 5.0), Vec3::X);
+    /// assert_eq!(along.intersect_triangle(triangle), None);
+    ///
+    /// //Small triangles are hit regardless of their scale.
+    /// let tiny = triangle.map(|x| x * Vec3::new(1e-4, 1e-4, 1.0));
+    /// let ray = Ray::new(Vec3::new(0.25e-4, 0.25e-4, 0.0), Vec3::Z);
+    /// assert_eq!(ray.intersect_triangle(tiny), Some(5.0));
+    /// ```
+    pub fn intersect_triangle(&self, triangle: [Vec3; 3]) -> Option<f32> {
+        //Möller–Trumbore intersection.
+        let edge1 = triangle[1] - triangle[0];
+        let edge2 = triangle[2] - triangle[0];
+        let p = self.direction.cross(edge2);
+        let det = edge1.dot(p);
+        //The determinant scales with the edges and the direction, so it is compared to their
+        //lengths to only reject rays almost parallel to the triangle.
+        let scale = self.direction.length() * edge1.length() * edge2.length();
+        if det.abs() <= f32::EPSILON * scale {
+            return None;
+        }
+        let offset = self.origin - triangle[0];
+        let u = offset.dot(p) / det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = offset.cross(edge1);
+        let v = self.direction.dot(q) / det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let distance = edge2.dot(q) / det;
+        (distance >= 0.0).then_some(distance)
+    }
+}
+
+/// Closest point where a ray hits a mesh, see [`crate::Mesh::raycast`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+    /// Distance along the ray.
+    pub distance: f32,
+    pub point: Vec3,
+    /// Index of the triangle hit, its vertices are at `3 * triangle` in the mesh indices.
+    pub triangle: usize,
 }
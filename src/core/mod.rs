@@ -1,10 +1,14 @@
+mod animation;
 mod camera;
+mod camera_controller;
 mod color;
 mod math;
 mod transform;
 mod window;
 
+pub use animation::*;
 pub use camera::*;
+pub use camera_controller::*;
 pub use color::*;
 pub use math::*;
 pub use transform::*;
@@ -1,6 +1,9 @@
-use glam::{Affine3A, Mat4, Quat, Vec3};
+use std::ops::Mul;
+
+use glam::{Affine3A, Mat4, Quat, Vec3, Vec3A};
 
 /// Representation for position, rotation and scale.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Transform {
     affine: Affine3A,
 }
@@ -29,8 +32,235 @@ impl Transform {
         }
     }
 
+    /// Create transform from an affine matrix.
+    pub fn from_affine(affine: Affine3A) -> Self { Self { affine } }
+
     pub fn build_matrix(&self) -> Mat4 { Mat4::from(self.affine) }
+    pub fn affine(&self) -> Affine3A { self.affine }
+
+    pub fn position(&self) -> Vec3 { self.affine.translation.into() }
+    /// Rotation of the transform. Transforms with shear return an approximation.
+    pub fn rotation(&self) -> Quat { self.decompose().1 }
+    pub fn scale(&self) -> Vec3 { self.decompose().0 }
+
+    pub fn set_position(&mut self, position: Vec3) { self.affine.translation = position.into(); }
+
+    /// Replace the rotation, keeping position and scale.
+    ///
+    /// ```
+    /// use glam::{Quat, Vec3};
+    /// use tridify_rs::Transform;
+    ///
+    /// let scale = Vec3::new(1.0, 2.0, 3.0);
+    /// let mut transform = Transform::new(Vec3::X, Quat::from_rotation_x(0.3), scale);
+    /// let rotation = Quat::from_rotation_z(1.2);
+    /// transform.set_rotation(rotation);
+    /// assert!(transform.rotation().abs_diff_eq(rotation, 1e-5));
+    /// assert!(transform.scale().abs_diff_eq(scale, 1e-5));
+    /// assert_eq!(transform.position(), Vec3::X);
+    /// ```
+    pub fn set_rotation(&mut self, rotation: Quat) {
+        let (scale, _, position) = self.decompose();
+        *self = Self::new(position, rotation, scale);
+    }
+
+    /// Replace the scale, keeping position and rotation.
+    ///
+    /// ```
+    /// use glam::{EulerRot, Quat, Vec3};
+    /// use tridify_rs::Transform;
+    ///
+    /// let rotation = Quat::from_euler(EulerRot::YXZ, 0.5, 0.3, 0.1);
+    /// let mut transform = Transform::new(Vec3::Y, rotation, Vec3::ONE);
+    /// transform.set_scale(Vec3::new(0.5, 2.0, 4.0));
+    /// assert!(transform.scale().abs_diff_eq(Vec3::new(0.5, 2.0, 4.0), 1e-5));
+    /// assert!(transform.rotation().abs_diff_eq(rotation, 1e-5));
+    /// transform.set_scale(Vec3::ONE);
+    /// assert!(transform.scale().abs_diff_eq(Vec3::ONE, 1e-5));
+    /// assert!(transform.rotation().abs_diff_eq(rotation, 1e-5));
+    /// assert_eq!(transform.position(), Vec3::Y);
+    /// ```
+    pub fn set_scale(&mut self, scale: Vec3) {
+        let (_, rotation, position) = self.decompose();
+        *self = Self::new(position, rotation, scale);
+    }
+
+    /// Move the transform in world space.
+    ///
+    /// ```
+    /// use glam::{Quat, Vec3};
+    /// use tridify_rs::Transform;
+    ///
+    /// let mut transform = Transform::new(Vec3::X, Quat::from_rotation_y(1.0), Vec3::ONE);
+    /// transform.translate(Vec3::Y);
+    /// assert_eq!(transform.position(), Vec3::new(1.0, 1.0, 0.0));
+    /// ```
+    pub fn translate(&mut self, delta: Vec3) { self.affine.translation += Vec3A::from(delta); }
+
+    /// Rotate the transform around its position, applying the rotation after the current one.
+    ///
+    /// ```
+    /// use glam::{Quat, Vec3};
+    /// use tridify_rs::Transform;
+    ///
+    /// let mut transform = Transform::from_pos(Vec3::X);
+    /// transform.rotate(Quat::from_rotation_y(std::f32::consts::FRAC_PI_2));
+    /// assert_eq!(transform.position(), Vec3::X);
+    /// assert!(transform.forward().abs_diff_eq(Vec3::X, 1e-6));
+    /// ```
+    pub fn rotate(&mut self, rotation: Quat) {
+        let (scale, current, position) = self.decompose();
+        *self = Self::new(position, (rotation * current).normalize(), scale);
+    }
+
+    /// Rotate the transform so its forward vector points at `target`, keeping its position and
+    /// scale. Used for objects, camera views are built with [`Transform::from_look_at`].
+    ///
+    /// ```
+    /// use glam::Vec3;
+    /// use tridify_rs::Transform;
+    ///
+    /// let mut transform = Transform::from_pos(Vec3::new(0.0, 0.0, -5.0));
+    /// transform.look_at(Vec3::new(5.0, 0.0, -5.0), Vec3::Y);
+    /// assert!(transform.forward().abs_diff_eq(Vec3::X, 1e-6));
+    /// assert!(transform.up().abs_diff_eq(Vec3::Y, 1e-6));
+    /// ```
+    pub fn look_at(&mut self, target: Vec3, up: Vec3) {
+        let (scale, _, position) = self.decompose();
+        //The inverse of a view matrix places an object looking towards the same direction.
+        let view = Affine3A::look_at_lh(position, target, up);
+        let rotation = Quat::from_affine3(&view).inverse();
+        *self = Self::new(position, rotation, scale);
+    }
+
+    /// Local X axis in world space. Not affected by scale.
+    ///
+    /// ```
+    /// use glam::{Quat, Vec3};
+    /// use tridify_rs::Transform;
+    ///
+    /// let rotation = Quat::from_rotation_y(std::f32::consts::FRAC_PI_2);
+    /// let transform = Transform::new(Vec3::ONE, rotation, Vec3::new(3.0, 2.0, 1.0));
+    /// assert!(transform.right().abs_diff_eq(-Vec3::Z, 1e-6));
+    /// assert!(transform.up().abs_diff_eq(Vec3::Y, 1e-6));
+    /// assert!(transform.forward().abs_diff_eq(Vec3::X, 1e-6));
+    /// ```
+    pub fn right(&self) -> Vec3 { self.rotation() * Vec3::X }
+    /// Local Y axis in world space.
+    pub fn up(&self) -> Vec3 { self.rotation() * Vec3::Y }
+    /// Local Z axis in world space.
+    pub fn forward(&self) -> Vec3 { self.rotation() * Vec3::Z }
+
+    /// Transform undoing this one.
+    ///
+    /// ```
+    /// use glam::{Quat, Vec3};
+    /// use tridify_rs::Transform;
+    ///
+    /// let transform = Transform::new(Vec3::X, Quat::from_rotation_z(0.5), Vec3::splat(2.0));
+    /// let point = Vec3::new(1.0, 2.0, 3.0);
+    /// let back = transform.inverse().transform_point(transform.transform_point(point));
+    /// assert!(back.abs_diff_eq(point, 1e-5));
+    /// ```
+    pub fn inverse(&self) -> Self {
+        Self {
+            affine: self.affine.inverse(),
+        }
+    }
+
+    /// Apply scale, rotation and translation to a point.
+    pub fn transform_point(&self, point: Vec3) -> Vec3 { self.affine.transform_point3(point) }
+
+    /// Apply scale and rotation to a vector, ignoring translation.
+    ///
+    /// ```
+    /// use glam::{Quat, Vec3};
+    /// use tridify_rs::Transform;
+    ///
+    /// let rotation = Quat::from_rotation_z(std::f32::consts::FRAC_PI_2);
+    /// let transform = Transform::new(Vec3::new(5.0, 0.0, 0.0), rotation, Vec3::new(2.0, 1.0, 1.0));
+    /// //Scale is applied before rotation.
+    /// assert!(transform.transform_vector(Vec3::X).abs_diff_eq(Vec3::new(0.0, 2.0, 0.0), 1e-6));
+    /// assert!(transform.transform_point(Vec3::X).abs_diff_eq(Vec3::new(5.0, 2.0, 0.0), 1e-6));
+    /// ```
+    pub fn transform_vector(&self, vector: Vec3) -> Vec3 { self.affine.transform_vector3(vector) }
+
+    /// Interpolate position, rotation and scale linearly. Rotation is normalized after
+    /// interpolating, which is faster than [`Transform::slerp`] but doesn't keep a constant
+    /// angular speed. Rotations take the shortest path.
+    ///
+    /// ```
+    /// use std::f32::consts::PI;
+    ///
+    /// use glam::{Quat, Vec3};
+    /// use tridify_rs::Transform;
+    ///
+    /// let a = Transform::new(Vec3::ZERO, Quat::from_rotation_y(PI * 0.9), Vec3::ONE);
+    /// let b = Transform::new(Vec3::new(2.0, 4.0, 0.0), Quat::from_rotation_y(-PI * 0.9), Vec3::splat(3.0));
+    /// let middle = a.lerp(&b, 0.5);
+    /// assert!(middle.position().abs_diff_eq(Vec3::new(1.0, 2.0, 0.0), 1e-6));
+    /// assert!(middle.scale().abs_diff_eq(Vec3::splat(2.0), 1e-5));
+    /// //Both rotations are 36 degrees apart through the back, not 324 degrees through the front.
+    /// assert!(middle.forward().abs_diff_eq(-Vec3::Z, 1e-5));
+    /// assert!(a.lerp(&b, 0.0).rotation().abs_diff_eq(a.rotation(), 1e-6));
+    /// ```
+    pub fn lerp(&self, other: &Transform, t: f32) -> Self {
+        let (scale, rotation, position) = self.decompose();
+        let (other_scale, other_rotation, other_position) = other.decompose();
+        Self::new(
+            position.lerp(other_position, t),
+            rotation.lerp(other_rotation, t),
+            scale.lerp(other_scale, t),
+        )
+    }
+
+    /// Interpolate position and scale linearly and rotation spherically.
+    ///
+    /// ```
+    /// use glam::{Quat, Vec3};
+    /// use tridify_rs::Transform;
+    ///
+    /// let a = Transform::default();
+    /// let b = Transform::new(Vec3::new(2.0, 0.0, 0.0), Quat::from_rotation_y(1.0), Vec3::ONE);
+    /// let middle = a.slerp(&b, 0.5);
+    /// assert!(middle.position().abs_diff_eq(Vec3::X, 1e-6));
+    /// assert!(middle.rotation().abs_diff_eq(Quat::from_rotation_y(0.5), 1e-6));
+    /// ```
+    pub fn slerp(&self, other: &Transform, t: f32) -> Self {
+        let (scale, rotation, position) = self.decompose();
+        let (other_scale, other_rotation, other_position) = other.decompose();
+        Self::new(
+            position.lerp(other_position, t),
+            rotation.slerp(other_rotation, t),
+            scale.lerp(other_scale, t),
+        )
+    }
+
+    fn decompose(&self) -> (Vec3, Quat, Vec3) { self.affine.to_scale_rotation_translation() }
 }
 impl Default for Transform {
     fn default() -> Self { Transform::new(Vec3::ZERO, Quat::IDENTITY, Vec3::ONE) }
 }
+
+/// Combine two transforms, applying `rhs` first. A child transform multiplied by its parent on
+/// the left gives its world transform.
+///
+/// ```
+/// use glam::{Quat, Vec3};
+/// use tridify_rs::Transform;
+///
+/// let parent = Transform::new(Vec3::X, Quat::IDENTITY, Vec3::splat(2.0));
+/// let child = Transform::from_pos(Vec3::Y);
+/// let world = parent * child;
+/// assert_eq!(world.position(), Vec3::new(1.0, 2.0, 0.0));
+/// assert_eq!(world.scale(), Vec3::splat(2.0));
+/// ```
+impl Mul for Transform {
+    type Output = Transform;
+
+    fn mul(self, rhs: Transform) -> Transform {
+        Self {
+            affine: self.affine * rhs.affine,
+        }
+    }
+}
@@ -1,22 +1,24 @@
-use std::{
-    error::Error,
-    time::{Duration, Instant},
-};
+use std::time::{Duration, Instant};
 
+#[cfg(feature = "egui")]
 use egui::Context;
 use glam::UVec2;
 
-use winit::dpi::LogicalSize;
+use winit::{dpi::LogicalSize, event::Event};
 
-use crate::{FrameContext, RenderOptions, RenderPass, RenderPassBuilder};
+use crate::{ComputePassBuilder, FrameContext, RenderPassBuilder};
 
 #[cfg(feature = "egui")]
 use crate::EguiContext;
 
+type RenderLoop = Box<dyn FnMut(&mut GpuCtx, &FrameContext)>;
+
 /// Desktop window representation. Stores it's own GPU context and render loop.
 pub struct Window {
     pub(crate) ctx: GpuCtx,
-    pub(crate) user_loop: Option<Box<dyn FnMut(&mut GpuCtx, &FrameContext)>>,
+    pub(crate) user_loop: Option<RenderLoop>,
+    /// Events received since the last frame was drawn.
+    pub(crate) input_events: Vec<Event<'static, ()>>,
 }
 impl Window {
     /// Step through render loop once.
@@ -49,6 +51,8 @@ impl Window {
 pub struct GpuCtx {
     pub(crate) created_time: Instant,
     pub(crate) last_draw_time: Instant,
+    /// Start of the previous frame, used for the frame delta time.
+    pub(crate) last_frame_time: Instant,
 
     pub(crate) winit_wnd: winit::window::Window,
 
@@ -92,6 +96,9 @@ impl GpuCtx {
         )
     }
 
+    /// Create a command recorder to run compute passes outside of the frame render.
+    pub fn create_compute_builder(&self) -> ComputePassBuilder { ComputePassBuilder::new(self) }
+
     /// Time the window has been running since its creation.
     pub fn time_running(&self) -> Duration { self.created_time.elapsed() }
 
@@ -0,0 +1,217 @@
+use glam::UVec2;
+use image::RgbaImage;
+use wgpu::TextureFormat;
+
+use crate::{GpuCtx, Rect, Texture, TextureDesc, TextureSize, TextureUsage};
+
+/// Row of packed rectangles sharing the same height.
+struct Shelf {
+    y: u32,
+    height: u32,
+    next_x: u32,
+}
+
+/// Packs rectangles into a fixed area using rows (shelves). Rectangles are placed in the shelf
+/// that wastes less vertical space, opening a new shelf when none of them fits. `padding` pixels
+/// are left empty to the right of and below each rectangle, except where it would go past the
+/// edges of the area, so rectangles as large as the area still fit.
+///
+/// ```
+/// use glam::UVec2;
+/// use tridify_rs::ShelfPacker;
+///
+/// let mut packer = ShelfPacker::new(UVec2::new(64, 64), 2);
+/// let sizes = (0..40).map(|i| UVec2::new(3 + i * 7 % 11, 2 + i * 5 % 9)).collect::<Vec<_>>();
+/// let rects = sizes
+///     .iter()
+///     .filter_map(|size| Some((packer.pack(*size)?, *size)))
+///     .collect::<Vec<_>>();
+/// assert!(rects.len() > 20);
+/// for (i, (pos, size)) in rects.iter().enumerate() {
+///     assert!((*pos + *size).cmple(UVec2::splat(64)).all());
+///     //Rectangles grown by the padding don't overlap.
+///     for (other, other_size) in &rects[i + 1..] {
+///         let apart = (*pos + *size + 2).cmple(*other) | (*other + *other_size + 2).cmple(*pos);
+///         assert!(apart.any());
+///     }
+/// }
+/// ```
+pub struct ShelfPacker {
+    size: UVec2,
+    padding: u32,
+    shelves: Vec<Shelf>,
+}
+impl ShelfPacker {
+    pub fn new(size: UVec2, padding: u32) -> Self {
+        Self {
+            size,
+            padding,
+            shelves: Vec::new(),
+        }
+    }
+
+    pub fn size(&self) -> UVec2 { self.size }
+
+    /// Find space for a rectangle of the given size. Returns the top left corner of the area
+    /// reserved or `None` if there is no space left.
+    ///
+    /// ```
+    /// use glam::UVec2;
+    /// use tridify_rs::ShelfPacker;
+    ///
+    /// let mut packer = ShelfPacker::new(UVec2::new(10, 10), 1);
+    /// assert_eq!(packer.pack(UVec2::new(4, 3)), Some(UVec2::new(0, 0)));
+    /// assert_eq!(packer.pack(UVec2::new(5, 2)), Some(UVec2::new(5, 0)));
+    /// //The first shelf is full, so a new one starts below it and its padding.
+    /// assert_eq!(packer.pack(UVec2::new(3, 1)), Some(UVec2::new(0, 4)));
+    /// //A rectangle as wide as the area only has padding below it.
+    /// assert_eq!(packer.pack(UVec2::new(10, 2)), Some(UVec2::new(0, 6)));
+    /// //Short rectangles go to the shelf wasting less height.
+    /// assert_eq!(packer.pack(UVec2::new(2, 1)), Some(UVec2::new(4, 4)));
+    /// assert_eq!(packer.pack(UVec2::new(3, 2)), None);
+    /// //The last row of pixels fits without padding below.
+    /// assert_eq!(packer.pack(UVec2::new(10, 1)), Some(UVec2::new(0, 9)));
+    ///
+    /// let mut packer = ShelfPacker::new(UVec2::new(8, 8), 1);
+    /// assert_eq!(packer.pack(UVec2::new(9, 1)), None);
+    /// assert_eq!(packer.pack(UVec2::new(8, 8)), Some(UVec2::ZERO));
+    /// assert_eq!(packer.pack(UVec2::new(1, 1)), None);
+    /// ```
+    pub fn pack(&mut self, size: UVec2) -> Option<UVec2> {
+        if size.x > self.size.x || size.y > self.size.y {
+            return None;
+        }
+        let w = size.x + self.padding;
+        let h = size.y + self.padding;
+
+        let best_shelf = self
+            .shelves
+            .iter_mut()
+            .filter(|shelf| shelf.height >= h && shelf.next_x + size.x <= self.size.x)
+            .min_by_key(|shelf| shelf.height - h);
+        if let Some(shelf) = best_shelf {
+            let pos = UVec2::new(shelf.next_x, shelf.y);
+            shelf.next_x += w;
+            return Some(pos);
+        }
+
+        let next_y = self.shelves.last().map(|x| x.y + x.height).unwrap_or(0);
+        if next_y + size.y > self.size.y {
+            return None;
+        }
+        self.shelves.push(Shelf {
+            y: next_y,
+            height: h,
+            next_x: w,
+        });
+        Some(UVec2::new(0, next_y))
+    }
+
+    /// Enlarge the packing area. Rectangles already packed keep their position.
+    ///
+    /// ```
+    /// use glam::UVec2;
+    /// use tridify_rs::ShelfPacker;
+    ///
+    /// let mut packer = ShelfPacker::new(UVec2::new(8, 8), 1);
+    /// assert_eq!(packer.pack(UVec2::new(8, 4)), Some(UVec2::ZERO));
+    /// assert_eq!(packer.pack(UVec2::new(4, 4)), None);
+    /// packer.grow(UVec2::new(16, 8));
+    /// //The shelf continues after the padding that used to be cut by the edge.
+    /// assert_eq!(packer.pack(UVec2::new(4, 4)), Some(UVec2::new(9, 0)));
+    /// packer.grow(UVec2::new(16, 16));
+    /// assert_eq!(packer.pack(UVec2::new(16, 8)), Some(UVec2::new(0, 5)));
+    /// ```
+    pub fn grow(&mut self, size: UVec2) {
+        assert!(
+            size.x >= self.size.x && size.y >= self.size.y,
+            "Packers can't shrink."
+        );
+        self.size = size;
+    }
+
+    /// Remove all packed rectangles.
+    pub fn clear(&mut self) { self.shelves.clear(); }
+}
+
+/// Single texture containing many images. Each image inserted gets an index that can be used to
+/// retrieve its UVs, allowing multiple sprites to be drawn with the same brush.
+pub struct TextureAtlas {
+    texture: Texture,
+    packer: ShelfPacker,
+    uvs: Vec<Rect>,
+    pixel_rects: Vec<Rect>,
+}
+impl TextureAtlas {
+    /// Create an empty atlas with the given size in pixels.
+    pub fn new(gpu: &GpuCtx, size: UVec2) -> Self {
+        let desc = TextureDesc {
+            size: TextureSize::D2(size),
+            usage: TextureUsage::TEXTURE_BIND | TextureUsage::DESTINATION,
+            format: TextureFormat::Rgba8UnormSrgb,
+            mip_level_count: 1,
+        };
+        Self {
+            texture: Texture::new(gpu, desc, Some("Texture atlas")),
+            packer: ShelfPacker::new(size, 1),
+            uvs: Vec::new(),
+            pixel_rects: Vec::new(),
+        }
+    }
+
+    /// Create an atlas packing all images provided. Images are inserted from tallest to shortest
+    /// to reduce wasted space, but the indices returned follow the same order as `images`.
+    /// Returns `None` if they don't fit.
+    pub fn from_images(
+        gpu: &GpuCtx, size: UVec2, images: &[RgbaImage],
+    ) -> Option<(Self, Vec<usize>)> {
+        let mut atlas = Self::new(gpu, size);
+        let mut order = (0..images.len()).collect::<Vec<_>>();
+        order.sort_by_key(|i| std::cmp::Reverse(images[*i].height()));
+
+        let mut ids = vec![0; images.len()];
+        for i in order {
+            ids[i] = atlas.insert(gpu, &images[i])?;
+        }
+        Some((atlas, ids))
+    }
+
+    /// Pack and upload an image into the atlas. Returns the index to retrieve its UVs or `None` if
+    /// the atlas is full.
+    pub fn insert(&mut self, gpu: &GpuCtx, image: &RgbaImage) -> Option<usize> {
+        let size = UVec2::new(image.width(), image.height());
+        let pos = self.packer.pack(size)?;
+        self.texture
+            .write_region_pixels(gpu, image.as_raw(), pos.extend(0), size.extend(1));
+
+        let atlas_size = self.packer.size().as_vec2();
+        let pixel_rect = Rect::new(pos.as_vec2(), size.as_vec2());
+        self.uvs.push(Rect::new(
+            pixel_rect.pos / atlas_size,
+            pixel_rect.size / atlas_size,
+        ));
+        self.pixel_rects.push(pixel_rect);
+        Some(self.uvs.len() - 1)
+    }
+
+    /// Remove all entries. Pixels are not cleared but will be overwritten by new insertions.
+    pub fn clear(&mut self) {
+        self.packer.clear();
+        self.uvs.clear();
+        self.pixel_rects.clear();
+    }
+
+    /// Normalized UVs for the given entry.
+    pub fn uv(&self, index: usize) -> Rect { self.uvs[index] }
+
+    /// Area in pixels used by the given entry.
+    pub fn pixel_rect(&self, index: usize) -> Rect { self.pixel_rects[index] }
+
+    pub fn len(&self) -> usize { self.uvs.len() }
+    pub fn is_empty(&self) -> bool { self.uvs.is_empty() }
+
+    pub fn size(&self) -> UVec2 { self.packer.size() }
+
+    /// Texture holding all images. Cloning it is cheap and can be binded to brushes.
+    pub fn texture(&self) -> &Texture { &self.texture }
+}
@@ -1,7 +1,8 @@
-use std::{borrow::Borrow, collections::HashMap};
+use std::collections::HashMap;
 
 use wgpu::{
     BindGroup, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutEntry,
+    ShaderStages,
 };
 
 use crate::GpuCtx;
@@ -9,7 +10,7 @@ use crate::GpuCtx;
 /// Provides wgpu binding data for shaders.
 pub trait ToBinder {
     fn get_layout(&self, index: u32) -> BindGroupLayoutEntry;
-    fn get_group(&self, index: u32) -> BindGroupEntry;
+    fn get_group(&self, index: u32) -> BindGroupEntry<'_>;
     fn debug_name(&self) -> &'static str;
 }
 impl std::fmt::Debug for dyn ToBinder {
@@ -22,6 +23,9 @@ impl std::fmt::Debug for dyn ToBinder {
 pub struct Binder {
     bindings: HashMap<u32, Box<dyn ToBinder>>,
 }
+impl Default for Binder {
+    fn default() -> Self { Self::new() }
+}
 impl Binder {
     pub fn new() -> Self {
         Self {
@@ -34,19 +38,47 @@ impl Binder {
         self.bindings.insert(index, binding);
     }
 
+    /// Amount of bindings in the group using dynamic offsets.
+    pub fn dynamic_offset_count(&self) -> usize {
+        self.bindings
+            .iter()
+            .filter(|(id, to_bind)| {
+                matches!(
+                    to_bind.get_layout(**id).ty,
+                    wgpu::BindingType::Buffer {
+                        has_dynamic_offset: true,
+                        ..
+                    }
+                )
+            })
+            .count()
+    }
+
     /// Create GPU bindings to link with the render pipeline.
     pub fn bake(&self, wnd: &GpuCtx) -> (BindGroupLayout, BindGroup) {
+        self.bake_with_visibility(wnd, None)
+    }
+
+    /// Create GPU bindings overriding the shader stages each binding is visible from. Used to link
+    /// the same assets with compute pipelines.
+    pub fn bake_with_visibility(
+        &self, wnd: &GpuCtx, visibility: Option<ShaderStages>,
+    ) -> (BindGroupLayout, BindGroup) {
         let layout_entries = self
             .bindings
             .iter()
-            .map(|(id, to_bind)| to_bind.as_ref().borrow().get_layout(*id))
+            .map(|(id, to_bind)| {
+                let mut entry = to_bind.get_layout(*id);
+                if let Some(visibility) = visibility {
+                    entry.visibility = visibility;
+                }
+                entry
+            })
             .collect::<Vec<_>>();
         let mut bind_entries = Vec::new();
         for (i, bind) in self.bindings.iter() {
-            let cell = bind.as_ref();
-            let borrowed_tobind = cell.borrow();
-            println!("Loc: {:?} - Bind: {:?}", i, bind);
-            bind_entries.push((*i, borrowed_tobind));
+            log::debug!("Loc: {:?} - Bind: {:?}", i, bind);
+            bind_entries.push((*i, bind.as_ref()));
         }
         let groups = bind_entries
             .iter()
@@ -1,13 +1,15 @@
 use std::{borrow::Cow, collections::HashMap, error::Error, fs::File, io::Read, path::Path};
 
 use wgpu::{
-    BindGroup, BlendState, ColorTargetState, FragmentState, MultisampleState,
-    PipelineLayoutDescriptor, PrimitiveState, RenderPipeline, RenderPipelineDescriptor,
-    ShaderModule, ShaderModuleDescriptor, VertexState,
+    BindGroup, BlendState, ColorTargetState, CompareFunction, DepthStencilState, FragmentState,
+    MultisampleState, PipelineLayoutDescriptor, PrimitiveState, PrimitiveTopology, RenderPipeline,
+    RenderPipelineDescriptor, ShaderModule, ShaderModuleDescriptor, TextureFormat,
+    TextureSampleType, VertexState,
 };
 
 use crate::{Binder, GpuCtx, ToBinder, Vertex};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AlphaBlend {
     Default,
     Premultiplied,
@@ -47,8 +49,33 @@ impl From<AlphaBlend> for wgpu::BlendComponent {
     }
 }
 
+/// Depth testing configuration of a brush.
+#[derive(Debug, Clone, Copy)]
+pub struct DepthDesc {
+    pub format: TextureFormat,
+    /// Store depth of the drawn fragments.
+    pub write: bool,
+    pub compare: CompareFunction,
+}
+impl Default for DepthDesc {
+    fn default() -> Self {
+        Self {
+            format: TextureFormat::Depth32Float,
+            write: true,
+            compare: CompareFunction::Less,
+        }
+    }
+}
+
 pub struct BrushDesc {
     pub blend: wgpu::BlendState,
+    /// Formats of the textures the brush draws into, in shader location order. `None` draws into
+    /// the window surface.
+    pub targets: Option<Vec<TextureFormat>>,
+    /// Depth testing. Render passes using the brush need a depth texture with the same format.
+    pub depth: Option<DepthDesc>,
+    /// How vertices are assembled into primitives. Back faces are culled for triangles.
+    pub topology: PrimitiveTopology,
 }
 impl Default for BrushDesc {
     fn default() -> Self {
@@ -65,6 +92,9 @@ impl Default for BrushDesc {
                     operation: wgpu::BlendOperation::Add,
                 },
             },
+            targets: None,
+            depth: None,
+            topology: PrimitiveTopology::TriangleList,
         }
     }
 }
@@ -75,6 +105,8 @@ pub struct Brush {
     compiled_shader: ShaderModule,
     cached_pipeline: Option<RenderPipeline>,
     cached_bindings: Vec<(u32, BindGroup)>,
+    /// Amount of dynamic offsets required by each cached binding.
+    cached_dynamic_counts: Vec<usize>,
     assets_to_bind: HashMap<u32, Binder>,
     needs_update: bool,
 }
@@ -103,6 +135,7 @@ impl Brush {
             compiled_shader: shader,
             assets_to_bind: HashMap::new(),
             cached_bindings: Vec::new(),
+            cached_dynamic_counts: Vec::new(),
             cached_pipeline: None,
             needs_update: true,
         })
@@ -129,19 +162,41 @@ impl Brush {
     pub fn update(&mut self, gpu: &GpuCtx) {
         let device = &gpu.device;
         self.cached_bindings.clear();
+        self.cached_dynamic_counts.clear();
         let mut bgls: Vec<(u32, wgpu::BindGroupLayout)> = Vec::new();
-        for (i, binder) in self.assets_to_bind.iter() {
+        let mut groups = self.assets_to_bind.iter().collect::<Vec<_>>();
+        groups.sort_by_key(|x| *x.0);
+        for (i, binder) in groups {
             //Bake group
             let (bgl, bg) = binder.bake(gpu);
             bgls.push((*i, bgl));
             self.cached_bindings.push((*i, bg));
+            self.cached_dynamic_counts
+                .push(binder.dynamic_offset_count());
         }
-        bgls.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
         let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
             label: None,
             bind_group_layouts: &bgls.iter().map(|x| &x.1).collect::<Vec<_>>(),
             push_constant_ranges: &[],
         });
+        let formats = match &self.desc.targets {
+            Some(formats) => formats.clone(),
+            None => vec![gpu.surface.get_capabilities(&gpu.adapter).formats[0]],
+        };
+        let targets = formats
+            .into_iter()
+            .map(|format| {
+                Some(ColorTargetState {
+                    write_mask: wgpu::ColorWrites::ALL,
+                    format,
+                    //Integer formats can't be blended.
+                    blend: match format.sample_type(None) {
+                        Some(TextureSampleType::Uint | TextureSampleType::Sint) => None,
+                        _ => Some(self.desc.blend),
+                    },
+                })
+            })
+            .collect::<Vec<_>>();
         let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
             label: None,
             layout: Some(&pipeline_layout),
@@ -153,22 +208,29 @@ impl Brush {
             fragment: Some(FragmentState {
                 module: &self.compiled_shader,
                 entry_point: "fs_main",
-                targets: &[Some(ColorTargetState {
-                    write_mask: wgpu::ColorWrites::ALL,
-                    format: gpu.surface.get_capabilities(&gpu.adapter).formats[0],
-                    blend: Some(self.desc.blend),
-                })],
+                targets: &targets,
             }),
             primitive: PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
+                topology: self.desc.topology,
                 strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
+                cull_mode: match self.desc.topology {
+                    PrimitiveTopology::TriangleList | PrimitiveTopology::TriangleStrip => {
+                        Some(wgpu::Face::Back)
+                    }
+                    _ => None,
+                },
                 polygon_mode: wgpu::PolygonMode::Fill,
                 unclipped_depth: false,
                 conservative: false,
             },
-            depth_stencil: None,
+            depth_stencil: self.desc.depth.map(|depth| DepthStencilState {
+                format: depth.format,
+                depth_write_enabled: depth.write,
+                depth_compare: depth.compare,
+                stencil: Default::default(),
+                bias: Default::default(),
+            }),
             multisample: MultisampleState::default(),
             multiview: None,
         });
@@ -179,4 +241,22 @@ impl Brush {
     pub fn get_pipeline(&self) -> &RenderPipeline { self.cached_pipeline.as_ref().unwrap() }
 
     pub fn get_bind_groups(&self) -> &Vec<(u32, BindGroup)> { &self.cached_bindings }
+
+    /// Set all bind groups in the render pass. Dynamic offsets are consumed in group order and,
+    /// inside each group, in binding order.
+    pub(crate) fn set_bind_groups<'a>(
+        &'a self, pass: &mut wgpu::RenderPass<'a>, dynamic_offsets: &[u32],
+    ) {
+        let mut offsets = dynamic_offsets;
+        for ((id, bg), count) in self.cached_bindings.iter().zip(&self.cached_dynamic_counts) {
+            assert!(
+                offsets.len() >= *count,
+                "Missing dynamic offsets for bind group {}.",
+                id
+            );
+            let (group_offsets, rest) = offsets.split_at(*count);
+            pass.set_bind_group(*id, bg, group_offsets);
+            offsets = rest;
+        }
+    }
 }
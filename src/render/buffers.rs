@@ -1,13 +1,15 @@
-use std::error::Error;
+use std::ops::Range;
 
+use bytemuck::{Pod, Zeroable};
 use glam::{Quat, Vec3};
 use wgpu::{
     util::{BufferInitDescriptor, DeviceExt},
-    Buffer, BufferUsages,
+    Buffer, BufferUsages, IndexFormat,
 };
 
-use crate::{vertex, Color, GpuCtx, Rect, Vertex};
+use crate::{vertex, Aabb, Color, GpuCtx, Rect, Vertex};
 
+#[derive(Debug, Clone, Default)]
 pub struct Mesh {
     pub vertices: Vec<Vertex>,
     pub tris: Vec<u32>,
@@ -17,12 +19,60 @@ impl Mesh {
     pub fn new(vertices: Vec<Vertex>, tris: Vec<u32>) -> Self { Self { vertices, tris } }
 }
 
+/// Part of a batch that can be drawn on its own, see [`ShapeBatch::begin_submesh`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubMesh {
+    pub name: String,
+    /// Range of the index buffer to draw.
+    pub indices: Range<u32>,
+    /// Value added to each index, so submesh indices start at 0.
+    pub base_vertex: i32,
+}
+
+/// Arguments of an indexed draw read from a GPU buffer, see
+/// [`crate::RenderPass::render_indirect`]. Can be written by compute shaders using the same
+/// layout.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Pod, Zeroable)]
+pub struct DrawIndexedIndirectArgs {
+    pub index_count: u32,
+    pub instance_count: u32,
+    pub first_index: u32,
+    pub base_vertex: i32,
+    /// Must be 0 unless the device has the `INDIRECT_FIRST_INSTANCE` feature.
+    pub first_instance: u32,
+}
+impl DrawIndexedIndirectArgs {
+    /// Draw `instance_count` instances of a submesh.
+    pub fn from_submesh(submesh: &SubMesh, instance_count: u32) -> Self {
+        Self {
+            index_count: submesh.indices.len() as u32,
+            instance_count,
+            first_index: submesh.indices.start,
+            base_vertex: submesh.base_vertex,
+            first_instance: 0,
+        }
+    }
+}
+
 // ///Buffers created from the batch and prepared to be sent directly to the GPU
 // #[derive(Debug)]
 pub struct ShapeBuffer {
     pub vertex_buffer: Buffer,
     pub index_buffer: Buffer,
     pub index_len: u32,
+    pub index_format: IndexFormat,
+    /// Parts of the buffer drawn together by [`crate::RenderPass::render_shapes`].
+    pub submeshes: Vec<SubMesh>,
+    /// Bounds of the vertices in local space, used for culling. `None` for empty buffers.
+    pub aabb: Option<Aabb>,
+}
+
+impl ShapeBuffer {
+    /// Index of the first submesh with the given name.
+    pub fn find_submesh(&self, name: &str) -> Option<usize> {
+        self.submeshes.iter().position(|x| x.name == name)
+    }
 }
 
 // ///Queue of shapes to be drawn. All shapes added to the same batch will be drawn at the same time using the same brush.
@@ -30,7 +80,9 @@ pub struct ShapeBuffer {
 pub struct ShapeBatch {
     pub vertices: Vec<Vertex>,
     pub indices: Vec<u32>,
+    /// Index the next added vertex will have, relative to the current submesh.
     pub index_id_counter: u32,
+    submeshes: Vec<SubMesh>,
 }
 
 impl ShapeBatch {
@@ -39,7 +91,67 @@ impl ShapeBatch {
             vertices: Vec::new(),
             indices: Vec::new(),
             index_id_counter: 0,
+            submeshes: Vec::new(),
+        }
+    }
+
+    /// Start a submesh containing all shapes added until the next call, returning its index in
+    /// [`ShapeBuffer::submeshes`]. Shapes added before the first submesh form an unnamed one.
+    ///
+    /// Submesh indices start at 0 and are offset by the submesh `base_vertex` when drawn, so
+    /// [`ShapeBatch::indices`] can't be drawn as a whole once submeshes are used.
+    ///
+    /// ```
+    /// use glam::{Quat, Vec3};
+    /// use tridify_rs::{Color, ShapeBatch};
+    ///
+    /// let mut batch = ShapeBatch::new();
+    /// batch.begin_submesh("floor");
+    /// batch.add_2d_square(Vec3::ZERO, 1.0, 1.0, Color::WHITE);
+    /// let cube = batch.begin_submesh("cube");
+    /// batch.add_cube(Vec3::ZERO, Quat::IDENTITY, Vec3::ONE, Color::WHITE);
+    ///
+    /// let submeshes = batch.get_submeshes();
+    /// assert_eq!(submeshes[cube].indices, 6..42);
+    /// assert_eq!(submeshes[cube].base_vertex, 4);
+    /// ```
+    pub fn begin_submesh(&mut self, name: &str) -> usize {
+        if self.submeshes.is_empty() && !self.indices.is_empty() {
+            self.submeshes = self.get_submeshes();
+        }
+        self.submeshes.push(SubMesh {
+            name: name.to_string(),
+            indices: self.indices.len() as u32..self.indices.len() as u32,
+            base_vertex: self.vertices.len() as i32,
+        });
+        self.index_id_counter = 0;
+        self.submeshes.len() - 1
+    }
+
+    /// Submeshes recorded so far. Batches without submeshes have a single unnamed one.
+    pub fn get_submeshes(&self) -> Vec<SubMesh> {
+        let mut submeshes = self.submeshes.clone();
+        if submeshes.is_empty() {
+            submeshes.push(SubMesh {
+                name: String::new(),
+                indices: 0..0,
+                base_vertex: 0,
+            });
+        }
+        //Submeshes end where the next one starts.
+        let len = submeshes.len();
+        for i in 0..len {
+            submeshes[i].indices.end = match i + 1 < len {
+                true => submeshes[i + 1].indices.start,
+                false => self.indices.len() as u32,
+            };
         }
+        submeshes
+    }
+
+    /// Bounds of all vertices added so far, `None` when there are none.
+    pub fn aabb(&self) -> Option<Aabb> {
+        Aabb::from_points(self.vertices.iter().map(|x| Vec3::from(x.pos)))
     }
 
     ///Create buffers based on current batch data.
@@ -59,12 +171,30 @@ impl ShapeBatch {
             vertex_buffer,
             index_buffer,
             index_len: self.indices.len() as u32,
+            index_format: IndexFormat::Uint32,
+            submeshes: self.get_submeshes(),
+            aabb: self.aabb(),
         }
     }
 
+    /// Append mesh vertices and triangles, offsetting its indices by the vertices already added.
+    ///
+    /// ```
+    /// use tridify_rs::{vertex, Mesh, ShapeBatch};
+    ///
+    /// let triangle = Mesh {
+    ///     vertices: vec![vertex!(0.0, 0.0, 0.0), vertex!(1.0, 0.0, 0.0), vertex!(0.0, 1.0, 0.0)],
+    ///     tris: vec![0, 1, 2],
+    /// };
+    /// let mut batch = ShapeBatch::new();
+    /// batch.add_mesh(triangle.clone()).add_mesh(triangle);
+    /// assert_eq!(batch.vertices.len(), 6);
+    /// assert_eq!(batch.indices, [0, 1, 2, 3, 4, 5]);
+    /// ```
     pub fn add_mesh(&mut self, mesh: Mesh) -> &mut ShapeBatch {
+        let index = self.index_id_counter;
         self.vertices.extend(&mesh.vertices);
-        self.indices.extend(&mesh.tris);
+        self.indices.extend(mesh.tris.iter().map(|x| x + index));
         self.index_id_counter += mesh.vertices.len() as u32;
         self
     }
@@ -84,23 +214,68 @@ impl ShapeBatch {
 
     ///Add a square using a Rect as input
     pub fn add_rect(&mut self, rect: &Rect, color: Color) -> &mut ShapeBatch {
-        self.add_2d_square(rect.center().extend(0.), rect.size.x, rect.size.y, color);
-        self
+        self.add_rect_uv(rect, &Rect::UNIT, color)
+    }
+
+    ///Add a square using a Rect as input and mapping its corners to the given UVs. Useful to draw
+    ///entries from a [`crate::TextureAtlas`].
+    pub fn add_rect_uv(&mut self, rect: &Rect, uv: &Rect, color: Color) -> &mut ShapeBatch {
+        self.add_2d_square_uv(
+            rect.center().extend(0.),
+            rect.size.x,
+            rect.size.y,
+            uv,
+            color,
+        )
     }
 
     ///Add a square on axis XY to the batch specifying the center, width, height and color.
     pub fn add_2d_square(&mut self, center: Vec3, w: f32, h: f32, color: Color) -> &mut ShapeBatch {
+        self.add_2d_square_uv(center, w, h, &Rect::UNIT, color)
+    }
+
+    ///Add a square on axis XY to the batch specifying the center, width, height, UVs and color.
+    pub fn add_2d_square_uv(
+        &mut self, center: Vec3, w: f32, h: f32, uv: &Rect, color: Color,
+    ) -> &mut ShapeBatch {
         //Adding vertices
         let hw = w / 2.0;
         let hh = h / 2.0;
-        self.vertices
-            .push(vertex!(center.x - hw, center.y - hh, 0., color, [0.0, 0.0]));
-        self.vertices
-            .push(vertex!(center.x + hw, center.y - hh, 0., color, [1.0, 0.0]));
-        self.vertices
-            .push(vertex!(center.x - hw, center.y + hh, 0., color, [0.0, 1.0]));
-        self.vertices
-            .push(vertex!(center.x + hw, center.y + hh, 0., color, [1.0, 1.0]));
+        let (uv_min, uv_max) = (uv.min(), uv.max());
+        self.vertices.push(vertex!(
+            center.x - hw,
+            center.y - hh,
+            0.,
+            color,
+            [uv_min.x, uv_min.y]
+        ));
+        self.vertices.push(vertex!(
+            center.x + hw,
+            center.y - hh,
+            0.,
+            color,
+            [uv_max.x, uv_min.y]
+        ));
+        self.vertices.push(vertex!(
+            center.x - hw,
+            center.y + hh,
+            0.,
+            color,
+            [uv_min.x, uv_max.y]
+        ));
+        self.vertices.push(vertex!(
+            center.x + hw,
+            center.y + hh,
+            0.,
+            color,
+            [uv_max.x, uv_max.y]
+        ));
+
+        //Facing the camera when looking towards +Z.
+        let len = self.vertices.len();
+        for vertex in self.vertices[len - 4..].iter_mut() {
+            vertex.normal = [0.0, 0.0, -1.0];
+        }
 
         //Adding indices
         let index = self.index_id_counter;
@@ -124,26 +299,22 @@ impl ShapeBatch {
         let hw = w / 2.0;
         let hh = h / 2.0;
 
-        self.vertices.push(Vertex::from_vec(
-            center - right * hw - up * hh,
-            Some(color),
-            Some([0.0, 0.0]),
-        ));
-        self.vertices.push(Vertex::from_vec(
-            center + right * hw - up * hh,
-            Some(color),
-            Some([1.0, 0.0]),
-        ));
-        self.vertices.push(Vertex::from_vec(
-            center - right * hw + up * hh,
-            Some(color),
-            Some([0.0, 1.0]),
-        ));
-        self.vertices.push(Vertex::from_vec(
-            center + right * hw + up * hh,
-            Some(color),
-            Some([1.0, 1.0]),
-        ));
+        self.vertices.push(
+            Vertex::from_vec(center - right * hw - up * hh, Some(color), Some([0.0, 0.0]))
+                .with_normal(normal),
+        );
+        self.vertices.push(
+            Vertex::from_vec(center + right * hw - up * hh, Some(color), Some([1.0, 0.0]))
+                .with_normal(normal),
+        );
+        self.vertices.push(
+            Vertex::from_vec(center - right * hw + up * hh, Some(color), Some([0.0, 1.0]))
+                .with_normal(normal),
+        );
+        self.vertices.push(
+            Vertex::from_vec(center + right * hw + up * hh, Some(color), Some([1.0, 1.0]))
+                .with_normal(normal),
+        );
 
         //Adding indices
         let index = self.index_id_counter;
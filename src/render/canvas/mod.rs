@@ -0,0 +1,454 @@
+use std::collections::HashMap;
+use std::f32::consts::{PI, TAU};
+use std::ops::Range;
+
+use glam::{Mat4, UVec2, Vec2};
+use wgpu::{BindGroup, BlendState, TextureFormat};
+
+use crate::{
+    AlphaBlend, Binder, Brush, BrushDesc, Color, DynamicShapeBuffer, GlyphAtlas, GlyphMode,
+    GpuBuffer, GpuCtx, Rect, Sampler, ShapeBatch, ShapeBuffer, TextLayout, TextStyle, Texture,
+    TextureDesc, TextureSize, TextureUsage, Vertex,
+};
+
+/// WGSL source of the default canvas brush. Custom canvas shaders need to declare the same
+/// bindings, see [`Canvas::add_shader`].
+pub const CANVAS_SHADER_TEMPLATE: &str = include_str!("shader.wgsl");
+
+/// Index of the shader drawing text from SDF glyph atlases.
+const SDF_SHADER: usize = 1;
+
+/// Miters longer than this factor of the line thickness are drawn as bevels.
+const MITER_LIMIT: f32 = 4.0;
+
+/// Shader stored in a [`Canvas`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CanvasShaderId(usize);
+
+/// Shape drawn where two segments of a polyline meet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineJoin {
+    /// Extend both segments until they meet. Sharp corners fall back to [`LineJoin::Bevel`].
+    #[default]
+    Miter,
+    /// Connect the outer corners of both segments with a straight edge.
+    Bevel,
+    /// Connect the outer corners of both segments with an arc.
+    Round,
+}
+
+/// Range of indices drawn with the same shader and texture.
+struct CanvasDraw {
+    /// Shader index and texture id.
+    key: (usize, usize),
+    indices: Range<u32>,
+}
+
+/// Immediate-mode 2D drawing. Shapes are recorded every frame and drawn in call order with
+/// [`crate::RenderPass::render_canvas`], which flushes them so the canvas is empty for the next
+/// frame. Consecutive shapes with the same texture and shader are batched in a single draw call.
+///
+/// Coordinates are in pixels, with the origin at the top left corner of the window and Y
+/// pointing down, so rects are placed by their top left corner.
+pub struct Canvas {
+    batch: ShapeBatch,
+    buffer: DynamicShapeBuffer,
+    view_proj: GpuBuffer<[f32; 16]>,
+    sampler: Sampler,
+    white: Texture,
+    shaders: Vec<String>,
+    shader: usize,
+    targets: Option<Vec<TextureFormat>>,
+    /// Textures used since the last flush.
+    textures: HashMap<usize, Texture>,
+    /// Brush of each shader, drawing with the texture bind group of each draw.
+    brushes: HashMap<usize, Brush>,
+    /// Texture and sampler bind group of each texture used in the last flush.
+    texture_groups: HashMap<usize, BindGroup>,
+    draws: Vec<CanvasDraw>,
+    flushed: Vec<CanvasDraw>,
+}
+
+impl Canvas {
+    /// Create a canvas drawing into the window surface.
+    pub fn new(gpu: &GpuCtx) -> Self { Self::with_targets(gpu, None) }
+
+    /// Create a canvas drawing into textures with the given formats, see [`BrushDesc::targets`].
+    pub fn with_targets(gpu: &GpuCtx, targets: Option<Vec<TextureFormat>>) -> Self {
+        let white = Texture::init(
+            gpu,
+            TextureDesc {
+                size: TextureSize::D2(UVec2::ONE),
+                usage: TextureUsage::TEXTURE_BIND | TextureUsage::DESTINATION,
+                format: TextureFormat::Rgba8UnormSrgb,
+                mip_level_count: 1,
+            },
+            &[255; 4],
+            Some("Canvas white texture"),
+        );
+        Self {
+            batch: ShapeBatch::new(),
+            buffer: DynamicShapeBuffer::new(gpu, 1024, 1536),
+            view_proj: GpuBuffer::init(gpu, &[Mat4::IDENTITY.to_cols_array()]),
+            sampler: Sampler::new_default(gpu),
+            white,
+            shaders: vec![
+                CANVAS_SHADER_TEMPLATE.to_string(),
+                include_str!("sdf.wgsl").to_string(),
+            ],
+            shader: 0,
+            targets,
+            textures: HashMap::new(),
+            brushes: HashMap::new(),
+            texture_groups: HashMap::new(),
+            draws: Vec::new(),
+            flushed: Vec::new(),
+        }
+    }
+
+    /// Store a custom shader based on [`CANVAS_SHADER_TEMPLATE`]. It receives the pixel
+    /// projection in group 0, binding 0 and the texture and sampler in group 1, bindings 0 and 1.
+    pub fn add_shader(&mut self, source: String) -> CanvasShaderId {
+        self.shaders.push(source);
+        CanvasShaderId(self.shaders.len() - 1)
+    }
+
+    /// Shader used by the shapes drawn from now on. `None` restores the default shader.
+    pub fn set_shader(&mut self, shader: Option<CanvasShaderId>) {
+        self.shader = shader.map_or(0, |x| x.0);
+    }
+
+    /// Release the brushes cached for each shader and the bindings of the textures used in the
+    /// last flush. Bindings of textures not used in a flush are released automatically.
+    pub fn clear_cache(&mut self) {
+        self.brushes.clear();
+        self.texture_groups.clear();
+    }
+
+    /// Draw a straight line between two points.
+    pub fn line(&mut self, from: Vec2, to: Vec2, thickness: f32, color: Color) {
+        self.polyline(&[from, to], thickness, LineJoin::Bevel, false, color);
+    }
+
+    /// Draw connected line segments through the given points. Closed polylines also connect the
+    /// last point to the first one.
+    pub fn polyline(
+        &mut self, points: &[Vec2], thickness: f32, join: LineJoin, closed: bool, color: Color,
+    ) {
+        let mut points = points.to_vec();
+        points.dedup();
+        if closed && points.len() > 2 && points.first() == points.last() {
+            points.pop();
+        }
+        if points.len() < 2 {
+            return;
+        }
+        let closed = closed && points.len() > 2;
+        let half = thickness / 2.0;
+        let segment_count = match closed {
+            true => points.len(),
+            false => points.len() - 1,
+        };
+        let dir = |i: usize| (points[(i + 1) % points.len()] - points[i]).normalize();
+
+        self.begin_shape(None);
+        for i in 0..segment_count {
+            let offset = dir(i).perp() * half;
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            self.quad(
+                [a + offset, b + offset, b - offset, a - offset],
+                &Rect::UNIT,
+                color,
+            );
+        }
+        let joints = match closed {
+            true => 0..points.len(),
+            false => 1..points.len() - 1,
+        };
+        for i in joints {
+            let incoming = dir((i + points.len() - 1) % points.len());
+            self.join(points[i], incoming, dir(i), half, join, color);
+        }
+        self.end_shape();
+    }
+
+    /// Draw a filled circle.
+    pub fn circle(&mut self, center: Vec2, radius: f32, color: Color) {
+        let segments = Self::arc_segments(radius, TAU);
+        let points = (0..segments)
+            .map(|i| center + Vec2::from_angle(TAU * i as f32 / segments as f32) * radius)
+            .collect::<Vec<_>>();
+        self.begin_shape(None);
+        self.convex(center, &points, color);
+        self.end_shape();
+    }
+
+    /// Draw a filled rect.
+    pub fn rect(&mut self, rect: &Rect, color: Color) {
+        self.begin_shape(None);
+        self.rect_corners(rect, &Rect::UNIT, color);
+        self.end_shape();
+    }
+
+    /// Draw a filled rect with rounded corners. The radius is clamped to half the smallest side.
+    pub fn rounded_rect(&mut self, rect: &Rect, corner_radius: f32, color: Color) {
+        let radius = corner_radius
+            .min(rect.size.x.min(rect.size.y) / 2.0)
+            .max(0.0);
+        if radius == 0.0 {
+            return self.rect(rect, color);
+        }
+        let segments = Self::arc_segments(radius, PI / 2.0);
+        let (min, max) = (rect.min() + radius, rect.max() - radius);
+        let corners = [
+            Vec2::new(max.x, max.y),
+            Vec2::new(min.x, max.y),
+            Vec2::new(min.x, min.y),
+            Vec2::new(max.x, min.y),
+        ];
+        let mut points = Vec::with_capacity(corners.len() * (segments as usize + 1));
+        for (i, corner) in corners.iter().enumerate() {
+            for j in 0..=segments {
+                let angle = PI / 2.0 * (i as f32 + j as f32 / segments as f32);
+                points.push(*corner + Vec2::from_angle(angle) * radius);
+            }
+        }
+        self.begin_shape(None);
+        self.convex(rect.center(), &points, color);
+        self.end_shape();
+    }
+
+    /// Draw a texture stretched over the rect, tinted by the color.
+    pub fn sprite(&mut self, texture: &Texture, rect: &Rect, color: Color) {
+        self.sprite_uv(texture, rect, &Rect::UNIT, color);
+    }
+
+    /// Draw a region of a texture over the rect, tinted by the color. UVs start at the top left
+    /// corner of the texture. Useful to draw entries from a [`crate::TextureAtlas`].
+    pub fn sprite_uv(&mut self, texture: &Texture, rect: &Rect, uv: &Rect, color: Color) {
+        self.begin_shape(Some(texture));
+        self.rect_corners(rect, uv, color);
+        self.end_shape();
+    }
+
+    /// Draw text with its top left corner at `pos`. Glyphs missing from the atlas are rasterized
+    /// into it. Text from [`GlyphMode::Sdf`] atlases uses a distance field shader unless a custom
+    /// shader is set.
+    pub fn text(
+        &mut self, gpu: &GpuCtx, atlas: &mut GlyphAtlas, text: &str, pos: Vec2, style: &TextStyle,
+        color: Color,
+    ) {
+        let layout = atlas.font().layout(text, style);
+        self.text_layout(gpu, atlas, &layout, pos, color);
+    }
+
+    /// Draw text already laid out with [`crate::Font::layout`], useful to measure it first.
+    pub fn text_layout(
+        &mut self, gpu: &GpuCtx, atlas: &mut GlyphAtlas, layout: &TextLayout, pos: Vec2,
+        color: Color,
+    ) {
+        let quads = atlas.glyph_quads(gpu, layout);
+        let shader = self.shader;
+        let bitmap = atlas.mode() == GlyphMode::Bitmap;
+        if shader == 0 && !bitmap {
+            self.shader = SDF_SHADER;
+        }
+        self.begin_shape(Some(atlas.texture()));
+        for quad in quads {
+            let mut rect = quad.rect;
+            rect.pos += pos;
+            //Bitmap glyphs are sharper aligned to pixels.
+            if bitmap {
+                rect.pos = rect.pos.round();
+            }
+            self.rect_corners(&rect, &quad.uv, color);
+        }
+        self.end_shape();
+        self.shader = shader;
+    }
+
+    /// Upload the shapes recorded since the last flush and prepare their brushes. Recorded shapes
+    /// are cleared, ready for the next frame.
+    pub(crate) fn flush(&mut self, gpu: &GpuCtx) {
+        let size = gpu.get_wnd_size().as_vec2().max(Vec2::ONE);
+        let projection = Mat4::orthographic_lh(0.0, size.x, size.y, 0.0, -1.0, 1.0);
+        self.view_proj.write(gpu, &projection.to_cols_array());
+        self.flushed = std::mem::take(&mut self.draws);
+        if self.flushed.is_empty() {
+            return;
+        }
+        self.buffer.upload(gpu, &self.batch);
+        self.batch.vertices.clear();
+        self.batch.indices.clear();
+
+        for draw in self.flushed.iter() {
+            let shader = draw.key.0;
+            if self.brushes.contains_key(&shader) {
+                continue;
+            }
+            let desc = BrushDesc {
+                blend: BlendState {
+                    color: AlphaBlend::Default.into(),
+                    alpha: AlphaBlend::Default.into(),
+                },
+                targets: self.targets.clone(),
+                ..Default::default()
+            };
+            let mut brush = Brush::from_source(desc, gpu, self.shaders[shader].clone())
+                .expect("Error creating canvas brush.");
+            //The texture group is replaced for each draw, the white texture only sets its layout.
+            brush.bind(0, 0, self.view_proj.clone());
+            brush.bind(1, 0, self.white.clone());
+            brush.bind(1, 1, self.sampler.clone());
+            brush.update(gpu);
+            self.brushes.insert(shader, brush);
+        }
+
+        //Keep bindings only for the textures used in this flush, so replaced textures like
+        //regrown glyph atlases are released.
+        let textures = std::mem::take(&mut self.textures);
+        self.texture_groups
+            .retain(|id, _| textures.contains_key(id));
+        for (id, texture) in textures {
+            self.texture_groups.entry(id).or_insert_with(|| {
+                let mut binder = Binder::new();
+                binder.bind(0, Box::new(texture));
+                binder.bind(1, Box::new(self.sampler.clone()));
+                binder.bake(gpu).1
+            });
+        }
+    }
+
+    /// Brushes, texture bind groups and index ranges of the last flush, in drawing order.
+    pub(crate) fn flushed_draws(&self) -> impl Iterator<Item = (&Brush, &BindGroup, Range<u32>)> {
+        self.flushed.iter().map(|draw| {
+            (
+                &self.brushes[&draw.key.0],
+                &self.texture_groups[&draw.key.1],
+                draw.indices.clone(),
+            )
+        })
+    }
+
+    pub(crate) fn shape_buffer(&self) -> &ShapeBuffer { self.buffer.shape_buffer() }
+
+    /// Start recording a shape drawn with the texture, or a white one if `None`.
+    fn begin_shape(&mut self, texture: Option<&Texture>) {
+        let texture = texture.unwrap_or(&self.white);
+        let key = (self.shader, texture.id());
+        self.textures
+            .entry(key.1)
+            .or_insert_with(|| texture.clone());
+        let start = self.batch.indices.len() as u32;
+        match self.draws.last() {
+            Some(draw) if draw.key == key => {}
+            _ => self.draws.push(CanvasDraw {
+                key,
+                indices: start..start,
+            }),
+        }
+    }
+
+    fn end_shape(&mut self) {
+        if let Some(draw) = self.draws.last_mut() {
+            draw.indices.end = self.batch.indices.len() as u32;
+        }
+    }
+
+    fn vertex(&mut self, pos: Vec2, uv: Vec2, color: Color) -> u32 {
+        self.batch
+            .vertices
+            .push(Vertex::new(pos.x, pos.y, 0.0, Some(color), Some(uv.into())));
+        self.batch.vertices.len() as u32 - 1
+    }
+
+    /// Add a triangle, fixing its winding so it isn't culled. Y points down in pixel units, so
+    /// counter-clockwise triangles on screen have a negative area.
+    fn triangle(&mut self, a: u32, b: u32, c: u32) {
+        let pos = |i: u32| Vec2::from_slice(&self.batch.vertices[i as usize].pos);
+        let area = (pos(b) - pos(a)).perp_dot(pos(c) - pos(a));
+        match area > 0.0 {
+            true => self.batch.indices.extend([a, c, b]),
+            false => self.batch.indices.extend([a, b, c]),
+        }
+    }
+
+    /// Add a quad given its corners in order and the UVs mapped to them, with the first corner
+    /// mapped to the UV rect position.
+    fn quad(&mut self, corners: [Vec2; 4], uv: &Rect, color: Color) {
+        let uvs = [
+            uv.min(),
+            Vec2::new(uv.max().x, uv.min().y),
+            uv.max(),
+            Vec2::new(uv.min().x, uv.max().y),
+        ];
+        let ids = [0, 1, 2, 3].map(|i| self.vertex(corners[i], uvs[i], color));
+        self.triangle(ids[0], ids[1], ids[2]);
+        self.triangle(ids[0], ids[2], ids[3]);
+    }
+
+    fn rect_corners(&mut self, rect: &Rect, uv: &Rect, color: Color) {
+        let (min, max) = (rect.min(), rect.max());
+        let corners = [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)];
+        self.quad(corners, uv, color);
+    }
+
+    /// Add a convex shape as a fan of triangles around the center.
+    fn convex(&mut self, center: Vec2, points: &[Vec2], color: Color) {
+        let center = self.vertex(center, Vec2::ZERO, color);
+        let first = self.vertex(points[0], Vec2::ZERO, color);
+        let mut previous = first;
+        for point in &points[1..] {
+            let current = self.vertex(*point, Vec2::ZERO, color);
+            self.triangle(center, previous, current);
+            previous = current;
+        }
+        self.triangle(center, previous, first);
+    }
+
+    /// Fill the gap left on the outer side of two segments meeting at `point`.
+    fn join(
+        &mut self, point: Vec2, incoming: Vec2, outgoing: Vec2, half: f32, join: LineJoin,
+        color: Color,
+    ) {
+        let turn = incoming.perp_dot(outgoing);
+        if turn.abs() < 1e-6 && incoming.dot(outgoing) > 0.0 {
+            return;
+        }
+        //The outer side is opposite to the turn direction.
+        let side = if turn > 0.0 { -1.0 } else { 1.0 };
+        let (normal_in, normal_out) = (incoming.perp() * side, outgoing.perp() * side);
+        let center = self.vertex(point, Vec2::ZERO, color);
+        let start = self.vertex(point + normal_in * half, Vec2::ZERO, color);
+        let end = self.vertex(point + normal_out * half, Vec2::ZERO, color);
+        let miter = (normal_in + normal_out).normalize_or_zero();
+        let miter_scale = 1.0 / miter.dot(normal_in).max(f32::EPSILON);
+        match join {
+            LineJoin::Miter if miter != Vec2::ZERO && miter_scale <= MITER_LIMIT => {
+                let tip = self.vertex(point + miter * half * miter_scale, Vec2::ZERO, color);
+                self.triangle(center, start, tip);
+                self.triangle(center, tip, end);
+            }
+            LineJoin::Round => {
+                let angle = normal_in.angle_between(normal_out);
+                let segments = Self::arc_segments(half, angle.abs());
+                let mut previous = start;
+                for i in 1..segments {
+                    let rotation = Vec2::from_angle(angle * i as f32 / segments as f32);
+                    let current =
+                        self.vertex(point + rotation.rotate(normal_in) * half, Vec2::ZERO, color);
+                    self.triangle(center, previous, current);
+                    previous = current;
+                }
+                self.triangle(center, previous, end);
+            }
+            _ => self.triangle(center, start, end),
+        }
+    }
+
+    /// Amount of segments needed to draw a smooth arc with the given radius in pixels.
+    fn arc_segments(radius: f32, angle: f32) -> u32 {
+        let full_circle = (radius.max(0.0).sqrt() * 6.0).clamp(8.0, 128.0);
+        ((full_circle * angle / TAU).ceil() as u32).max(1)
+    }
+}
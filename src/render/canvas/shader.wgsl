@@ -0,0 +1,32 @@
+// Canvas brush template. Canvases bind the pixel projection to group 0, binding 0 and the drawn
+// texture and its sampler to group 1, bindings 0 and 1. Untextured shapes use a white texture.
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) uv: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) uv: vec2<f32>,
+};
+
+@group(0) @binding(0) var<uniform> u_view_proj: mat4x4<f32>;
+@group(1) @binding(0) var t_texture: texture_2d<f32>;
+@group(1) @binding(1) var s_texture: sampler;
+
+@vertex
+fn vs_main(model: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = u_view_proj * vec4<f32>(model.position, 1.0);
+    out.color = model.color;
+    out.uv = model.uv;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color * textureSample(t_texture, s_texture, in.uv);
+}
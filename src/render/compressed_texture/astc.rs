@@ -0,0 +1,579 @@
+use glam::UVec2;
+
+/// Color of blocks that can't be decoded, as required by the specification.
+const ERROR_COLOR: [u8; 4] = [255, 0, 255, 255];
+
+/// How values of an integer sequence are stored besides their low bits.
+#[derive(Clone, Copy, PartialEq)]
+enum Encoding {
+    Bits,
+    /// Groups of 5 values share 8 bits storing a base 3 digit for each one.
+    Trit,
+    /// Groups of 3 values share 7 bits storing a base 5 digit for each one.
+    Quint,
+}
+
+/// Integer sequence ranges from smaller to bigger, with their encoding and low bits per value.
+const RANGES: [(Encoding, u32); 21] = {
+    use Encoding::*;
+    [
+        (Bits, 1),
+        (Trit, 0),
+        (Bits, 2),
+        (Quint, 0),
+        (Trit, 1),
+        (Bits, 3),
+        (Quint, 1),
+        (Trit, 2),
+        (Bits, 4),
+        (Quint, 2),
+        (Trit, 3),
+        (Bits, 5),
+        (Quint, 3),
+        (Trit, 4),
+        (Bits, 6),
+        (Quint, 4),
+        (Trit, 5),
+        (Bits, 7),
+        (Quint, 5),
+        (Trit, 6),
+        (Bits, 8),
+    ]
+};
+
+/// Weight grid of a block.
+struct BlockMode {
+    size: UVec2,
+    dual_plane: bool,
+    /// Index in [`RANGES`].
+    weight_range: usize,
+}
+
+/// Decode an ASTC block with LDR colors. Blocks using HDR colors are decoded as the error color.
+pub(super) fn decode_astc(block: &[u8], block_size: UVec2, srgb: bool, pixels: &mut [[u8; 4]]) {
+    let bits = u128::from_le_bytes(block[..16].try_into().unwrap());
+    if decode_block(bits, block_size, srgb, pixels).is_none() {
+        pixels.fill(ERROR_COLOR);
+    }
+}
+
+fn read(bits: u128, start: u32, count: u32) -> u32 { ((bits >> start) & ((1 << count) - 1)) as u32 }
+
+/// Decode a block into pixels, returning `None` for error blocks.
+fn decode_block(bits: u128, block_size: UVec2, srgb: bool, pixels: &mut [[u8; 4]]) -> Option<()> {
+    let block_mode = read(bits, 0, 11);
+    if block_mode & 0x1FF == 0x1FC {
+        return decode_void_extent(bits, srgb, pixels);
+    }
+    let mode = decode_block_mode(block_mode)?;
+    if mode.size.x > block_size.x || mode.size.y > block_size.y {
+        return None;
+    }
+    let planes = 1 + mode.dual_plane as usize;
+    let weight_count = (mode.size.x * mode.size.y) as usize * planes;
+    if weight_count > 64 {
+        return None;
+    }
+    let weight_bits = sequence_bits(weight_count, mode.weight_range);
+    if !(24..=96).contains(&weight_bits) {
+        return None;
+    }
+
+    let partitions = read(bits, 11, 2) as usize + 1;
+    if partitions == 4 && mode.dual_plane {
+        return None;
+    }
+    let mut below_weights = 128 - weight_bits;
+    let mut endpoint_modes = [0u32; 4];
+    let color_start = if partitions == 1 {
+        endpoint_modes[0] = read(bits, 13, 4);
+        17
+    } else {
+        let encoded = read(bits, 23, 6);
+        if encoded & 3 == 0 {
+            endpoint_modes = [encoded >> 2; 4];
+        } else {
+            //Each partition stores an offset from the base class and a mode inside the class, with
+            //the bits that don't fit stored below the weights.
+            let extra_bits = 3 * partitions as u32 - 4;
+            below_weights -= extra_bits;
+            let class = (encoded & 3) - 1;
+            let encoded = encoded >> 2 | read(bits, below_weights, extra_bits) << 4;
+            for (i, mode) in endpoint_modes[..partitions].iter_mut().enumerate() {
+                let offset = (encoded >> i) & 1;
+                *mode = (class + offset) << 2 | (encoded >> (partitions + 2 * i)) & 3;
+            }
+        }
+        29
+    };
+    let plane_2_channel = if mode.dual_plane {
+        below_weights -= 2;
+        Some(read(bits, below_weights, 2) as usize)
+    } else {
+        None
+    };
+
+    let value_counts = endpoint_modes.map(|x| ((x >> 2) as usize + 1) * 2);
+    let color_count = value_counts[..partitions].iter().sum::<usize>();
+    if color_count > 18 || below_weights < color_start {
+        return None;
+    }
+    //Colors use the biggest range that fits between the block configuration and the weights.
+    let color_bits = below_weights - color_start;
+    if color_bits < (13 * color_count as u32).div_ceil(5) {
+        return None;
+    }
+    let color_range = (0..RANGES.len())
+        .rev()
+        .find(|x| sequence_bits(color_count, *x) <= color_bits)?;
+    let mut colors = [0u32; 18];
+    let color_data = bits >> color_start;
+    decode_sequence(color_data, color_range, &mut colors[..color_count]);
+    let mut endpoints = [([0i32; 4], [0i32; 4]); 4];
+    let mut values = colors.map(|x| unquantize_color(x, color_range) as i32);
+    let mut values = &mut values[..];
+    for partition in 0..partitions {
+        let (partition_values, rest) = values.split_at_mut(value_counts[partition]);
+        endpoints[partition] = decode_endpoints(endpoint_modes[partition], partition_values)?;
+        values = rest;
+    }
+
+    //Weights are stored from the end of the block with their bits reversed.
+    let mut weights = [0u32; 64];
+    let weight_data = bits.reverse_bits();
+    decode_sequence(weight_data, mode.weight_range, &mut weights[..weight_count]);
+    for weight in &mut weights[..weight_count] {
+        *weight = unquantize_weight(*weight, mode.weight_range);
+    }
+
+    let partition_index = read(bits, 13, 10);
+    let small_block = block_size.x * block_size.y < 31;
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        let texel = UVec2::new(i as u32 % block_size.x, i as u32 / block_size.x);
+        let partition = if partitions > 1 {
+            select_partition(partition_index, texel, partitions as u32, small_block)
+        } else {
+            0
+        };
+        let plane_weights =
+            [0, planes - 1].map(|plane| infill_weight(&weights, &mode, plane, block_size, texel));
+        let (e0, e1) = endpoints[partition];
+        for channel in 0..4 {
+            let weight = plane_weights[(plane_2_channel == Some(channel)) as usize];
+            pixel[channel] = interpolate(e0[channel] as u32, e1[channel] as u32, weight, srgb);
+        }
+    }
+    Some(())
+}
+
+fn decode_block_mode(mode: u32) -> Option<BlockMode> {
+    let bits = |start: u32, count: u32| (mode >> start) & ((1 << count) - 1);
+    let (a, b) = (bits(5, 2), bits(7, 2));
+    let mut dual_plane = bits(10, 1) == 1;
+    let mut high_precision = bits(9, 1) == 1;
+    let (size, range) = if bits(0, 2) != 0 {
+        let size = match bits(2, 2) {
+            0 => (b + 4, a + 2),
+            1 => (b + 8, a + 2),
+            2 => (a + 2, b + 8),
+            _ if bits(8, 1) == 0 => (a + 2, bits(7, 1) + 6),
+            _ => (bits(7, 1) + 2, a + 2),
+        };
+        (size, bits(0, 2) << 1 | bits(4, 1))
+    } else {
+        if bits(0, 4) == 0 {
+            return None;
+        }
+        let size = match bits(5, 4) {
+            0..=3 => (12, a + 2),
+            4..=7 => (a + 2, 12),
+            8..=11 => {
+                dual_plane = false;
+                high_precision = false;
+                (a + 6, bits(9, 2) + 6)
+            }
+            12 => (6, 10),
+            13 => (10, 6),
+            _ => return None,
+        };
+        (size, bits(2, 2) << 1 | bits(4, 1))
+    };
+    //Ranges 2 to 7 go from 0..1 to 0..7, or from 0..9 to 0..31 with high precision.
+    Some(BlockMode {
+        size: UVec2::new(size.0, size.1),
+        dual_plane,
+        weight_range: range as usize - 2 + 6 * high_precision as usize,
+    })
+}
+
+/// Blocks with a single color for all pixels.
+fn decode_void_extent(bits: u128, srgb: bool, pixels: &mut [[u8; 4]]) -> Option<()> {
+    //HDR colors can't be decoded into LDR pixels and the reserved bits must be set.
+    if read(bits, 9, 1) == 1 || read(bits, 10, 2) != 3 {
+        return None;
+    }
+    let coords = [12, 25, 38, 51].map(|x| read(bits, x, 13));
+    if coords != [0x1FFF; 4] && (coords[0] >= coords[1] || coords[2] >= coords[3]) {
+        return None;
+    }
+    let color = [64, 80, 96, 112].map(|x| to_unorm8(read(bits, x, 16), srgb));
+    pixels.fill(color);
+    Some(())
+}
+
+/// Bits used by a sequence of values of the given range.
+fn sequence_bits(count: usize, range: usize) -> u32 {
+    let count = count as u32;
+    let (encoding, bits) = RANGES[range];
+    count * bits
+        + match encoding {
+            Encoding::Bits => 0,
+            Encoding::Trit => (8 * count).div_ceil(5),
+            Encoding::Quint => (7 * count).div_ceil(3),
+        }
+}
+
+/// Decode integer sequence stored from the first bit of `data`.
+fn decode_sequence(data: u128, range: usize, values: &mut [u32]) {
+    let (encoding, bits) = RANGES[range];
+    //Bits past the end of the sequence must be read as zeros.
+    let length = sequence_bits(values.len(), range);
+    let data = data & (u128::MAX >> (128 - length));
+    let mut position = 0;
+    let mut take = |count: u32| {
+        let value = if position >= 128 {
+            0
+        } else {
+            read(data >> position, 0, count)
+        };
+        position += count;
+        value
+    };
+    let (group_size, packed_bits): (usize, &[u32]) = match encoding {
+        Encoding::Bits => (1, &[0]),
+        Encoding::Trit => (5, &[2, 2, 1, 2, 1]),
+        Encoding::Quint => (3, &[3, 2, 2]),
+    };
+    for group in values.chunks_mut(group_size) {
+        let mut low_bits = [0u32; 5];
+        let mut packed = 0;
+        let mut packed_position = 0;
+        for (i, count) in packed_bits.iter().enumerate() {
+            low_bits[i] = take(bits);
+            packed |= take(*count) << packed_position;
+            packed_position += count;
+        }
+        let digits = match encoding {
+            Encoding::Bits => [0; 5],
+            Encoding::Trit => decode_trits(packed),
+            Encoding::Quint => {
+                let [q0, q1, q2] = decode_quints(packed);
+                [q0, q1, q2, 0, 0]
+            }
+        };
+        for (i, value) in group.iter_mut().enumerate() {
+            *value = digits[i] << bits | low_bits[i];
+        }
+    }
+}
+
+fn decode_trits(t: u32) -> [u32; 5] {
+    let bit = |x: u32, i: u32| (x >> i) & 1;
+    let bits = |x: u32, start: u32, count: u32| (x >> start) & ((1 << count) - 1);
+    let (c, t4, t3);
+    if bits(t, 2, 3) == 0b111 {
+        c = bits(t, 5, 3) << 2 | bits(t, 0, 2);
+        (t4, t3) = (2, 2);
+    } else {
+        c = bits(t, 0, 5);
+        if bits(t, 5, 2) == 0b11 {
+            (t4, t3) = (2, bit(t, 7));
+        } else {
+            (t4, t3) = (bit(t, 7), bits(t, 5, 2));
+        }
+    }
+    let (t2, t1, t0);
+    if bits(c, 0, 2) == 0b11 {
+        (t2, t1) = (2, bit(c, 4));
+        t0 = bit(c, 3) << 1 | (bit(c, 2) & !bit(c, 3) & 1);
+    } else if bits(c, 2, 2) == 0b11 {
+        (t2, t1, t0) = (2, 2, bits(c, 0, 2));
+    } else {
+        (t2, t1) = (bit(c, 4), bits(c, 2, 2));
+        t0 = bit(c, 1) << 1 | (bit(c, 0) & !bit(c, 1) & 1);
+    }
+    [t0, t1, t2, t3, t4]
+}
+
+fn decode_quints(q: u32) -> [u32; 3] {
+    let bit = |x: u32, i: u32| (x >> i) & 1;
+    let bits = |x: u32, start: u32, count: u32| (x >> start) & ((1 << count) - 1);
+    if bits(q, 1, 2) == 0b11 && bits(q, 5, 2) == 0 {
+        let not_q0 = !bit(q, 0) & 1;
+        let q2 = bit(q, 0) << 2 | (bit(q, 4) & not_q0) << 1 | (bit(q, 3) & not_q0);
+        return [4, 4, q2];
+    }
+    let (q2, c) = if bits(q, 1, 2) == 0b11 {
+        (
+            4,
+            bits(q, 3, 2) << 3 | (!bits(q, 5, 2) & 3) << 1 | bit(q, 0),
+        )
+    } else {
+        (bits(q, 5, 2), bits(q, 0, 5))
+    };
+    if bits(c, 0, 3) == 0b101 {
+        [bits(c, 3, 2), 4, q2]
+    } else {
+        [bits(c, 0, 3), bits(c, 3, 2), q2]
+    }
+}
+
+/// Repeat the bits of a value to fill a wider value.
+fn replicate(value: u32, bits: u32, target_bits: u32) -> u32 {
+    let mut result = 0;
+    let mut filled = 0;
+    while filled < target_bits {
+        result = result << bits | value;
+        filled += bits;
+    }
+    result >> (filled - target_bits)
+}
+
+/// Map color endpoint value to 0..255.
+fn unquantize_color(value: u32, range: usize) -> u32 {
+    let (encoding, bits) = RANGES[range];
+    if encoding == Encoding::Bits {
+        return replicate(value, bits, 8);
+    }
+    let digit = value >> bits;
+    let a = if value & 1 == 1 { 0x1FF } else { 0 };
+    //Remaining low bits, arranged following the specification bit layouts.
+    let x = (value & ((1 << bits) - 1)) >> 1;
+    let (b, c) = match (encoding, bits) {
+        (Encoding::Trit, 1) => (0, 204),
+        (Encoding::Trit, 2) => (x << 8 | x << 4 | x << 2 | x << 1, 93),
+        (Encoding::Trit, 3) => (x << 7 | x << 2 | x, 44),
+        (Encoding::Trit, 4) => (x << 6 | x, 22),
+        (Encoding::Trit, 5) => (x << 5 | x >> 2, 11),
+        (Encoding::Trit, _) => (x << 4 | x >> 4, 5),
+        (_, 1) => (0, 113),
+        (_, 2) => (x << 8 | x << 3 | x << 2, 54),
+        (_, 3) => (x << 7 | x << 1 | x >> 1, 26),
+        (_, 4) => (x << 6 | x >> 1, 13),
+        _ => (x << 5 | x >> 3, 6),
+    };
+    let t = (digit * c + b) ^ a;
+    (a & 0x80) | (t >> 2)
+}
+
+/// Map weight value to 0..64.
+fn unquantize_weight(value: u32, range: usize) -> u32 {
+    let (encoding, bits) = RANGES[range];
+    let weight = match (encoding, bits) {
+        (Encoding::Bits, _) => replicate(value, bits, 6),
+        (Encoding::Trit, 0) => [0, 32, 63][value as usize],
+        (Encoding::Quint, 0) => [0, 16, 32, 47, 63][value as usize],
+        _ => {
+            let digit = value >> bits;
+            let a = if value & 1 == 1 { 0x7F } else { 0 };
+            let x = (value & ((1 << bits) - 1)) >> 1;
+            let (b, c) = match (encoding, bits) {
+                (Encoding::Trit, 1) => (0, 50),
+                (Encoding::Trit, 2) => (x << 6 | x << 2 | x, 23),
+                (Encoding::Trit, _) => (x << 5 | x, 11),
+                (_, 1) => (0, 28),
+                _ => (x << 6 | x << 1, 13),
+            };
+            let t = (digit * c + b) ^ a;
+            (a & 0x20) | (t >> 2)
+        }
+    };
+    weight + (weight > 32) as u32
+}
+
+/// Move the high bit of the offset at `v[i + 1]` into the base at `v[i]` and turn the offset
+/// into a signed value.
+fn bit_transfer_signed(v: &mut [i32], i: usize) {
+    v[i] = (v[i] >> 1) | (v[i + 1] & 0x80);
+    v[i + 1] = (v[i + 1] >> 1) & 0x3F;
+    if v[i + 1] & 0x20 != 0 {
+        v[i + 1] -= 0x40;
+    }
+}
+
+fn blue_contract(r: i32, g: i32, b: i32, a: i32) -> [i32; 4] { [(r + b) >> 1, (g + b) >> 1, b, a] }
+
+/// Endpoint colors of a partition from its color values, `None` for HDR modes.
+fn decode_endpoints(mode: u32, v: &mut [i32]) -> Option<([i32; 4], [i32; 4])> {
+    let clamp = |x: [i32; 4]| x.map(|c| c.clamp(0, 255));
+    let endpoints = match mode {
+        0 => ([v[0], v[0], v[0], 255], [v[1], v[1], v[1], 255]),
+        1 => {
+            let l0 = (v[0] >> 2) | (v[1] & 0xC0);
+            let l1 = (l0 + (v[1] & 0x3F)).min(255);
+            ([l0, l0, l0, 255], [l1, l1, l1, 255])
+        }
+        4 => ([v[0], v[0], v[0], v[2]], [v[1], v[1], v[1], v[3]]),
+        5 => {
+            bit_transfer_signed(v, 0);
+            bit_transfer_signed(v, 2);
+            let (l, a) = (v[0], v[2]);
+            (
+                [l, l, l, a],
+                clamp([l + v[1], l + v[1], l + v[1], a + v[3]]),
+            )
+        }
+        6 => {
+            let scaled = |c: i32| (c * v[3]) >> 8;
+            (
+                [scaled(v[0]), scaled(v[1]), scaled(v[2]), 255],
+                [v[0], v[1], v[2], 255],
+            )
+        }
+        8 | 12 => {
+            let alpha = if mode == 12 { [v[6], v[7]] } else { [255, 255] };
+            if v[1] + v[3] + v[5] >= v[0] + v[2] + v[4] {
+                ([v[0], v[2], v[4], alpha[0]], [v[1], v[3], v[5], alpha[1]])
+            } else {
+                (
+                    blue_contract(v[1], v[3], v[5], alpha[1]),
+                    blue_contract(v[0], v[2], v[4], alpha[0]),
+                )
+            }
+        }
+        9 | 13 => {
+            let channels = if mode == 13 { 4 } else { 3 };
+            for i in 0..channels {
+                bit_transfer_signed(v, i * 2);
+            }
+            let alpha = if mode == 13 {
+                [v[6], v[6] + v[7]]
+            } else {
+                [255, 255]
+            };
+            let (r1, g1, b1) = (v[0] + v[1], v[2] + v[3], v[4] + v[5]);
+            if v[1] + v[3] + v[5] >= 0 {
+                ([v[0], v[2], v[4], alpha[0]], clamp([r1, g1, b1, alpha[1]]))
+            } else {
+                (
+                    clamp(blue_contract(r1, g1, b1, alpha[1])),
+                    clamp(blue_contract(v[0], v[2], v[4], alpha[0])),
+                )
+            }
+        }
+        10 => {
+            let scaled = |c: i32| (c * v[3]) >> 8;
+            (
+                [scaled(v[0]), scaled(v[1]), scaled(v[2]), v[4]],
+                [v[0], v[1], v[2], v[5]],
+            )
+        }
+        _ => return None,
+    };
+    Some(endpoints)
+}
+
+/// Partition of a texel in blocks with more than one partition.
+fn select_partition(seed: u32, texel: UVec2, partitions: u32, small_block: bool) -> usize {
+    let (x, y) = if small_block {
+        (texel.x << 1, texel.y << 1)
+    } else {
+        (texel.x, texel.y)
+    };
+    let seed = seed + (partitions - 1) * 1024;
+    let rnum = hash52(seed);
+    let mut seeds = [0u32; 8];
+    for (i, s) in seeds.iter_mut().enumerate() {
+        let value = (rnum >> (i * 4)) & 0xF;
+        *s = value * value;
+    }
+    let (sh1, sh2) = if seed & 1 == 1 {
+        (
+            if seed & 2 != 0 { 4 } else { 5 },
+            if partitions == 3 { 6 } else { 5 },
+        )
+    } else {
+        (
+            if partitions == 3 { 6 } else { 5 },
+            if seed & 2 != 0 { 4 } else { 5 },
+        )
+    };
+    for (i, s) in seeds.iter_mut().enumerate() {
+        *s >>= if i % 2 == 0 { sh1 } else { sh2 };
+    }
+    let mut values = [
+        seeds[0] * x + seeds[1] * y + (rnum >> 14),
+        seeds[2] * x + seeds[3] * y + (rnum >> 10),
+        seeds[4] * x + seeds[5] * y + (rnum >> 6),
+        seeds[6] * x + seeds[7] * y + (rnum >> 2),
+    ]
+    .map(|x| x & 0x3F);
+    for value in &mut values[partitions as usize..] {
+        *value = 0;
+    }
+    let [a, b, c, d] = values;
+    if a >= b && a >= c && a >= d {
+        0
+    } else if b >= c && b >= d {
+        1
+    } else if c >= d {
+        2
+    } else {
+        3
+    }
+}
+
+fn hash52(mut p: u32) -> u32 {
+    p ^= p >> 15;
+    p = p.wrapping_sub(p << 17);
+    p = p.wrapping_add(p << 7);
+    p = p.wrapping_add(p << 4);
+    p ^= p >> 5;
+    p = p.wrapping_add(p << 16);
+    p ^= p >> 7;
+    p ^= p >> 3;
+    p ^= p << 6;
+    p ^= p >> 17;
+    p
+}
+
+/// Weight of a texel bilinearly interpolated from the weight grid.
+fn infill_weight(
+    weights: &[u32], mode: &BlockMode, plane: usize, block_size: UVec2, texel: UVec2,
+) -> u32 {
+    let planes = 1 + mode.dual_plane as usize;
+    let grid = mode.size;
+    let scale = |size: u32| (1024 + size / 2) / (size - 1);
+    let s = (scale(block_size.x) * texel.x * (grid.x - 1) + 32) >> 6;
+    let t = (scale(block_size.y) * texel.y * (grid.y - 1) + 32) >> 6;
+    let (js, fs, jt, ft) = (s >> 4, s & 0xF, t >> 4, t & 0xF);
+    let w11 = (fs * ft + 8) >> 4;
+    let factors = [16 + w11 - fs - ft, fs - w11, ft - w11, w11];
+    let offsets = [(0, 0), (1, 0), (0, 1), (1, 1)];
+    let mut sum = 0;
+    for (factor, (dx, dy)) in factors.into_iter().zip(offsets) {
+        //Texels on the last row or column don't use the weights past the grid.
+        if factor > 0 {
+            let index = ((jt + dy) * grid.x + js + dx) as usize;
+            sum += weights[index * planes + plane] * factor;
+        }
+    }
+    (sum + 8) >> 4
+}
+
+fn to_unorm8(value: u32, srgb: bool) -> u8 {
+    if srgb {
+        (value >> 8) as u8
+    } else {
+        ((value * 255 + 32767) / 65535) as u8
+    }
+}
+
+/// Apply weight to 8 bit endpoints expanded to 16 bits.
+fn interpolate(e0: u32, e1: u32, weight: u32, srgb: bool) -> u8 {
+    let expand = |x: u32| if srgb { x << 8 | 0x80 } else { x << 8 | x };
+    to_unorm8(
+        (expand(e0) * (64 - weight) + expand(e1) * weight + 32) >> 6,
+        srgb,
+    )
+}
@@ -0,0 +1,641 @@
+use half::f16;
+
+fn decode_565(color: u16) -> [u8; 4] {
+    let r = ((color >> 11) & 0x1f) as u32;
+    let g = ((color >> 5) & 0x3f) as u32;
+    let b = (color & 0x1f) as u32;
+    [
+        ((r * 255 + 15) / 31) as u8,
+        ((g * 255 + 31) / 63) as u8,
+        ((b * 255 + 15) / 31) as u8,
+        255,
+    ]
+}
+
+fn mix_color(a: [u8; 4], b: [u8; 4], wa: u32, wb: u32) -> [u8; 4] {
+    let mix = |x: u8, y: u8| ((x as u32 * wa + y as u32 * wb + (wa + wb) / 2) / (wa + wb)) as u8;
+    [mix(a[0], b[0]), mix(a[1], b[1]), mix(a[2], b[2]), 255]
+}
+
+/// Decode color part of BC1, BC2 and BC3 blocks. Only BC1 supports the 3 color + transparent mode.
+fn decode_color_block(block: &[u8], pixels: &mut [[u8; 4]], allow_transparent: bool) {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let (a, b) = (decode_565(c0), decode_565(c1));
+    let palette = if c0 > c1 || !allow_transparent {
+        [a, b, mix_color(a, b, 2, 1), mix_color(a, b, 1, 2)]
+    } else {
+        [a, b, mix_color(a, b, 1, 1), [0, 0, 0, 0]]
+    };
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        *pixel = palette[((indices >> (i * 2)) & 0b11) as usize];
+    }
+}
+
+/// Decode a single channel block used by BC3 alpha, BC4 and BC5.
+fn decode_channel_block(block: &[u8], signed: bool) -> [u8; 16] {
+    let (e0, e1) = if signed {
+        (
+            (block[0] as i8).max(-127) as i32,
+            (block[1] as i8).max(-127) as i32,
+        )
+    } else {
+        (block[0] as i32, block[1] as i32)
+    };
+    let (min, max) = if signed { (-127, 127) } else { (0, 255) };
+    let mix =
+        |i: i32, steps: i32| ((e0 * (steps - i) + e1 * i) as f32 / steps as f32).round() as i32;
+    let mut palette = [0i32; 8];
+    palette[0] = e0;
+    palette[1] = e1;
+    if e0 > e1 {
+        for i in 1..7 {
+            palette[i + 1] = mix(i as i32, 7);
+        }
+    } else {
+        for i in 1..5 {
+            palette[i + 1] = mix(i as i32, 5);
+        }
+        palette[6] = min;
+        palette[7] = max;
+    }
+
+    let mut bits = [0u8; 8];
+    bits[..6].copy_from_slice(&block[2..8]);
+    let indices = u64::from_le_bytes(bits);
+    let mut values = [0u8; 16];
+    for (i, value) in values.iter_mut().enumerate() {
+        let v = palette[((indices >> (i * 3)) & 0b111) as usize];
+        *value = if signed { v as i8 as u8 } else { v as u8 };
+    }
+    values
+}
+
+pub(super) fn decode_bc1(block: &[u8], pixels: &mut [[u8; 4]]) {
+    decode_color_block(block, pixels, true);
+}
+
+pub(super) fn decode_bc2(block: &[u8], pixels: &mut [[u8; 4]]) {
+    decode_color_block(&block[8..], pixels, false);
+    let alpha = u64::from_le_bytes(block[..8].try_into().unwrap());
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        pixel[3] = ((alpha >> (i * 4)) & 0xf) as u8 * 17;
+    }
+}
+
+pub(super) fn decode_bc3(block: &[u8], pixels: &mut [[u8; 4]]) {
+    decode_color_block(&block[8..], pixels, false);
+    let alpha = decode_channel_block(&block[..8], false);
+    for (pixel, alpha) in pixels.iter_mut().zip(alpha) {
+        pixel[3] = alpha;
+    }
+}
+
+fn decode_bc4(block: &[u8], pixels: &mut [[u8; 4]], signed: bool) {
+    let one = if signed { 127 } else { 255 };
+    let red = decode_channel_block(block, signed);
+    for (pixel, red) in pixels.iter_mut().zip(red) {
+        *pixel = [red, 0, 0, one];
+    }
+}
+
+fn decode_bc5(block: &[u8], pixels: &mut [[u8; 4]], signed: bool) {
+    let one = if signed { 127 } else { 255 };
+    let red = decode_channel_block(&block[..8], signed);
+    let green = decode_channel_block(&block[8..], signed);
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        *pixel = [red[i], green[i], 0, one];
+    }
+}
+
+pub(super) fn decode_bc4_unorm(block: &[u8], pixels: &mut [[u8; 4]]) {
+    decode_bc4(block, pixels, false);
+}
+pub(super) fn decode_bc4_snorm(block: &[u8], pixels: &mut [[u8; 4]]) {
+    decode_bc4(block, pixels, true);
+}
+pub(super) fn decode_bc5_unorm(block: &[u8], pixels: &mut [[u8; 4]]) {
+    decode_bc5(block, pixels, false);
+}
+pub(super) fn decode_bc5_snorm(block: &[u8], pixels: &mut [[u8; 4]]) {
+    decode_bc5(block, pixels, true);
+}
+
+/// Reads bits of a 128 bit block starting from the least significant one.
+struct BitReader {
+    bits: u128,
+}
+
+impl BitReader {
+    fn new(block: &[u8]) -> Self {
+        Self {
+            bits: u128::from_le_bytes(block[..16].try_into().unwrap()),
+        }
+    }
+
+    fn read(&mut self, count: u32) -> u32 {
+        let value = (self.bits & ((1 << count) - 1)) as u32;
+        self.bits >>= count;
+        value
+    }
+}
+
+/// Interpolation weights for 2, 3 and 4 bit indices, shared by BC6H and BC7.
+const WEIGHTS: [&[u32]; 3] = [
+    &[0, 21, 43, 64],
+    &[0, 9, 18, 27, 37, 46, 55, 64],
+    &[0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64],
+];
+
+fn interpolate(a: i32, b: i32, index: u32, index_bits: u32) -> i32 {
+    let weight = WEIGHTS[index_bits as usize - 2][index as usize] as i32;
+    (a * (64 - weight) + b * weight + 32) >> 6
+}
+
+/// Subset of each pixel for the 2 subset partitions, one bit per pixel.
+const PARTITIONS_2: [u16; 64] = [
+    0xCCCC, 0x8888, 0xEEEE, 0xECC8, 0xC880, 0xFEEC, 0xFEC8, 0xEC80, 0xC800, 0xFFEC, 0xFE80, 0xE800,
+    0xFFE8, 0xFF00, 0xFFF0, 0xF000, 0xF710, 0x008E, 0x7100, 0x08CE, 0x008C, 0x7310, 0x3100, 0x8CCE,
+    0x088C, 0x3110, 0x6666, 0x366C, 0x17E8, 0x0FF0, 0x718E, 0x399C, 0xAAAA, 0xF0F0, 0x5A5A, 0x33CC,
+    0x3C3C, 0x55AA, 0x9696, 0xA55A, 0x73CE, 0x13C8, 0x324C, 0x3BDC, 0x6996, 0xC33C, 0x9966, 0x0660,
+    0x0272, 0x04E4, 0x4E40, 0x2720, 0xC936, 0x936C, 0x39C6, 0x639C, 0x9336, 0x9CC6, 0x817E, 0xE718,
+    0xCCF0, 0x0FCC, 0x7744, 0xEE22,
+];
+
+/// Subset of each pixel for the 3 subset partitions, two bits per pixel.
+const PARTITIONS_3: [u32; 64] = [
+    0xAA685050, 0x6A5A5040, 0x5A5A4200, 0x5450A0A8, 0xA5A50000, 0xA0A05050, 0x5555A0A0, 0x5A5A5050,
+    0xAA550000, 0xAA555500, 0xAAAA5500, 0x90909090, 0x94949494, 0xA4A4A4A4, 0xA9A59450, 0x2A0A4250,
+    0xA5945040, 0x0A425054, 0xA5A5A500, 0x55A0A0A0, 0xA8A85454, 0x6A6A4040, 0xA4A45000, 0x1A1A0500,
+    0x0050A4A4, 0xAAA59090, 0x14696914, 0x69691400, 0xA08585A0, 0xAA821414, 0x50A4A450, 0x6A5A0200,
+    0xA9A58000, 0x5090A0A8, 0xA8A09050, 0x24242424, 0x00AA5500, 0x24924924, 0x24499224, 0x50A50A50,
+    0x500AA550, 0xAAAA4444, 0x66660000, 0xA5A0A5A0, 0x50A050A0, 0x69286928, 0x44AAAA44, 0x66666600,
+    0xAA444444, 0x54A854A8, 0x95809580, 0x96969600, 0xA85454A8, 0x80959580, 0xAA141414, 0x96960000,
+    0xAAAA1414, 0xA05050A0, 0xA0A5A5A0, 0x96000000, 0x40804080, 0xA9A8A9A8, 0xAAAAAA44, 0x2A4A5254,
+];
+
+/// Anchor pixel of the second subset for the 2 subset partitions.
+const ANCHORS_2: [usize; 64] = [
+    15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 2, 8, 2, 2, 8, 8, 15, 2, 8,
+    2, 2, 8, 8, 2, 2, 15, 15, 6, 8, 2, 8, 15, 15, 2, 8, 2, 2, 2, 15, 15, 6, 6, 2, 6, 8, 15, 15, 2,
+    2, 15, 15, 15, 15, 15, 2, 2, 15,
+];
+
+/// Anchor pixels of the second and third subsets for the 3 subset partitions.
+const ANCHORS_3: [[usize; 64]; 2] = [
+    [
+        3, 3, 15, 15, 8, 3, 15, 15, 8, 8, 6, 6, 6, 5, 3, 3, 3, 3, 8, 15, 3, 3, 6, 10, 5, 8, 8, 6,
+        8, 5, 15, 15, 8, 15, 3, 5, 6, 10, 8, 15, 15, 3, 15, 5, 15, 15, 15, 15, 3, 15, 5, 5, 5, 8,
+        5, 10, 5, 10, 8, 13, 15, 12, 3, 3,
+    ],
+    [
+        15, 8, 8, 3, 15, 15, 3, 8, 15, 15, 15, 15, 15, 15, 15, 8, 15, 8, 15, 3, 15, 8, 15, 8, 3,
+        15, 6, 10, 15, 15, 10, 8, 15, 3, 15, 10, 10, 8, 9, 10, 6, 15, 8, 15, 3, 6, 6, 8, 15, 3, 15,
+        15, 15, 15, 15, 15, 15, 15, 15, 15, 3, 15, 15, 8,
+    ],
+];
+
+fn subset_of(subsets: usize, partition: usize, pixel: usize) -> usize {
+    match subsets {
+        1 => 0,
+        2 => (PARTITIONS_2[partition] >> pixel) as usize & 1,
+        _ => (PARTITIONS_3[partition] >> (pixel * 2)) as usize & 3,
+    }
+}
+
+fn is_anchor(subsets: usize, partition: usize, pixel: usize) -> bool {
+    match subsets {
+        1 => pixel == 0,
+        2 => pixel == 0 || pixel == ANCHORS_2[partition],
+        _ => pixel == 0 || ANCHORS_3[0][partition] == pixel || ANCHORS_3[1][partition] == pixel,
+    }
+}
+
+/// Layout of a BC7 mode.
+struct Bc7Mode {
+    subsets: usize,
+    partition_bits: u32,
+    rotation_bits: u32,
+    index_selection_bits: u32,
+    color_bits: u32,
+    alpha_bits: u32,
+    /// P bit for each endpoint, shared by all its channels.
+    endpoint_p_bits: bool,
+    /// P bit for each subset, shared by both endpoints.
+    shared_p_bits: bool,
+    index_bits: u32,
+    /// Index bits used by the second set of indices, 0 if there is no second set.
+    index_bits_2: u32,
+}
+
+const fn bc7_mode(
+    subsets: usize, partition_bits: u32, rotation_bits: (u32, u32), color_bits: u32,
+    alpha_bits: u32, p_bits: (bool, bool), index_bits: (u32, u32),
+) -> Bc7Mode {
+    Bc7Mode {
+        subsets,
+        partition_bits,
+        rotation_bits: rotation_bits.0,
+        index_selection_bits: rotation_bits.1,
+        color_bits,
+        alpha_bits,
+        endpoint_p_bits: p_bits.0,
+        shared_p_bits: p_bits.1,
+        index_bits: index_bits.0,
+        index_bits_2: index_bits.1,
+    }
+}
+
+const BC7_MODES: [Bc7Mode; 8] = [
+    bc7_mode(3, 4, (0, 0), 4, 0, (true, false), (3, 0)),
+    bc7_mode(2, 6, (0, 0), 6, 0, (false, true), (3, 0)),
+    bc7_mode(3, 6, (0, 0), 5, 0, (false, false), (2, 0)),
+    bc7_mode(2, 6, (0, 0), 7, 0, (true, false), (2, 0)),
+    bc7_mode(1, 0, (2, 1), 5, 6, (false, false), (2, 3)),
+    bc7_mode(1, 0, (2, 0), 7, 8, (false, false), (2, 2)),
+    bc7_mode(1, 0, (0, 0), 7, 7, (true, false), (4, 0)),
+    bc7_mode(2, 6, (0, 0), 5, 5, (true, false), (2, 0)),
+];
+
+pub(super) fn decode_bc7(block: &[u8], pixels: &mut [[u8; 4]]) {
+    let mode = block[0].trailing_zeros() as usize;
+    //Blocks without mode are reserved and decoded as transparent black.
+    let Some(m) = BC7_MODES.get(mode) else {
+        pixels.fill([0; 4]);
+        return;
+    };
+    let mut bits = BitReader::new(block);
+    bits.read(mode as u32 + 1);
+    let partition = bits.read(m.partition_bits) as usize;
+    let rotation = bits.read(m.rotation_bits);
+    let index_selection = bits.read(m.index_selection_bits);
+
+    let endpoint_count = m.subsets * 2;
+    let mut endpoints = [[0u32; 4]; 6];
+    for channel in 0..3 {
+        for endpoint in &mut endpoints[..endpoint_count] {
+            endpoint[channel] = bits.read(m.color_bits);
+        }
+    }
+    for endpoint in &mut endpoints[..endpoint_count] {
+        endpoint[3] = bits.read(m.alpha_bits);
+    }
+    let mut p_bits = [0u32; 6];
+    if m.endpoint_p_bits {
+        for p in &mut p_bits[..endpoint_count] {
+            *p = bits.read(1);
+        }
+    } else if m.shared_p_bits {
+        for subset in 0..m.subsets {
+            let p = bits.read(1);
+            p_bits[subset * 2] = p;
+            p_bits[subset * 2 + 1] = p;
+        }
+    }
+    let has_p_bits = (m.endpoint_p_bits || m.shared_p_bits) as u32;
+    let expand = |value: u32, p: u32, bits: u32| {
+        let (value, bits) = ((value << has_p_bits) | p, bits + has_p_bits);
+        ((value << (8 - bits)) | (value >> (2 * bits - 8))) as i32
+    };
+    let mut colors = [[0i32; 4]; 6];
+    for (i, endpoint) in endpoints[..endpoint_count].iter().enumerate() {
+        for channel in 0..3 {
+            colors[i][channel] = expand(endpoint[channel], p_bits[i], m.color_bits);
+        }
+        colors[i][3] = if m.alpha_bits == 0 {
+            255
+        } else {
+            expand(endpoint[3], p_bits[i], m.alpha_bits)
+        };
+    }
+
+    let mut indices = [0u32; 16];
+    for (i, index) in indices.iter_mut().enumerate() {
+        *index = bits.read(m.index_bits - is_anchor(m.subsets, partition, i) as u32);
+    }
+    let mut indices_2 = [0u32; 16];
+    if m.index_bits_2 > 0 {
+        for (i, index) in indices_2.iter_mut().enumerate() {
+            *index = bits.read(m.index_bits_2 - (i == 0) as u32);
+        }
+    }
+
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        let subset = subset_of(m.subsets, partition, i);
+        let (a, b) = (colors[subset * 2], colors[subset * 2 + 1]);
+        let (color_index, alpha_index) = match (m.index_bits_2, index_selection) {
+            (0, _) => ((indices[i], m.index_bits), (indices[i], m.index_bits)),
+            (_, 0) => ((indices[i], m.index_bits), (indices_2[i], m.index_bits_2)),
+            _ => ((indices_2[i], m.index_bits_2), (indices[i], m.index_bits)),
+        };
+        let mut color = [0u8; 4];
+        for channel in 0..3 {
+            color[channel] =
+                interpolate(a[channel], b[channel], color_index.0, color_index.1) as u8;
+        }
+        color[3] = interpolate(a[3], b[3], alpha_index.0, alpha_index.1) as u8;
+        if rotation > 0 {
+            color.swap(3, rotation as usize - 1);
+        }
+        *pixel = color;
+    }
+}
+
+/// Endpoint components of a BC6H block, in the order used by the mode layouts.
+#[derive(Clone, Copy)]
+enum Field {
+    Rw,
+    Gw,
+    Bw,
+    Rx,
+    Gx,
+    Bx,
+    Ry,
+    Gy,
+    By,
+    Rz,
+    Gz,
+    Bz,
+    /// Partition index.
+    D,
+}
+
+/// Bits of a field read one after another, from the second bit index towards the first.
+type Bits = (Field, u8, u8);
+
+/// Layout of a BC6H mode after the mode bits.
+struct Bc6hMode {
+    /// Whether endpoints are stored as deltas of the first one.
+    transformed: bool,
+    endpoint_bits: u32,
+    delta_bits: [u32; 3],
+    layout: &'static [Bits],
+}
+
+#[rustfmt::skip]
+const BC6H_MODES: [Bc6hMode; 14] = {
+    use Field::*;
+    [
+        Bc6hMode {
+            transformed: true,
+            endpoint_bits: 10,
+            delta_bits: [5, 5, 5],
+            layout: &[
+                (Gy, 4, 4), (By, 4, 4), (Bz, 4, 4), (Rw, 9, 0), (Gw, 9, 0), (Bw, 9, 0), (Rx, 4, 0),
+                (Gz, 4, 4), (Gy, 3, 0), (Gx, 4, 0), (Bz, 0, 0), (Gz, 3, 0), (Bx, 4, 0), (Bz, 1, 1),
+                (By, 3, 0), (Ry, 4, 0), (Bz, 2, 2), (Rz, 4, 0), (Bz, 3, 3), (D, 4, 0),
+            ],
+        },
+        Bc6hMode {
+            transformed: true,
+            endpoint_bits: 7,
+            delta_bits: [6, 6, 6],
+            layout: &[
+                (Gy, 5, 5), (Gz, 4, 4), (Gz, 5, 5), (Rw, 6, 0), (Bz, 0, 0), (Bz, 1, 1), (By, 4, 4),
+                (Gw, 6, 0), (By, 5, 5), (Bz, 2, 2), (Gy, 4, 4), (Bw, 6, 0), (Bz, 3, 3), (Bz, 5, 5),
+                (Bz, 4, 4), (Rx, 5, 0), (Gy, 3, 0), (Gx, 5, 0), (Gz, 3, 0), (Bx, 5, 0), (By, 3, 0),
+                (Ry, 5, 0), (Rz, 5, 0), (D, 4, 0),
+            ],
+        },
+        Bc6hMode {
+            transformed: true,
+            endpoint_bits: 11,
+            delta_bits: [5, 4, 4],
+            layout: &[
+                (Rw, 9, 0), (Gw, 9, 0), (Bw, 9, 0), (Rx, 4, 0), (Rw, 10, 10), (Gy, 3, 0), (Gx, 3, 0),
+                (Gw, 10, 10), (Bz, 0, 0), (Gz, 3, 0), (Bx, 3, 0), (Bw, 10, 10), (Bz, 1, 1),
+                (By, 3, 0), (Ry, 4, 0), (Bz, 2, 2), (Rz, 4, 0), (Bz, 3, 3), (D, 4, 0),
+            ],
+        },
+        Bc6hMode {
+            transformed: true,
+            endpoint_bits: 11,
+            delta_bits: [4, 5, 4],
+            layout: &[
+                (Rw, 9, 0), (Gw, 9, 0), (Bw, 9, 0), (Rx, 3, 0), (Rw, 10, 10), (Gz, 4, 4), (Gy, 3, 0),
+                (Gx, 4, 0), (Gw, 10, 10), (Gz, 3, 0), (Bx, 3, 0), (Bw, 10, 10), (Bz, 1, 1),
+                (By, 3, 0), (Ry, 3, 0), (Bz, 0, 0), (Bz, 2, 2), (Rz, 3, 0), (Gy, 4, 4), (Bz, 3, 3),
+                (D, 4, 0),
+            ],
+        },
+        Bc6hMode {
+            transformed: true,
+            endpoint_bits: 11,
+            delta_bits: [4, 4, 5],
+            layout: &[
+                (Rw, 9, 0), (Gw, 9, 0), (Bw, 9, 0), (Rx, 3, 0), (Rw, 10, 10), (By, 4, 4), (Gy, 3, 0),
+                (Gx, 3, 0), (Gw, 10, 10), (Bz, 0, 0), (Gz, 3, 0), (Bx, 4, 0), (Bw, 10, 10),
+                (By, 3, 0), (Ry, 3, 0), (Bz, 1, 1), (Bz, 2, 2), (Rz, 3, 0), (Bz, 4, 4), (Bz, 3, 3),
+                (D, 4, 0),
+            ],
+        },
+        Bc6hMode {
+            transformed: true,
+            endpoint_bits: 9,
+            delta_bits: [5, 5, 5],
+            layout: &[
+                (Rw, 8, 0), (By, 4, 4), (Gw, 8, 0), (Gy, 4, 4), (Bw, 8, 0), (Bz, 4, 4), (Rx, 4, 0),
+                (Gz, 4, 4), (Gy, 3, 0), (Gx, 4, 0), (Bz, 0, 0), (Gz, 3, 0), (Bx, 4, 0), (Bz, 1, 1),
+                (By, 3, 0), (Ry, 4, 0), (Bz, 2, 2), (Rz, 4, 0), (Bz, 3, 3), (D, 4, 0),
+            ],
+        },
+        Bc6hMode {
+            transformed: true,
+            endpoint_bits: 8,
+            delta_bits: [6, 5, 5],
+            layout: &[
+                (Rw, 7, 0), (Gz, 4, 4), (By, 4, 4), (Gw, 7, 0), (Bz, 2, 2), (Gy, 4, 4), (Bw, 7, 0),
+                (Bz, 3, 3), (Bz, 4, 4), (Rx, 5, 0), (Gy, 3, 0), (Gx, 4, 0), (Bz, 0, 0), (Gz, 3, 0),
+                (Bx, 4, 0), (Bz, 1, 1), (By, 3, 0), (Ry, 5, 0), (Rz, 5, 0), (D, 4, 0),
+            ],
+        },
+        Bc6hMode {
+            transformed: true,
+            endpoint_bits: 8,
+            delta_bits: [5, 6, 5],
+            layout: &[
+                (Rw, 7, 0), (Bz, 0, 0), (By, 4, 4), (Gw, 7, 0), (Gy, 5, 5), (Gy, 4, 4), (Bw, 7, 0),
+                (Gz, 5, 5), (Bz, 4, 4), (Rx, 4, 0), (Gz, 4, 4), (Gy, 3, 0), (Gx, 5, 0), (Gz, 3, 0),
+                (Bx, 4, 0), (Bz, 1, 1), (By, 3, 0), (Ry, 4, 0), (Bz, 2, 2), (Rz, 4, 0), (Bz, 3, 3),
+                (D, 4, 0),
+            ],
+        },
+        Bc6hMode {
+            transformed: true,
+            endpoint_bits: 8,
+            delta_bits: [5, 5, 6],
+            layout: &[
+                (Rw, 7, 0), (Bz, 1, 1), (By, 4, 4), (Gw, 7, 0), (By, 5, 5), (Gy, 4, 4), (Bw, 7, 0),
+                (Bz, 5, 5), (Bz, 4, 4), (Rx, 4, 0), (Gz, 4, 4), (Gy, 3, 0), (Gx, 4, 0), (Bz, 0, 0),
+                (Gz, 3, 0), (Bx, 5, 0), (By, 3, 0), (Ry, 4, 0), (Bz, 2, 2), (Rz, 4, 0), (Bz, 3, 3),
+                (D, 4, 0),
+            ],
+        },
+        Bc6hMode {
+            transformed: false,
+            endpoint_bits: 6,
+            delta_bits: [6, 6, 6],
+            layout: &[
+                (Rw, 5, 0), (Gz, 4, 4), (Bz, 0, 0), (Bz, 1, 1), (By, 4, 4), (Gw, 5, 0), (Gy, 5, 5),
+                (By, 5, 5), (Bz, 2, 2), (Gy, 4, 4), (Bw, 5, 0), (Gz, 5, 5), (Bz, 3, 3), (Bz, 5, 5),
+                (Bz, 4, 4), (Rx, 5, 0), (Gy, 3, 0), (Gx, 5, 0), (Gz, 3, 0), (Bx, 5, 0), (By, 3, 0),
+                (Ry, 5, 0), (Rz, 5, 0), (D, 4, 0),
+            ],
+        },
+        Bc6hMode {
+            transformed: false,
+            endpoint_bits: 10,
+            delta_bits: [10, 10, 10],
+            layout: &[(Rw, 9, 0), (Gw, 9, 0), (Bw, 9, 0), (Rx, 9, 0), (Gx, 9, 0), (Bx, 9, 0)],
+        },
+        Bc6hMode {
+            transformed: true,
+            endpoint_bits: 11,
+            delta_bits: [9, 9, 9],
+            layout: &[
+                (Rw, 9, 0), (Gw, 9, 0), (Bw, 9, 0), (Rx, 8, 0), (Rw, 10, 10), (Gx, 8, 0),
+                (Gw, 10, 10), (Bx, 8, 0), (Bw, 10, 10),
+            ],
+        },
+        Bc6hMode {
+            transformed: true,
+            endpoint_bits: 12,
+            delta_bits: [8, 8, 8],
+            layout: &[
+                (Rw, 9, 0), (Gw, 9, 0), (Bw, 9, 0), (Rx, 7, 0), (Rw, 10, 11), (Gx, 7, 0),
+                (Gw, 10, 11), (Bx, 7, 0), (Bw, 10, 11),
+            ],
+        },
+        Bc6hMode {
+            transformed: true,
+            endpoint_bits: 16,
+            delta_bits: [4, 4, 4],
+            layout: &[
+                (Rw, 9, 0), (Gw, 9, 0), (Bw, 9, 0), (Rx, 3, 0), (Rw, 10, 15), (Gx, 3, 0),
+                (Gw, 10, 15), (Bx, 3, 0), (Bw, 10, 15),
+            ],
+        },
+    ]
+};
+
+fn sign_extend(value: i32, bits: u32) -> i32 { (value << (32 - bits)) >> (32 - bits) }
+
+/// Expand endpoint component to 16 bits, or 15 bits plus sign for signed formats.
+fn unquantize_bc6h(value: i32, bits: u32, signed: bool) -> i32 {
+    if !signed {
+        if bits >= 15 || value == 0 {
+            value
+        } else if value == (1 << bits) - 1 {
+            0xFFFF
+        } else {
+            ((value << 16) + 0x8000) >> bits
+        }
+    } else {
+        let magnitude = value.abs();
+        let unquantized = if bits >= 16 || magnitude == 0 {
+            magnitude
+        } else if magnitude >= (1 << (bits - 1)) - 1 {
+            0x7FFF
+        } else {
+            ((magnitude << 15) + 0x4000) >> (bits - 1)
+        };
+        unquantized * value.signum()
+    }
+}
+
+/// Scale interpolated value into the half float range, keeping its bits.
+fn finish_unquantize_bc6h(value: i32, signed: bool) -> f16 {
+    if !signed {
+        f16::from_bits(((value * 31) >> 6) as u16)
+    } else if value < 0 {
+        f16::from_bits(0x8000 | ((-value * 31) >> 5) as u16)
+    } else {
+        f16::from_bits(((value * 31) >> 5) as u16)
+    }
+}
+
+pub(super) fn decode_bc6h(block: &[u8], signed: bool, pixels: &mut [[f16; 4]]) {
+    let mut bits = BitReader::new(block);
+    let mut mode_bits = bits.read(2);
+    if mode_bits > 1 {
+        mode_bits |= bits.read(3) << 2;
+    }
+    let mode = match mode_bits {
+        0b00 => 0,
+        0b01 => 1,
+        0b00010 => 2,
+        0b00110 => 3,
+        0b01010 => 4,
+        0b01110 => 5,
+        0b10010 => 6,
+        0b10110 => 7,
+        0b11010 => 8,
+        0b11110 => 9,
+        0b00011 => 10,
+        0b00111 => 11,
+        0b01011 => 12,
+        0b01111 => 13,
+        //Reserved modes are decoded as black.
+        _ => {
+            pixels.fill([f16::ZERO, f16::ZERO, f16::ZERO, f16::ONE]);
+            return;
+        }
+    };
+    let m = &BC6H_MODES[mode];
+
+    //Endpoints in w, x, y, z order followed by the partition index.
+    let mut fields = [0i32; 13];
+    for &(field, first, last) in m.layout {
+        let mut bit = last as i32;
+        let step = if first <= last { -1 } else { 1 };
+        loop {
+            fields[field as usize] |= (bits.read(1) as i32) << bit;
+            if bit == first as i32 {
+                break;
+            }
+            bit += step;
+        }
+    }
+    let partition = fields[Field::D as usize] as usize;
+    let subsets = if mode >= 10 { 1 } else { 2 };
+    let mut endpoints = [[0i32; 3]; 4];
+    for (i, endpoint) in endpoints[..subsets * 2].iter_mut().enumerate() {
+        endpoint.copy_from_slice(&fields[i * 3..i * 3 + 3]);
+    }
+
+    let bits_mask = (1 << m.endpoint_bits) - 1;
+    for channel in 0..3 {
+        if signed {
+            endpoints[0][channel] = sign_extend(endpoints[0][channel], m.endpoint_bits);
+        }
+        let base = endpoints[0][channel];
+        for endpoint in &mut endpoints[1..subsets * 2] {
+            let value = &mut endpoint[channel];
+            if m.transformed {
+                let delta = sign_extend(*value, m.delta_bits[channel]);
+                *value = (base + delta) & bits_mask;
+            }
+            if signed {
+                *value = sign_extend(*value, m.endpoint_bits);
+            }
+        }
+    }
+    for endpoint in &mut endpoints[..subsets * 2] {
+        for value in endpoint.iter_mut() {
+            *value = unquantize_bc6h(*value, m.endpoint_bits, signed);
+        }
+    }
+
+    let index_bits = if subsets == 1 { 4 } else { 3 };
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        let index = bits.read(index_bits - is_anchor(subsets, partition, i) as u32);
+        let subset = subset_of(subsets, partition, i);
+        let (a, b) = (endpoints[subset * 2], endpoints[subset * 2 + 1]);
+        for channel in 0..3 {
+            let value = interpolate(a[channel], b[channel], index, index_bits);
+            pixel[channel] = finish_unquantize_bc6h(value, signed);
+        }
+        pixel[3] = f16::ONE;
+    }
+}
@@ -0,0 +1,240 @@
+use half::f16;
+
+/// Intensity modifiers of ETC1 and ETC2 subblocks.
+const MODIFIERS: [[i32; 4]; 8] = [
+    [2, 8, -2, -8],
+    [5, 17, -5, -17],
+    [9, 29, -9, -29],
+    [13, 42, -13, -42],
+    [18, 60, -18, -60],
+    [24, 80, -24, -80],
+    [33, 106, -33, -106],
+    [47, 183, -47, -183],
+];
+
+/// Distances between paint colors of the T and H modes.
+const DISTANCES: [i32; 8] = [3, 6, 11, 16, 23, 32, 41, 64];
+
+/// Modifiers of EAC alpha and R11 blocks.
+const EAC_MODIFIERS: [[i32; 8]; 16] = [
+    [-3, -6, -9, -15, 2, 5, 8, 14],
+    [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12],
+    [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11],
+    [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10],
+    [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9],
+    [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9],
+    [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9],
+    [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8],
+    [-3, -5, -7, -9, 2, 4, 6, 8],
+];
+
+fn extend_4(value: u32) -> i32 { (value << 4 | value) as i32 }
+fn extend_5(value: u32) -> i32 { (value << 3 | value >> 2) as i32 }
+fn extend_6(value: u32) -> i32 { (value << 2 | value >> 4) as i32 }
+fn extend_7(value: u32) -> i32 { (value << 1 | value >> 6) as i32 }
+
+fn offset_color(color: [i32; 3], offset: i32) -> [u8; 4] {
+    let channel = |x: i32| (x + offset).clamp(0, 255) as u8;
+    [channel(color[0]), channel(color[1]), channel(color[2]), 255]
+}
+
+/// Decode an ETC2 color block. With punchthrough alpha the differential bit tells whether the
+/// block is opaque, and transparent pixels are black.
+fn decode_color(block: &[u8], pixels: &mut [[u8; 4]], punchthrough: bool) {
+    let bits = u64::from_be_bytes(block[..8].try_into().unwrap());
+    let (high, low) = ((bits >> 32) as u32, bits as u32);
+    let field = |shift: u32, count: u32| (high >> shift) & ((1 << count) - 1);
+    let differential = field(1, 1) == 1;
+    let opaque = !punchthrough || differential;
+    let flip = field(0, 1) == 1;
+    //Pixel indices are stored column by column, with their high bits first.
+    let index = |x: usize, y: usize| {
+        let i = x * 4 + y;
+        ((low >> (i + 16)) & 1) << 1 | (low >> i) & 1
+    };
+    let transparent = |i: u32| !opaque && i == 2;
+
+    if !punchthrough && !differential {
+        let base = [
+            [field(28, 4), field(20, 4), field(12, 4)].map(extend_4),
+            [field(24, 4), field(16, 4), field(8, 4)].map(extend_4),
+        ];
+        let tables = [field(5, 3), field(2, 3)];
+        return decode_subblocks(pixels, base, tables, flip, index, opaque);
+    }
+
+    let delta = |shift: u32| ((field(shift, 3) as i32) << 29) >> 29;
+    let (r, g, b) = (
+        field(27, 5) as i32,
+        field(19, 5) as i32,
+        field(11, 5) as i32,
+    );
+    let (r2, g2, b2) = (r + delta(24), g + delta(16), b + delta(8));
+    if !(0..32).contains(&r2) {
+        //T mode.
+        let c1 = [field(27, 2) << 2 | field(24, 2), field(20, 4), field(16, 4)].map(extend_4);
+        let c2 = [field(12, 4), field(8, 4), field(4, 4)].map(extend_4);
+        let distance = DISTANCES[(field(2, 2) << 1 | field(0, 1)) as usize];
+        let paint = [
+            offset_color(c1, 0),
+            offset_color(c2, distance),
+            offset_color(c2, 0),
+            offset_color(c2, -distance),
+        ];
+        decode_paint(pixels, paint, index, transparent);
+    } else if !(0..32).contains(&g2) {
+        //H mode.
+        let c1 = [
+            field(27, 4),
+            field(24, 3) << 1 | field(20, 1),
+            field(19, 1) << 3 | field(15, 3),
+        ];
+        let c2 = [field(11, 4), field(7, 4), field(3, 4)];
+        let order = (c1[0] << 8 | c1[1] << 4 | c1[2]) >= (c2[0] << 8 | c2[1] << 4 | c2[2]);
+        let distance = DISTANCES[(field(2, 1) << 2 | field(0, 1) << 1 | order as u32) as usize];
+        let (c1, c2) = (c1.map(extend_4), c2.map(extend_4));
+        let paint = [
+            offset_color(c1, distance),
+            offset_color(c1, -distance),
+            offset_color(c2, distance),
+            offset_color(c2, -distance),
+        ];
+        decode_paint(pixels, paint, index, transparent);
+    } else if !(0..32).contains(&b2) {
+        //Planar mode, always opaque.
+        let origin = [
+            extend_6(field(25, 6)),
+            extend_7(field(24, 1) << 6 | field(17, 6)),
+            extend_6(field(16, 1) << 5 | field(11, 2) << 3 | field(7, 3)),
+        ];
+        let horizontal = [
+            extend_6(field(2, 5) << 1 | field(0, 1)),
+            extend_7((low >> 25) & 0x7F),
+            extend_6((low >> 19) & 0x3F),
+        ];
+        let vertical = [
+            extend_6((low >> 13) & 0x3F),
+            extend_7((low >> 6) & 0x7F),
+            extend_6(low & 0x3F),
+        ];
+        for (i, pixel) in pixels.iter_mut().enumerate() {
+            let (x, y) = ((i % 4) as i32, (i / 4) as i32);
+            let channel = |c: usize| {
+                let (o, h, v) = (origin[c], horizontal[c], vertical[c]);
+                ((x * (h - o) + y * (v - o) + 4 * o + 2) >> 2).clamp(0, 255) as u8
+            };
+            *pixel = [channel(0), channel(1), channel(2), 255];
+        }
+    } else {
+        let base = [[r, g, b], [r2, g2, b2]].map(|x| x.map(|c| extend_5(c as u32)));
+        let tables = [field(5, 3), field(2, 3)];
+        decode_subblocks(pixels, base, tables, flip, index, opaque);
+    }
+}
+
+/// Decode individual and differential blocks made of two subblocks.
+fn decode_subblocks(
+    pixels: &mut [[u8; 4]], base: [[i32; 3]; 2], tables: [u32; 2], flip: bool,
+    index: impl Fn(usize, usize) -> u32, opaque: bool,
+) {
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        let (x, y) = (i % 4, i / 4);
+        let subblock = if flip { y / 2 } else { x / 2 };
+        let index = index(x, y);
+        let mut modifiers = MODIFIERS[tables[subblock] as usize];
+        if !opaque {
+            //Punchthrough blocks replace the small modifiers with the base color and transparency.
+            if index == 2 {
+                *pixel = [0; 4];
+                continue;
+            }
+            modifiers[0] = 0;
+        }
+        *pixel = offset_color(base[subblock], modifiers[index as usize]);
+    }
+}
+
+/// Decode T and H blocks, which choose one of four colors for each pixel.
+fn decode_paint(
+    pixels: &mut [[u8; 4]], paint: [[u8; 4]; 4], index: impl Fn(usize, usize) -> u32,
+    transparent: impl Fn(u32) -> bool,
+) {
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        let index = index(i % 4, i / 4);
+        *pixel = if transparent(index) {
+            [0; 4]
+        } else {
+            paint[index as usize]
+        };
+    }
+}
+
+/// Base value, multiplier, modifier table and modifier index of each pixel in an EAC block.
+fn decode_eac(block: &[u8]) -> (u8, i32, &'static [i32; 8], [usize; 16]) {
+    let bits = u64::from_be_bytes(block[..8].try_into().unwrap());
+    let mut indices = [0; 16];
+    for (i, index) in indices.iter_mut().enumerate() {
+        let (x, y) = (i % 4, i / 4);
+        *index = (bits >> (45 - 3 * (x * 4 + y))) as usize & 7;
+    }
+    let table = &EAC_MODIFIERS[(bits >> 48) as usize & 15];
+    ((bits >> 56) as u8, (bits >> 52) as i32 & 15, table, indices)
+}
+
+/// Decode an EAC block with 11 bits of precision into normalized values.
+fn decode_eac_11(block: &[u8], signed: bool) -> [f16; 16] {
+    let (base, multiplier, table, indices) = decode_eac(block);
+    //A multiplier of 0 applies the modifiers without scaling them.
+    let scale = |modifier: i32| {
+        if multiplier == 0 {
+            modifier
+        } else {
+            modifier * multiplier * 8
+        }
+    };
+    indices.map(|index| {
+        if signed {
+            let base = (base as i8).max(-127) as i32;
+            let value = (base * 8 + scale(table[index])).clamp(-1023, 1023);
+            f16::from_f32(value as f32 / 1023.0)
+        } else {
+            let value = (base as i32 * 8 + 4 + scale(table[index])).clamp(0, 2047);
+            f16::from_f32(value as f32 / 2047.0)
+        }
+    })
+}
+
+pub(super) fn decode_etc2_rgb(block: &[u8], pixels: &mut [[u8; 4]]) {
+    decode_color(block, pixels, false);
+}
+
+pub(super) fn decode_etc2_rgb_a1(block: &[u8], pixels: &mut [[u8; 4]]) {
+    decode_color(block, pixels, true);
+}
+
+pub(super) fn decode_etc2_rgba(block: &[u8], pixels: &mut [[u8; 4]]) {
+    decode_color(&block[8..], pixels, false);
+    let (base, multiplier, table, indices) = decode_eac(block);
+    for (pixel, index) in pixels.iter_mut().zip(indices) {
+        pixel[3] = (base as i32 + table[index] * multiplier).clamp(0, 255) as u8;
+    }
+}
+
+pub(super) fn decode_eac_r11(block: &[u8], signed: bool, pixels: &mut [f16]) {
+    pixels.copy_from_slice(&decode_eac_11(block, signed));
+}
+
+pub(super) fn decode_eac_rg11(block: &[u8], signed: bool, pixels: &mut [[f16; 2]]) {
+    let red = decode_eac_11(&block[..8], signed);
+    let green = decode_eac_11(&block[8..], signed);
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        *pixel = [red[i], green[i]];
+    }
+}
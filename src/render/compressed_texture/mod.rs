@@ -0,0 +1,514 @@
+use std::{error::Error, fs, path::Path};
+
+use ddsfile::{Caps2, D3DFormat, Dds, DxgiFormat, MiscFlag};
+use glam::UVec2;
+use half::f16;
+use wgpu::{AstcBlock, AstcChannel, Features, TextureFormat};
+
+use crate::{GpuCtx, Texture, TextureDesc, TextureSize, TextureUsage};
+
+mod astc;
+mod bcn;
+mod etc2;
+
+/// Compression features used by compressed texture containers. Requested when creating the device
+/// if the adapter supports them.
+pub const TEXTURE_COMPRESSION_FEATURES: Features = Features::TEXTURE_COMPRESSION_BC
+    .union(Features::TEXTURE_COMPRESSION_ETC2)
+    .union(Features::TEXTURE_COMPRESSION_ASTC);
+
+/// Raw texture data read from a texture container, before being uploaded to the GPU.
+struct TextureContainer {
+    format: TextureFormat,
+    size: UVec2,
+    layers: u32,
+    is_cube: bool,
+    /// Data for each mip level, containing all layers one after another.
+    levels: Vec<Vec<u8>>,
+}
+
+impl Texture {
+    /// Load a KTX2 texture from path. See [`Texture::from_ktx2_bytes`].
+    pub fn from_ktx2_path(gpu: &GpuCtx, path: &Path) -> Result<Self, Box<dyn Error>> {
+        Self::from_ktx2_bytes(gpu, &fs::read(path)?)
+    }
+
+    /// Load a KTX2 texture with all its mip levels, array layers and cube faces. Block compressed
+    /// formats are uploaded as they are. If the GPU does not support the compression format, the
+    /// texture is decompressed in the CPU with [`Texture::decompress_level`]. Supercompressed
+    /// files are not supported.
+    pub fn from_ktx2_bytes(gpu: &GpuCtx, bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let reader = ktx2::Reader::new(bytes).map_err(|e| format!("Invalid KTX2 file: {:?}", e))?;
+        let header = reader.header();
+        if let Some(scheme) = header.supercompression_scheme {
+            return Err(format!("Unsupported KTX2 supercompression {:?}.", scheme).into());
+        }
+        if header.pixel_depth > 1 {
+            return Err("3D KTX2 textures are not supported.".into());
+        }
+        let format = header
+            .format
+            .ok_or("KTX2 file without format is not supported.")?;
+        let format = ktx2_to_wgpu_format(format)
+            .ok_or_else(|| format!("Unsupported KTX2 format {:?}.", format))?;
+
+        let container = TextureContainer {
+            format,
+            size: UVec2::new(header.pixel_width, header.pixel_height.max(1)),
+            layers: header.layer_count.max(1) * header.face_count,
+            is_cube: header.face_count == 6,
+            levels: reader.levels().map(|x| x.to_vec()).collect(),
+        };
+        Self::from_container(gpu, container)
+    }
+
+    /// Load a DDS texture from path. See [`Texture::from_dds_bytes`].
+    pub fn from_dds_path(gpu: &GpuCtx, path: &Path) -> Result<Self, Box<dyn Error>> {
+        Self::from_dds_bytes(gpu, &fs::read(path)?)
+    }
+
+    /// Load a DDS texture with all its mip levels, array layers and cube faces. Same as KTX2 files,
+    /// compressed formats fallback to CPU decompression when the GPU does not support them.
+    pub fn from_dds_bytes(gpu: &GpuCtx, bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let dds = Dds::read(bytes)?;
+        if dds.get_depth() > 1 {
+            return Err("3D DDS textures are not supported.".into());
+        }
+        let format = if let Some(format) = dds.get_dxgi_format() {
+            dxgi_to_wgpu_format(format)
+                .ok_or_else(|| format!("Unsupported DDS format {:?}.", format))?
+        } else {
+            let format = dds.get_d3d_format().ok_or("Unknown DDS format.")?;
+            d3d_to_wgpu_format(format)
+                .ok_or_else(|| format!("Unsupported DDS format {:?}.", format))?
+        };
+
+        let is_cube = dds.header.caps2.contains(Caps2::CUBEMAP)
+            || dds
+                .header10
+                .as_ref()
+                .is_some_and(|x| x.misc_flag.contains(MiscFlag::TEXTURECUBE));
+        let mut layers = dds.get_num_array_layers();
+        if is_cube && dds.header10.is_some() {
+            layers *= 6;
+        }
+        let size = UVec2::new(dds.get_width(), dds.get_height());
+        let mip_count = dds.get_num_mipmap_levels().max(1);
+
+        //DDS stores every mip level for each layer, we need every layer for each mip level.
+        let mip_sizes = (0..mip_count)
+            .map(|mip| level_layer_size(format, size, mip))
+            .collect::<Vec<_>>();
+        let layer_stride = mip_sizes.iter().sum::<usize>();
+        if dds.data.len() < layer_stride * layers as usize {
+            return Err("DDS file is smaller than expected.".into());
+        }
+        let levels = mip_sizes
+            .iter()
+            .enumerate()
+            .map(|(mip, mip_size)| {
+                let mip_offset = mip_sizes[..mip].iter().sum::<usize>();
+                (0..layers as usize)
+                    .flat_map(|layer| {
+                        let start = layer * layer_stride + mip_offset;
+                        dds.data[start..start + mip_size].iter().copied()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let container = TextureContainer {
+            format,
+            size,
+            layers,
+            is_cube,
+            levels,
+        };
+        Self::from_container(gpu, container)
+    }
+
+    /// Decompress a mip level of a block compressed format in the CPU, with its layers stored one
+    /// after another. Returns the uncompressed format used and its pixels. BC6H and EAC formats
+    /// are decompressed into float formats and the rest into 8 bit formats. HDR ASTC formats are
+    /// not supported.
+    ///
+    /// ```
+    /// use glam::UVec2;
+    /// use tridify_rs::Texture;
+    /// use wgpu::TextureFormat;
+    ///
+    /// //BC1 block with red and blue endpoints. The top row uses the first endpoint, the second
+    /// //row the second one and the last rows the colors in between.
+    /// let block = [0x00, 0xF8, 0x1F, 0x00, 0b00000000, 0b01010101, 0b10101010, 0b11111111];
+    /// let (format, pixels) =
+    ///     Texture::decompress_level(TextureFormat::Bc1RgbaUnorm, UVec2::new(4, 4), 1, &block)
+    ///         .unwrap();
+    /// assert_eq!(format, TextureFormat::Rgba8Unorm);
+    /// assert_eq!(pixels.len(), 4 * 4 * 4);
+    /// assert_eq!(pixels[0..4], [255, 0, 0, 255]);
+    /// assert_eq!(pixels[16..20], [0, 0, 255, 255]);
+    /// assert_eq!(pixels[32..36], [170, 0, 85, 255]);
+    /// assert_eq!(pixels[48..52], [85, 0, 170, 255]);
+    /// ```
+    ///
+    /// Blocks using partitions, signed endpoints, the ETC2 T, H and planar modes and ASTC weight
+    /// grids smaller than the block, built by hand from the format specifications.
+    ///
+    /// ```
+    /// use glam::UVec2;
+    /// use tridify_rs::Texture;
+    /// use wgpu::{AstcBlock, AstcChannel, TextureFormat};
+    ///
+    /// let decode = |format, block: &[u8]| {
+    ///     let size = UVec2::new(4, 4);
+    ///     Texture::decompress_level(format, size, 1, block).unwrap().1
+    /// };
+    /// let rgba8 = |pixels: &[u8], x: usize, y: usize| pixels[(y * 4 + x) * 4..][..4].to_vec();
+    ///
+    /// //BC7 mode 1 with partition 13, which gives the two top rows to the first subset. Subsets
+    /// //use red and blue 6 bit endpoints with a 0 P-bit, which expand to 253.
+    /// let block = [
+    ///     0x36, 0x3F, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ///     0xF0, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    /// ];
+    /// let pixels = decode(TextureFormat::Bc7RgbaUnorm, &block);
+    /// for x in 0..4 {
+    ///     assert_eq!(rgba8(&pixels, x, 1), [253, 0, 0, 255]);
+    ///     assert_eq!(rgba8(&pixels, x, 2), [0, 0, 253, 255]);
+    /// }
+    ///
+    /// //BC6H mode 11 with a first endpoint of 511, -511 and 0 as 10 bit signed values, which
+    /// //saturate to the largest half float. The last pixel uses the second endpoint, which is 0.
+    /// let block = [
+    ///     0xE3, 0xBF, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00,
+    ///     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xF0,
+    /// ];
+    /// let pixels = decode(TextureFormat::Bc6hRgbFloat, &block)
+    ///     .chunks(2)
+    ///     .map(|x| half::f16::from_le_bytes([x[0], x[1]]).to_f32())
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(pixels[..4], [65504.0, -65504.0, 0.0, 1.0]);
+    /// assert_eq!(pixels[60..], [0.0, 0.0, 0.0, 1.0]);
+    ///
+    /// //ETC2 T mode, the red channel overflows in differential mode. The top row uses the four
+    /// //paint colors: green, then gray plus, equal and minus the first distance of 3.
+    /// let pixels = decode(
+    ///     TextureFormat::Etc2Rgb8Unorm,
+    ///     &[0x04, 0xF0, 0x88, 0x82, 0x11, 0x00, 0x10, 0x10],
+    /// );
+    /// assert_eq!(rgba8(&pixels, 0, 0), [0, 255, 0, 255]);
+    /// assert_eq!(rgba8(&pixels, 1, 0), [139, 139, 139, 255]);
+    /// assert_eq!(rgba8(&pixels, 2, 0), [136, 136, 136, 255]);
+    /// assert_eq!(rgba8(&pixels, 3, 0), [133, 133, 133, 255]);
+    ///
+    /// //ETC2 H mode, the green channel overflows. Red is greater than blue, so the distance index
+    /// //gets a low bit of 1 and paint colors are red and blue plus and minus 6.
+    /// let pixels = decode(
+    ///     TextureFormat::Etc2Rgb8Unorm,
+    ///     &[0x40, 0x04, 0x00, 0x42, 0x11, 0x00, 0x10, 0x10],
+    /// );
+    /// assert_eq!(rgba8(&pixels, 0, 0), [142, 6, 6, 255]);
+    /// assert_eq!(rgba8(&pixels, 1, 0), [130, 0, 0, 255]);
+    /// assert_eq!(rgba8(&pixels, 2, 0), [6, 6, 142, 255]);
+    /// assert_eq!(rgba8(&pixels, 3, 0), [0, 0, 130, 255]);
+    ///
+    /// //ETC2 planar mode, the blue channel overflows. Red goes from 0 at the origin to 255 at the
+    /// //horizontal color, one pixel past the block.
+    /// let pixels = decode(
+    ///     TextureFormat::Etc2Rgb8Unorm,
+    ///     &[0x00, 0x00, 0x04, 0x7F, 0x00, 0x00, 0x00, 0x00],
+    /// );
+    /// for y in 0..4 {
+    ///     let row = (0..4).map(|x| rgba8(&pixels, x, y)[0]).collect::<Vec<_>>();
+    ///     assert_eq!(row, [0, 64, 128, 191]);
+    /// }
+    ///
+    /// //ASTC 4x4 block with luminance endpoints of 0 and 255 and a 3x3 grid of 3 bit weights,
+    /// //0 for the top left 2x2 and the bottom left corner and 7 for the rest. Pixels between
+    /// //grid points get bilinearly interpolated weights.
+    /// let block = [
+    ///     0xBF, 0x01, 0x00, 0xFE, 0x01, 0x00, 0x00, 0x00,
+    ///     0x00, 0x00, 0x00, 0x00, 0xE0, 0xC7, 0x81, 0x03,
+    /// ];
+    /// let format = TextureFormat::Astc {
+    ///     block: AstcBlock::B4x4,
+    ///     channel: AstcChannel::Unorm,
+    /// };
+    /// let pixels = decode(format, &block);
+    /// let luminance = |x, y| rgba8(&pixels, x, y)[0];
+    /// assert_eq!((0..4).map(|x| luminance(x, 0)).collect::<Vec<_>>(), [0, 0, 80, 255]);
+    /// assert_eq!([luminance(1, 2), luminance(2, 2), luminance(1, 3)], [48, 128, 175]);
+    /// assert_eq!(rgba8(&pixels, 3, 3), [255, 255, 255, 255]);
+    /// ```
+    pub fn decompress_level(
+        format: TextureFormat, size: UVec2, layers: u32, data: &[u8],
+    ) -> Result<(TextureFormat, Vec<u8>), Box<dyn Error>> {
+        let (decoded_format, decode_block) = block_decoder(format)?;
+        let block_size = format.block_size(None).unwrap() as usize;
+        let pixel_size = decoded_format.block_size(None).unwrap() as usize;
+        let (bw, bh) = format.block_dimensions();
+        let blocks = UVec2::new(size.x.div_ceil(bw), size.y.div_ceil(bh));
+        let layer_size = (blocks.x * blocks.y) as usize * block_size;
+        if data.len() < layer_size * layers as usize {
+            return Err("Texture level is smaller than expected.".into());
+        }
+
+        let mut pixels = vec![0u8; (size.x * size.y * layers) as usize * pixel_size];
+        let mut block_pixels = vec![0u8; (bw * bh) as usize * pixel_size];
+        for layer in 0..layers {
+            let layer_data = &data[layer as usize * layer_size..][..layer_size];
+            for (i, block) in layer_data.chunks_exact(block_size).enumerate() {
+                decode_block(block, &mut block_pixels);
+                let block_pos = UVec2::new(i as u32 % blocks.x * bw, i as u32 / blocks.x * bh);
+                for (j, pixel) in block_pixels.chunks_exact(pixel_size).enumerate() {
+                    let x = block_pos.x + j as u32 % bw;
+                    let y = block_pos.y + j as u32 / bw;
+                    if x >= size.x || y >= size.y {
+                        continue;
+                    }
+                    let index = ((layer * size.y + y) * size.x + x) as usize * pixel_size;
+                    pixels[index..index + pixel_size].copy_from_slice(pixel);
+                }
+            }
+        }
+        Ok((decoded_format, pixels))
+    }
+
+    fn from_container(
+        gpu: &GpuCtx, mut container: TextureContainer,
+    ) -> Result<Self, Box<dyn Error>> {
+        if !gpu
+            .device
+            .features()
+            .contains(container.format.required_features())
+        {
+            decompress_container(&mut container)?;
+        }
+
+        let size = if container.is_cube && container.layers == 6 {
+            TextureSize::Cube(container.size)
+        } else if container.layers > 1 {
+            TextureSize::D2Array(container.size.extend(container.layers))
+        } else {
+            TextureSize::D2(container.size)
+        };
+        let desc = TextureDesc {
+            size,
+            usage: TextureUsage::TEXTURE_BIND | TextureUsage::DESTINATION,
+            format: container.format,
+            mip_level_count: container.levels.len() as u32,
+        };
+        let texture = Self::new(gpu, desc, None);
+        for (mip, data) in container.levels.iter().enumerate() {
+            texture.write_mip_pixels(gpu, data, mip as u32);
+        }
+        Ok(texture)
+    }
+}
+
+/// Bytes used by a single layer of the given mip level.
+fn level_layer_size(format: TextureFormat, size: UVec2, mip_level: u32) -> usize {
+    let (bw, bh) = format.block_dimensions();
+    let block_size = format.block_size(None).unwrap_or(0);
+    let w = (size.x >> mip_level).max(1);
+    let h = (size.y >> mip_level).max(1);
+    (w.div_ceil(bw) * h.div_ceil(bh) * block_size) as usize
+}
+
+/// Decodes a single compressed block into its pixels row by row, using the decoded format.
+type BlockDecoder = Box<dyn Fn(&[u8], &mut [u8])>;
+
+fn rgba8_decoder(decode: fn(&[u8], &mut [[u8; 4]])) -> BlockDecoder {
+    Box::new(move |block, pixels| decode(block, bytemuck::cast_slice_mut(pixels)))
+}
+
+/// Uncompressed format and decoder used to decompress a format in the CPU.
+fn block_decoder(format: TextureFormat) -> Result<(TextureFormat, BlockDecoder), Box<dyn Error>> {
+    use TextureFormat as F;
+    let rgba8 = if format.is_srgb() {
+        F::Rgba8UnormSrgb
+    } else {
+        F::Rgba8Unorm
+    };
+    let decoder = match format {
+        F::Bc1RgbaUnorm | F::Bc1RgbaUnormSrgb => (rgba8, rgba8_decoder(bcn::decode_bc1)),
+        F::Bc2RgbaUnorm | F::Bc2RgbaUnormSrgb => (rgba8, rgba8_decoder(bcn::decode_bc2)),
+        F::Bc3RgbaUnorm | F::Bc3RgbaUnormSrgb => (rgba8, rgba8_decoder(bcn::decode_bc3)),
+        F::Bc4RUnorm => (rgba8, rgba8_decoder(bcn::decode_bc4_unorm)),
+        F::Bc4RSnorm => (F::Rgba8Snorm, rgba8_decoder(bcn::decode_bc4_snorm)),
+        F::Bc5RgUnorm => (rgba8, rgba8_decoder(bcn::decode_bc5_unorm)),
+        F::Bc5RgSnorm => (F::Rgba8Snorm, rgba8_decoder(bcn::decode_bc5_snorm)),
+        F::Bc6hRgbUfloat | F::Bc6hRgbFloat => {
+            let signed = format == F::Bc6hRgbFloat;
+            let decode: BlockDecoder = Box::new(move |block, pixels| {
+                let mut decoded = [[f16::ZERO; 4]; 16];
+                bcn::decode_bc6h(block, signed, &mut decoded);
+                pixels.copy_from_slice(bytemuck::cast_slice(&decoded));
+            });
+            (F::Rgba16Float, decode)
+        }
+        F::Bc7RgbaUnorm | F::Bc7RgbaUnormSrgb => (rgba8, rgba8_decoder(bcn::decode_bc7)),
+        F::Etc2Rgb8Unorm | F::Etc2Rgb8UnormSrgb => (rgba8, rgba8_decoder(etc2::decode_etc2_rgb)),
+        F::Etc2Rgb8A1Unorm | F::Etc2Rgb8A1UnormSrgb => {
+            (rgba8, rgba8_decoder(etc2::decode_etc2_rgb_a1))
+        }
+        F::Etc2Rgba8Unorm | F::Etc2Rgba8UnormSrgb => (rgba8, rgba8_decoder(etc2::decode_etc2_rgba)),
+        F::EacR11Unorm | F::EacR11Snorm => {
+            let signed = format == F::EacR11Snorm;
+            let decode: BlockDecoder = Box::new(move |block, pixels| {
+                let mut decoded = [f16::ZERO; 16];
+                etc2::decode_eac_r11(block, signed, &mut decoded);
+                pixels.copy_from_slice(bytemuck::cast_slice(&decoded));
+            });
+            (F::R16Float, decode)
+        }
+        F::EacRg11Unorm | F::EacRg11Snorm => {
+            let signed = format == F::EacRg11Snorm;
+            let decode: BlockDecoder = Box::new(move |block, pixels| {
+                let mut decoded = [[f16::ZERO; 2]; 16];
+                etc2::decode_eac_rg11(block, signed, &mut decoded);
+                pixels.copy_from_slice(bytemuck::cast_slice(&decoded));
+            });
+            (F::Rg16Float, decode)
+        }
+        F::Astc {
+            channel: AstcChannel::Hdr,
+            ..
+        } => return Err("HDR ASTC textures can't be decompressed in the CPU.".into()),
+        F::Astc { .. } => {
+            let srgb = format.is_srgb();
+            let (width, height) = format.block_dimensions();
+            let size = UVec2::new(width, height);
+            let decode: BlockDecoder = Box::new(move |block, pixels| {
+                astc::decode_astc(block, size, srgb, bytemuck::cast_slice_mut(pixels))
+            });
+            (rgba8, decode)
+        }
+        format => return Err(format!("{:?} is not a compressed format.", format).into()),
+    };
+    Ok(decoder)
+}
+
+/// Decompress container data in the CPU.
+fn decompress_container(container: &mut TextureContainer) -> Result<(), Box<dyn Error>> {
+    let mut decoded_format = container.format;
+    for (mip, level) in container.levels.iter_mut().enumerate() {
+        let size = (container.size >> mip as u32).max(UVec2::ONE);
+        let (format, pixels) =
+            Texture::decompress_level(container.format, size, container.layers, level)?;
+        decoded_format = format;
+        *level = pixels;
+    }
+    container.format = decoded_format;
+    Ok(())
+}
+
+fn ktx2_to_wgpu_format(format: ktx2::Format) -> Option<TextureFormat> {
+    use ktx2::Format as F;
+    let format = match format {
+        F::R8_UNORM => TextureFormat::R8Unorm,
+        F::R8G8_UNORM => TextureFormat::Rg8Unorm,
+        F::R8G8B8A8_UNORM => TextureFormat::Rgba8Unorm,
+        F::R8G8B8A8_SRGB => TextureFormat::Rgba8UnormSrgb,
+        F::B8G8R8A8_UNORM => TextureFormat::Bgra8Unorm,
+        F::B8G8R8A8_SRGB => TextureFormat::Bgra8UnormSrgb,
+        F::R16G16B16A16_SFLOAT => TextureFormat::Rgba16Float,
+        F::R32G32B32A32_SFLOAT => TextureFormat::Rgba32Float,
+        F::BC1_RGB_UNORM_BLOCK | F::BC1_RGBA_UNORM_BLOCK => TextureFormat::Bc1RgbaUnorm,
+        F::BC1_RGB_SRGB_BLOCK | F::BC1_RGBA_SRGB_BLOCK => TextureFormat::Bc1RgbaUnormSrgb,
+        F::BC2_UNORM_BLOCK => TextureFormat::Bc2RgbaUnorm,
+        F::BC2_SRGB_BLOCK => TextureFormat::Bc2RgbaUnormSrgb,
+        F::BC3_UNORM_BLOCK => TextureFormat::Bc3RgbaUnorm,
+        F::BC3_SRGB_BLOCK => TextureFormat::Bc3RgbaUnormSrgb,
+        F::BC4_UNORM_BLOCK => TextureFormat::Bc4RUnorm,
+        F::BC4_SNORM_BLOCK => TextureFormat::Bc4RSnorm,
+        F::BC5_UNORM_BLOCK => TextureFormat::Bc5RgUnorm,
+        F::BC5_SNORM_BLOCK => TextureFormat::Bc5RgSnorm,
+        F::BC6H_UFLOAT_BLOCK => TextureFormat::Bc6hRgbUfloat,
+        F::BC6H_SFLOAT_BLOCK => TextureFormat::Bc6hRgbFloat,
+        F::BC7_UNORM_BLOCK => TextureFormat::Bc7RgbaUnorm,
+        F::BC7_SRGB_BLOCK => TextureFormat::Bc7RgbaUnormSrgb,
+        F::ETC2_R8G8B8_UNORM_BLOCK => TextureFormat::Etc2Rgb8Unorm,
+        F::ETC2_R8G8B8_SRGB_BLOCK => TextureFormat::Etc2Rgb8UnormSrgb,
+        F::ETC2_R8G8B8A1_UNORM_BLOCK => TextureFormat::Etc2Rgb8A1Unorm,
+        F::ETC2_R8G8B8A1_SRGB_BLOCK => TextureFormat::Etc2Rgb8A1UnormSrgb,
+        F::ETC2_R8G8B8A8_UNORM_BLOCK => TextureFormat::Etc2Rgba8Unorm,
+        F::ETC2_R8G8B8A8_SRGB_BLOCK => TextureFormat::Etc2Rgba8UnormSrgb,
+        F::EAC_R11_UNORM_BLOCK => TextureFormat::EacR11Unorm,
+        F::EAC_R11_SNORM_BLOCK => TextureFormat::EacR11Snorm,
+        F::EAC_R11G11_UNORM_BLOCK => TextureFormat::EacRg11Unorm,
+        F::EAC_R11G11_SNORM_BLOCK => TextureFormat::EacRg11Snorm,
+        format => {
+            //ASTC formats are stored in pairs of UNORM and SRGB following the block size order.
+            let index = format
+                .0
+                .get()
+                .checked_sub(F::ASTC_4x4_UNORM_BLOCK.0.get())?;
+            let block = match index / 2 {
+                0 => AstcBlock::B4x4,
+                1 => AstcBlock::B5x4,
+                2 => AstcBlock::B5x5,
+                3 => AstcBlock::B6x5,
+                4 => AstcBlock::B6x6,
+                5 => AstcBlock::B8x5,
+                6 => AstcBlock::B8x6,
+                7 => AstcBlock::B8x8,
+                8 => AstcBlock::B10x5,
+                9 => AstcBlock::B10x6,
+                10 => AstcBlock::B10x8,
+                11 => AstcBlock::B10x10,
+                12 => AstcBlock::B12x10,
+                13 => AstcBlock::B12x12,
+                _ => return None,
+            };
+            let channel = if index % 2 == 0 {
+                AstcChannel::Unorm
+            } else {
+                AstcChannel::UnormSrgb
+            };
+            TextureFormat::Astc { block, channel }
+        }
+    };
+    Some(format)
+}
+
+fn dxgi_to_wgpu_format(format: DxgiFormat) -> Option<TextureFormat> {
+    let format = match format {
+        DxgiFormat::R8_UNorm => TextureFormat::R8Unorm,
+        DxgiFormat::R8G8_UNorm => TextureFormat::Rg8Unorm,
+        DxgiFormat::R8G8B8A8_UNorm => TextureFormat::Rgba8Unorm,
+        DxgiFormat::R8G8B8A8_UNorm_sRGB => TextureFormat::Rgba8UnormSrgb,
+        DxgiFormat::B8G8R8A8_UNorm => TextureFormat::Bgra8Unorm,
+        DxgiFormat::B8G8R8A8_UNorm_sRGB => TextureFormat::Bgra8UnormSrgb,
+        DxgiFormat::R16G16B16A16_Float => TextureFormat::Rgba16Float,
+        DxgiFormat::R32G32B32A32_Float => TextureFormat::Rgba32Float,
+        DxgiFormat::BC1_Typeless | DxgiFormat::BC1_UNorm => TextureFormat::Bc1RgbaUnorm,
+        DxgiFormat::BC1_UNorm_sRGB => TextureFormat::Bc1RgbaUnormSrgb,
+        DxgiFormat::BC2_Typeless | DxgiFormat::BC2_UNorm => TextureFormat::Bc2RgbaUnorm,
+        DxgiFormat::BC2_UNorm_sRGB => TextureFormat::Bc2RgbaUnormSrgb,
+        DxgiFormat::BC3_Typeless | DxgiFormat::BC3_UNorm => TextureFormat::Bc3RgbaUnorm,
+        DxgiFormat::BC3_UNorm_sRGB => TextureFormat::Bc3RgbaUnormSrgb,
+        DxgiFormat::BC4_Typeless | DxgiFormat::BC4_UNorm => TextureFormat::Bc4RUnorm,
+        DxgiFormat::BC4_SNorm => TextureFormat::Bc4RSnorm,
+        DxgiFormat::BC5_Typeless | DxgiFormat::BC5_UNorm => TextureFormat::Bc5RgUnorm,
+        DxgiFormat::BC5_SNorm => TextureFormat::Bc5RgSnorm,
+        DxgiFormat::BC6H_Typeless | DxgiFormat::BC6H_UF16 => TextureFormat::Bc6hRgbUfloat,
+        DxgiFormat::BC6H_SF16 => TextureFormat::Bc6hRgbFloat,
+        DxgiFormat::BC7_Typeless | DxgiFormat::BC7_UNorm => TextureFormat::Bc7RgbaUnorm,
+        DxgiFormat::BC7_UNorm_sRGB => TextureFormat::Bc7RgbaUnormSrgb,
+        _ => return None,
+    };
+    Some(format)
+}
+
+fn d3d_to_wgpu_format(format: D3DFormat) -> Option<TextureFormat> {
+    let format = match format {
+        D3DFormat::A8B8G8R8 => TextureFormat::Rgba8Unorm,
+        D3DFormat::A8R8G8B8 => TextureFormat::Bgra8Unorm,
+        D3DFormat::DXT1 => TextureFormat::Bc1RgbaUnorm,
+        D3DFormat::DXT2 | D3DFormat::DXT3 => TextureFormat::Bc2RgbaUnorm,
+        D3DFormat::DXT4 | D3DFormat::DXT5 => TextureFormat::Bc3RgbaUnorm,
+        _ => return None,
+    };
+    Some(format)
+}
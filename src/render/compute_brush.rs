@@ -0,0 +1,119 @@
+use std::{borrow::Cow, collections::HashMap, error::Error, fs::File, io::Read, path::Path};
+
+use wgpu::{
+    BindGroup, ComputePipeline, ComputePipelineDescriptor, PipelineLayoutDescriptor, ShaderModule,
+    ShaderModuleDescriptor, ShaderStages,
+};
+
+use crate::{Binder, GpuCtx, ToBinder};
+
+///Used to tell the GPU how to run a compute shader. Works the same as [`crate::Brush`] but all
+///bindings are visible from the compute stage.
+pub struct ComputeBrush {
+    entry_point: String,
+    compiled_shader: ShaderModule,
+    cached_pipeline: Option<ComputePipeline>,
+    cached_bindings: Vec<(u32, BindGroup)>,
+    /// Amount of dynamic offsets required by each cached binding.
+    cached_dynamic_counts: Vec<usize>,
+    assets_to_bind: HashMap<u32, Binder>,
+    needs_update: bool,
+}
+
+impl ComputeBrush {
+    /// Create compute brush from shader path. `entry_point` is the name of the `@compute` function.
+    pub fn from_path(
+        wnd: &GpuCtx, shader_path: &Path, entry_point: &str,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut source = String::new();
+        File::open(shader_path)?.read_to_string(&mut source)?;
+        Self::from_source(wnd, source, entry_point)
+    }
+
+    /// Create compute brush directly providing the shader source.
+    pub fn from_source(
+        wnd: &GpuCtx, shader_source: String, entry_point: &str,
+    ) -> Result<Self, Box<dyn Error>> {
+        let shader = wnd.device.create_shader_module(ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(shader_source.as_str())),
+        });
+        Ok(Self {
+            entry_point: entry_point.to_string(),
+            compiled_shader: shader,
+            cached_pipeline: None,
+            cached_bindings: Vec::new(),
+            cached_dynamic_counts: Vec::new(),
+            assets_to_bind: HashMap::new(),
+            needs_update: true,
+        })
+    }
+
+    /// Bind asset given a group and location index. Both indices need to match with shader's or it
+    /// will panic when baking and linking with the compute pipeline.
+    pub fn bind(&mut self, group_index: u32, loc_index: u32, asset: impl ToBinder + 'static) {
+        self.assets_to_bind
+            .entry(group_index)
+            .or_default()
+            .bind(loc_index, Box::new(asset));
+        self.needs_update = true;
+    }
+
+    /// Returns if brush has been modified and needs to update the GPU with new data.
+    pub fn needs_update(&self) -> bool { self.needs_update }
+
+    /// Update GPU bindings and pipeline with current brush data.
+    pub fn update(&mut self, gpu: &GpuCtx) {
+        self.cached_bindings.clear();
+        self.cached_dynamic_counts.clear();
+        let mut bgls = Vec::new();
+        let mut groups = self.assets_to_bind.iter().collect::<Vec<_>>();
+        groups.sort_by_key(|x| *x.0);
+        for (i, binder) in groups {
+            let (bgl, bg) = binder.bake_with_visibility(gpu, Some(ShaderStages::COMPUTE));
+            bgls.push(bgl);
+            self.cached_bindings.push((*i, bg));
+            self.cached_dynamic_counts
+                .push(binder.dynamic_offset_count());
+        }
+        let pipeline_layout = gpu
+            .device
+            .create_pipeline_layout(&PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &bgls.iter().collect::<Vec<_>>(),
+                push_constant_ranges: &[],
+            });
+        let pipeline = gpu
+            .device
+            .create_compute_pipeline(&ComputePipelineDescriptor {
+                label: None,
+                layout: Some(&pipeline_layout),
+                module: &self.compiled_shader,
+                entry_point: &self.entry_point,
+            });
+        self.cached_pipeline = Some(pipeline);
+        self.needs_update = false;
+    }
+
+    pub fn get_pipeline(&self) -> &ComputePipeline { self.cached_pipeline.as_ref().unwrap() }
+
+    pub fn get_bind_groups(&self) -> &Vec<(u32, BindGroup)> { &self.cached_bindings }
+
+    /// Set all bind groups in the compute pass. Dynamic offsets are consumed in group order and,
+    /// inside each group, in binding order.
+    pub(crate) fn set_bind_groups<'a>(
+        &'a self, pass: &mut wgpu::ComputePass<'a>, dynamic_offsets: &[u32],
+    ) {
+        let mut offsets = dynamic_offsets;
+        for ((id, bg), count) in self.cached_bindings.iter().zip(&self.cached_dynamic_counts) {
+            assert!(
+                offsets.len() >= *count,
+                "Missing dynamic offsets for bind group {}.",
+                id
+            );
+            let (group_offsets, rest) = offsets.split_at(*count);
+            pass.set_bind_group(*id, bg, group_offsets);
+            offsets = rest;
+        }
+    }
+}
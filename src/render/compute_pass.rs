@@ -0,0 +1,66 @@
+use glam::UVec3;
+use wgpu::{CommandEncoder, CommandEncoderDescriptor, ComputePassDescriptor};
+
+use crate::{ComputeBrush, GpuCtx};
+
+/// Records compute passes outside of a frame render. Useful to run compute shaders without
+/// presenting anything to the window.
+pub struct ComputePassBuilder {
+    draw_cmds: CommandEncoder,
+}
+impl ComputePassBuilder {
+    pub fn new(gpu: &GpuCtx) -> Self {
+        Self {
+            draw_cmds: gpu
+                .device
+                .create_command_encoder(&CommandEncoderDescriptor { label: None }),
+        }
+    }
+
+    pub fn build_compute_pass(&mut self) -> ComputePass<'_> {
+        ComputePass::new(&mut self.draw_cmds)
+    }
+
+    /// Submit all compute passes to the GPU.
+    pub fn finish(self, gpu: &GpuCtx) { gpu.queue.submit(Some(self.draw_cmds.finish())); }
+}
+
+/// Manages the compute dispatches of a pass.
+pub struct ComputePass<'a> {
+    pass: wgpu::ComputePass<'a>,
+}
+
+impl<'a> ComputePass<'a> {
+    pub(crate) fn new(encoder: &'a mut CommandEncoder) -> Self {
+        Self {
+            pass: encoder.begin_compute_pass(&ComputePassDescriptor { label: None }),
+        }
+    }
+
+    /// Run compute shader with the given amount of workgroups on each dimension.
+    pub fn dispatch(&mut self, gpu: &GpuCtx, brush: &'a mut ComputeBrush, workgroups: UVec3) {
+        if brush.needs_update() {
+            brush.update(gpu);
+        }
+        self.dispatch_cached(brush, workgroups);
+    }
+
+    /// Run compute shader. Does not check if brush requires any changes.
+    pub fn dispatch_cached(&mut self, brush: &'a ComputeBrush, workgroups: UVec3) {
+        self.dispatch_cached_with_offsets(brush, workgroups, &[]);
+    }
+
+    /// Run compute shader using dynamic offsets for the brush bindings that require them. Does not
+    /// check if brush requires any changes.
+    pub fn dispatch_cached_with_offsets(
+        &mut self, brush: &'a ComputeBrush, workgroups: UVec3, offsets: &[u32],
+    ) {
+        self.pass.set_pipeline(brush.get_pipeline());
+        brush.set_bind_groups(&mut self.pass, offsets);
+        self.pass
+            .dispatch_workgroups(workgroups.x, workgroups.y, workgroups.z);
+    }
+
+    pub fn finish(self) {
+    }
+}
@@ -0,0 +1,74 @@
+use glam::Mat4;
+
+use crate::{Aabb, Camera, Frustum};
+
+/// Amount of draws tested by a [`FrustumCuller`] since its last reset.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CullStats {
+    pub visible: u32,
+    pub culled: u32,
+}
+
+/// Skips draws whose bounds are outside the camera frustum, counting visible and culled draws for
+/// profiling. Used by [`crate::RenderPass::render_shapes_culled`] and
+/// [`crate::RenderPass::render_scene_culled`].
+pub struct FrustumCuller {
+    frustum: Frustum,
+    stats: CullStats,
+}
+
+impl FrustumCuller {
+    pub fn new(camera: &Camera) -> Self {
+        Self {
+            frustum: camera.frustum(),
+            stats: CullStats::default(),
+        }
+    }
+
+    /// Start culling a new frame seen from the camera, resetting the counts.
+    pub fn begin(&mut self, camera: &Camera) {
+        self.frustum = camera.frustum();
+        self.stats = CullStats::default();
+    }
+
+    /// Whether local bounds placed with the model matrix are visible, counting the result. Draws
+    /// without bounds are always visible.
+    ///
+    /// ```
+    /// use glam::{Mat4, Vec3};
+    /// use tridify_rs::{Aabb, Camera, CullStats, FrustumCuller, Projection, Transform};
+    ///
+    /// let view = Transform::from_look_at(Vec3::new(0.0, 0.0, -10.0), Vec3::ZERO, Vec3::Y);
+    /// let mut culler = FrustumCuller::new(&Camera::new(view, Projection::default()));
+    /// let cube = Aabb::new(Vec3::splat(-1.0), Vec3::ONE);
+    /// let at = |x, y, z| Mat4::from_translation(Vec3::new(x, y, z));
+    /// assert!(culler.is_visible(Some(&cube), &Mat4::IDENTITY));
+    /// assert!(!culler.is_visible(Some(&cube), &at(0.0, 0.0, -20.0)));
+    /// assert!(!culler.is_visible(Some(&cube), &at(100.0, 0.0, 0.0)));
+    /// //Past the far plane, 100 units away.
+    /// assert!(!culler.is_visible(Some(&cube), &at(0.0, 0.0, 95.0)));
+    /// assert!(culler.is_visible(None, &at(0.0, 0.0, -20.0)));
+    /// assert_eq!(culler.stats(), CullStats { visible: 2, culled: 3 });
+    ///
+    /// //Reverse-Z with an infinite far plane culls by the near plane and the sides only.
+    /// culler.begin(&Camera::new(view, Projection::infinite_reverse_z(16.0 / 9.0, 65.0, 0.1)));
+    /// assert_eq!(culler.stats(), CullStats::default());
+    /// assert!(culler.is_visible(Some(&cube), &at(0.0, 0.0, 1e6)));
+    /// assert!(!culler.is_visible(Some(&cube), &at(0.0, 0.0, -20.0)));
+    /// let before_near = Aabb::new(Vec3::new(-0.01, -0.01, -9.98), Vec3::new(0.01, 0.01, -9.95));
+    /// assert!(!culler.is_visible(Some(&before_near), &Mat4::IDENTITY));
+    /// assert!(!culler.is_visible(Some(&cube), &at(100.0, 0.0, 0.0)));
+    /// assert_eq!(culler.stats(), CullStats { visible: 1, culled: 3 });
+    /// ```
+    pub fn is_visible(&mut self, aabb: Option<&Aabb>, model: &Mat4) -> bool {
+        let visible = aabb.is_none_or(|x| self.frustum.intersects_aabb(&x.transformed(model)));
+        match visible {
+            true => self.stats.visible += 1,
+            false => self.stats.culled += 1,
+        }
+        visible
+    }
+
+    pub fn frustum(&self) -> &Frustum { &self.frustum }
+    pub fn stats(&self) -> CullStats { self.stats }
+}
@@ -0,0 +1,177 @@
+use std::f32::consts::TAU;
+
+use glam::{Mat4, Vec3, Vec4Swizzles};
+use wgpu::{BlendState, PrimitiveTopology, TextureFormat};
+
+use crate::{
+    Aabb, AlphaBlend, Brush, BrushDesc, Camera, Color, DepthDesc, DynamicShapeBuffer, GpuBuffer,
+    GpuCtx, ShapeBatch, ShapeBuffer, Transform, Vertex,
+};
+
+/// Segments used to draw circles and spheres.
+const CIRCLE_SEGMENTS: u32 = 32;
+
+/// Accumulates lines to visualize bounds, cameras and other gizmos. Lines are drawn with
+/// [`crate::RenderPass::render_debug`], which clears them for the next frame.
+pub struct DebugDraw {
+    batch: ShapeBatch,
+    buffer: DynamicShapeBuffer,
+    view_proj: GpuBuffer<[f32; 16]>,
+    brush: Brush,
+}
+
+impl DebugDraw {
+    /// Create a debug drawer for the window surface, drawn over everything else.
+    pub fn new(gpu: &GpuCtx) -> Self { Self::with_targets(gpu, None, None) }
+
+    /// Create a debug drawer for textures with the given formats, see [`BrushDesc::targets`].
+    /// Lines are hidden behind the geometry in the depth texture when depth testing is used,
+    /// usually without depth writes.
+    pub fn with_targets(
+        gpu: &GpuCtx, targets: Option<Vec<TextureFormat>>, depth: Option<DepthDesc>,
+    ) -> Self {
+        let view_proj = GpuBuffer::init(gpu, &[Mat4::IDENTITY.to_cols_array()]);
+        let desc = BrushDesc {
+            blend: BlendState {
+                color: AlphaBlend::Default.into(),
+                alpha: AlphaBlend::Default.into(),
+            },
+            targets,
+            depth,
+            topology: PrimitiveTopology::LineList,
+        };
+        let mut brush = Brush::from_source(desc, gpu, include_str!("shader.wgsl").to_string())
+            .expect("Error creating debug draw brush.");
+        brush.bind(0, 0, view_proj.clone());
+        brush.update(gpu);
+        Self {
+            batch: ShapeBatch::new(),
+            buffer: DynamicShapeBuffer::new(gpu, 1024, 1024),
+            view_proj,
+            brush,
+        }
+    }
+
+    pub fn line(&mut self, from: Vec3, to: Vec3, color: Color) {
+        let index = self.batch.vertices.len() as u32;
+        self.batch
+            .vertices
+            .extend([from, to].map(|x| Vertex::from_vec(x, Some(color), None)));
+        self.batch.indices.extend([index, index + 1]);
+    }
+
+    /// Draw the edges of a box.
+    pub fn aabb(&mut self, aabb: &Aabb, color: Color) {
+        let corner = |i: usize| {
+            Vec3::new(
+                if i & 1 == 0 { aabb.min.x } else { aabb.max.x },
+                if i & 2 == 0 { aabb.min.y } else { aabb.max.y },
+                if i & 4 == 0 { aabb.min.z } else { aabb.max.z },
+            )
+        };
+        self.box_edges(corner, color);
+    }
+
+    /// Draw a circle facing the normal.
+    pub fn circle(&mut self, center: Vec3, normal: Vec3, radius: f32, color: Color) {
+        let normal = normal.normalize_or_zero();
+        let tangent = normal.any_orthonormal_vector() * radius;
+        let bitangent = normal.cross(tangent);
+        let point = |i: u32| {
+            let angle = TAU * i as f32 / CIRCLE_SEGMENTS as f32;
+            center + tangent * angle.cos() + bitangent * angle.sin()
+        };
+        for i in 0..CIRCLE_SEGMENTS {
+            self.line(point(i), point(i + 1), color);
+        }
+    }
+
+    /// Draw a sphere as a circle around each axis.
+    pub fn sphere(&mut self, center: Vec3, radius: f32, color: Color) {
+        for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+            self.circle(center, axis, radius, color);
+        }
+    }
+
+    /// Draw the volume visible by the camera. Infinite projections are drawn up to a thousand
+    /// times the near plane distance.
+    pub fn frustum(&mut self, camera: &Camera, color: Color) {
+        let inverse = camera.build_camera_matrix().inverse();
+        let (near, far) = match camera.proj.is_reverse_z() {
+            true => (1.0, 0.001),
+            false => (0.0, 1.0),
+        };
+        let corner = |i: usize| {
+            let ndc = Vec3::new(
+                if i & 1 == 0 { -1.0 } else { 1.0 },
+                if i & 2 == 0 { -1.0 } else { 1.0 },
+                if i & 4 == 0 { near } else { far },
+            );
+            let point = inverse * ndc.extend(1.0);
+            point.xyz() / point.w
+        };
+        self.box_edges(corner, color);
+    }
+
+    /// Draw the local axes of the transform with the given length: X in red, Y in green and Z in
+    /// blue.
+    pub fn axes(&mut self, transform: &Transform, size: f32) {
+        let matrix = transform.build_matrix();
+        let origin = matrix.transform_point3(Vec3::ZERO);
+        for (axis, color) in [
+            (Vec3::X, Color::RED),
+            (Vec3::Y, Color::LIME),
+            (Vec3::Z, Color::BLUE),
+        ] {
+            self.line(origin, matrix.transform_point3(axis * size), color);
+        }
+    }
+
+    /// Draw a grid on the XZ plane centered at `center`, with `cells` cells of `cell_size` on
+    /// each side.
+    pub fn grid(&mut self, center: Vec3, cell_size: f32, cells: u32, color: Color) {
+        let half = cell_size * cells as f32 / 2.0;
+        for i in 0..=cells {
+            let offset = i as f32 * cell_size - half;
+            self.line(
+                center + Vec3::new(offset, 0.0, -half),
+                center + Vec3::new(offset, 0.0, half),
+                color,
+            );
+            self.line(
+                center + Vec3::new(-half, 0.0, offset),
+                center + Vec3::new(half, 0.0, offset),
+                color,
+            );
+        }
+    }
+
+    /// Remove all lines added since the last draw.
+    pub fn clear(&mut self) {
+        self.batch.vertices.clear();
+        self.batch.indices.clear();
+    }
+
+    /// Upload the lines seen from the camera and clear them for the next frame.
+    pub(crate) fn flush(&mut self, gpu: &GpuCtx, camera: &Camera) {
+        self.view_proj
+            .write(gpu, &camera.build_camera_matrix().to_cols_array());
+        self.buffer.upload(gpu, &self.batch);
+        self.clear();
+    }
+
+    pub(crate) fn brush(&self) -> &Brush { &self.brush }
+    pub(crate) fn shape_buffer(&self) -> &ShapeBuffer { self.buffer.shape_buffer() }
+
+    /// Draw the 12 edges of a box given a function returning its corners, where bits 0, 1 and 2
+    /// of the corner index select the max X, Y and Z side.
+    fn box_edges(&mut self, corner: impl Fn(usize) -> Vec3, color: Color) {
+        for i in 0..8 {
+            for axis in [1, 2, 4] {
+                if i & axis == 0 {
+                    self.line(corner(i), corner(i | axis), color);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,58 @@
+struct Scene {
+    inv_view_proj: mat4x4<f32>,
+    camera_position: vec3<f32>,
+    light_count: u32,
+    ambient: vec4<f32>,
+    znear: f32,
+    zfar: f32,
+};
+
+struct DebugView {
+    channel: u32,
+};
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) uv: vec2<f32>,
+};
+
+@group(0) @binding(0) var<uniform> u_scene: Scene;
+@group(0) @binding(1) var<uniform> u_view: DebugView;
+
+@group(1) @binding(0) var t_albedo: texture_2d<f32>;
+@group(1) @binding(1) var t_normal: texture_2d<f32>;
+@group(1) @binding(2) var t_material: texture_2d<f32>;
+@group(1) @binding(3) var t_depth: texture_depth_2d;
+
+@vertex
+fn vs_main(model: VertexInput) -> @builtin(position) vec4<f32> {
+    return vec4<f32>(model.position.xy, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) frag_position: vec4<f32>) -> @location(0) vec4<f32> {
+    let coord = vec2<i32>(frag_position.xy);
+    switch u_view.channel {
+        // Albedo
+        case 0u: {
+            return vec4<f32>(textureLoad(t_albedo, coord, 0).rgb, 1.0);
+        }
+        // Normal
+        case 1u: {
+            return vec4<f32>(textureLoad(t_normal, coord, 0).xyz * 0.5 + 0.5, 1.0);
+        }
+        // Material
+        case 2u: {
+            return vec4<f32>(textureLoad(t_material, coord, 0).rgb, 1.0);
+        }
+        // Linear depth, white at the far plane.
+        default: {
+            let depth = textureLoad(t_depth, coord, 0);
+            let near = u_scene.znear;
+            let far = u_scene.zfar;
+            let linear = near * far / (far - depth * (far - near));
+            return vec4<f32>(vec3<f32>(linear / far), 1.0);
+        }
+    }
+}
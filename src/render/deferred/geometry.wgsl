@@ -0,0 +1,54 @@
+// Geometry pass template. Writes surface data into the G-buffer instead of lit colors. Copy it and
+// add textures or extra vertex data as needed, keeping the fragment output locations.
+//
+// G-buffer contents:
+// - Albedo: base color.
+// - Normal: world space normal in xyz.
+// - Material: specular intensity in r, roughness in g and emission in b.
+
+struct Camera {
+    view_proj: mat4x4<f32>,
+};
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) uv: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) uv: vec2<f32>,
+    @location(2) world_position: vec3<f32>,
+};
+
+struct GBufferOutput {
+    @location(0) albedo: vec4<f32>,
+    @location(1) normal: vec4<f32>,
+    @location(2) material: vec4<f32>,
+};
+
+@group(0) @binding(0) var<uniform> u_camera: Camera;
+
+@vertex
+fn vs_main(model: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = u_camera.view_proj * vec4<f32>(model.position, 1.0);
+    out.color = model.color;
+    out.uv = model.uv;
+    out.world_position = model.position;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> GBufferOutput {
+    // Flat normal from the screen derivatives of the surface position.
+    let normal = normalize(cross(dpdx(in.world_position), dpdy(in.world_position)));
+
+    var out: GBufferOutput;
+    out.albedo = in.color;
+    out.normal = vec4<f32>(normal, 0.0);
+    out.material = vec4<f32>(0.5, 0.5, 0.0, 1.0);
+    return out;
+}
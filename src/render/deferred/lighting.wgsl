@@ -0,0 +1,77 @@
+struct Scene {
+    inv_view_proj: mat4x4<f32>,
+    camera_position: vec3<f32>,
+    light_count: u32,
+    ambient: vec4<f32>,
+    znear: f32,
+    zfar: f32,
+};
+
+struct PointLight {
+    position: vec3<f32>,
+    radius: f32,
+    // Color multiplied by intensity.
+    color: vec4<f32>,
+};
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) uv: vec2<f32>,
+};
+
+@group(0) @binding(0) var<uniform> u_scene: Scene;
+@group(0) @binding(1) var<storage, read> lights: array<PointLight>;
+
+@group(1) @binding(0) var t_albedo: texture_2d<f32>;
+@group(1) @binding(1) var t_normal: texture_2d<f32>;
+@group(1) @binding(2) var t_material: texture_2d<f32>;
+@group(1) @binding(3) var t_depth: texture_depth_2d;
+
+@vertex
+fn vs_main(model: VertexInput) -> @builtin(position) vec4<f32> {
+    return vec4<f32>(model.position.xy, 0.0, 1.0);
+}
+
+// World position of the pixel reconstructed from its depth.
+fn world_position(coord: vec2<i32>, depth: f32) -> vec3<f32> {
+    let uv = (vec2<f32>(coord) + 0.5) / vec2<f32>(textureDimensions(t_depth));
+    let ndc = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, depth, 1.0);
+    let world = u_scene.inv_view_proj * ndc;
+    return world.xyz / world.w;
+}
+
+@fragment
+fn fs_main(@builtin(position) frag_position: vec4<f32>) -> @location(0) vec4<f32> {
+    let coord = vec2<i32>(frag_position.xy);
+    let depth = textureLoad(t_depth, coord, 0);
+    // Nothing was drawn in this pixel.
+    if depth >= 1.0 {
+        discard;
+    }
+    let albedo = textureLoad(t_albedo, coord, 0).rgb;
+    let normal = normalize(textureLoad(t_normal, coord, 0).xyz);
+    let material = textureLoad(t_material, coord, 0);
+    let position = world_position(coord, depth);
+    let view_dir = normalize(u_scene.camera_position - position);
+    let shininess = mix(256.0, 2.0, material.g);
+
+    var color = albedo * u_scene.ambient.rgb + albedo * material.b;
+    for (var i = 0u; i < u_scene.light_count; i += 1u) {
+        let light = lights[i];
+        let to_light = light.position - position;
+        let dist = length(to_light);
+        if dist >= light.radius {
+            continue;
+        }
+        let light_dir = to_light / dist;
+        let n_dot_l = max(dot(normal, light_dir), 0.0);
+        let half_dir = normalize(light_dir + view_dir);
+        let specular = material.r * pow(max(dot(normal, half_dir), 0.0), shininess) * step(0.0001, n_dot_l);
+        // Smooth falloff reaching zero at the light radius.
+        let window = clamp(1.0 - pow(dist / light.radius, 4.0), 0.0, 1.0);
+        let attenuation = window * window / (dist * dist + 1.0);
+        color += (albedo * n_dot_l + specular) * light.color.rgb * attenuation;
+    }
+    return vec4<f32>(color, 1.0);
+}
@@ -0,0 +1,296 @@
+use std::error::Error;
+
+use bytemuck::{Pod, Zeroable};
+use glam::{Mat4, UVec2, Vec3};
+use wgpu::{BlendState, TextureFormat};
+
+use crate::{
+    Brush, BrushDesc, Camera, Color, DepthDesc, GpuBuffer, GpuBufferDesc, GpuCtx, GpuLayout,
+    GpuStructWriter, RenderOptions, RenderPass, RenderPassBuilder, ShapeBatch, ShapeBuffer,
+    Texture, TextureDesc, TextureSize, TextureUsage,
+};
+
+/// WGSL source of a geometry pass brush writing into a [`GBuffer`]. Use it as a starting point
+/// for custom geometry shaders.
+pub const GEOMETRY_SHADER_TEMPLATE: &str = include_str!("geometry.wgsl");
+
+/// Textures storing the surface data of the scene, filled by the geometry pass and read by the
+/// lighting pass.
+pub struct GBuffer {
+    /// Base color.
+    pub albedo: Texture,
+    /// World space normal.
+    pub normal: Texture,
+    /// Specular intensity in red, roughness in green and emission in blue.
+    pub material: Texture,
+    pub depth: Texture,
+}
+
+impl GBuffer {
+    pub const ALBEDO_FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+    pub const NORMAL_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+    pub const MATERIAL_FORMAT: TextureFormat = TextureFormat::Rgba8Unorm;
+    pub const DEPTH_FORMAT: TextureFormat = TextureFormat::Depth32Float;
+
+    pub fn new(gpu: &GpuCtx, size: UVec2) -> Self {
+        let size = size.max(UVec2::ONE);
+        let target = |format: TextureFormat, label: &str| {
+            Texture::new(
+                gpu,
+                TextureDesc {
+                    size: TextureSize::D2(size),
+                    usage: TextureUsage::RENDER | TextureUsage::TEXTURE_BIND,
+                    format,
+                    mip_level_count: 1,
+                },
+                Some(label),
+            )
+        };
+        Self {
+            albedo: target(Self::ALBEDO_FORMAT, "G-buffer albedo"),
+            normal: target(Self::NORMAL_FORMAT, "G-buffer normal"),
+            material: target(Self::MATERIAL_FORMAT, "G-buffer material"),
+            depth: target(Self::DEPTH_FORMAT, "G-buffer depth"),
+        }
+    }
+
+    pub fn size(&self) -> UVec2 { self.albedo.desc.size.get_size().truncate() }
+
+    /// Brush configuration for geometry pass brushes: one target per color texture in shader
+    /// location order and depth testing.
+    pub fn brush_desc() -> BrushDesc {
+        BrushDesc {
+            blend: BlendState::REPLACE,
+            targets: Some(vec![
+                Self::ALBEDO_FORMAT,
+                Self::NORMAL_FORMAT,
+                Self::MATERIAL_FORMAT,
+            ]),
+            depth: Some(DepthDesc {
+                format: Self::DEPTH_FORMAT,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Start a geometry pass clearing all G-buffer textures.
+    pub fn build_pass<'a>(&'a self, builder: &'a mut RenderPassBuilder) -> RenderPass<'a> {
+        builder.build_texture_pass(
+            &[&self.albedo, &self.normal, &self.material],
+            Some(&self.depth),
+            RenderOptions {
+                clear_color: Color::CLEAR,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Bind all textures to a brush group in albedo, normal, material and depth order.
+    fn bind(&self, brush: &mut Brush, group: u32) {
+        brush.bind(group, 0, self.albedo.clone());
+        brush.bind(group, 1, self.normal.clone());
+        brush.bind(group, 2, self.material.clone());
+        brush.bind(group, 3, self.depth.clone());
+    }
+}
+
+/// Light emitting in all directions from a point, reaching up to `radius`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointLight {
+    pub position: Vec3,
+    pub radius: f32,
+    pub color: Color,
+    pub intensity: f32,
+}
+impl Default for PointLight {
+    fn default() -> Self {
+        Self {
+            position: Vec3::ZERO,
+            radius: 10.0,
+            color: Color::WHITE,
+            intensity: 1.0,
+        }
+    }
+}
+
+/// Point light as stored in the GPU.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+struct GpuPointLight {
+    position: [f32; 3],
+    radius: f32,
+    color: [f32; 4],
+}
+impl From<&PointLight> for GpuPointLight {
+    fn from(light: &PointLight) -> Self {
+        let c = light.color;
+        Self {
+            position: light.position.to_array(),
+            radius: light.radius,
+            color: [
+                c.r * light.intensity,
+                c.g * light.intensity,
+                c.b * light.intensity,
+                c.a,
+            ],
+        }
+    }
+}
+
+/// G-buffer channel displayed by [`DeferredRenderer::render_debug`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GBufferView {
+    Albedo,
+    Normal,
+    Material,
+    /// Linear depth between the camera near and far planes.
+    Depth,
+}
+
+/// Opt-in deferred rendering path. Shapes are drawn once into a [`GBuffer`] and lit afterwards
+/// by a fullscreen pass, so the lighting cost depends on the pixels on screen instead of the
+/// amount of shapes.
+///
+/// Each frame: draw shapes with geometry brushes into [`DeferredRenderer::build_geometry_pass`],
+/// then call [`DeferredRenderer::render_lighting`] in a window render pass. The G-buffer must
+/// have the same size as the window, see [`DeferredRenderer::resize`].
+pub struct DeferredRenderer {
+    gbuffer: GBuffer,
+    scene_buf: GpuBuffer,
+    lights_buf: GpuBuffer<GpuPointLight>,
+    debug_buf: GpuBuffer<u32>,
+    lighting_brush: Brush,
+    debug_brush: Brush,
+    quad: ShapeBuffer,
+    inv_view_proj: Mat4,
+    camera_position: Vec3,
+    znear: f32,
+    zfar: f32,
+    ambient: Color,
+    light_count: u32,
+}
+
+impl DeferredRenderer {
+    /// Create renderer with a G-buffer matching the window size.
+    pub fn new(gpu: &GpuCtx) -> Result<Self, Box<dyn Error>> {
+        let gbuffer = GBuffer::new(gpu, gpu.get_wnd_size());
+        let scene_buf = GpuBuffer::init(gpu, &[0u8; 112]);
+        let lights_buf = GpuBuffer::new(gpu, GpuBufferDesc::storage(true), 16);
+        let debug_buf = GpuBuffer::init(gpu, &[0u32; 4]);
+
+        let desc = || BrushDesc {
+            blend: BlendState::REPLACE,
+            ..Default::default()
+        };
+        let mut lighting_brush =
+            Brush::from_source(desc(), gpu, include_str!("lighting.wgsl").to_string())?;
+        lighting_brush.bind(0, 0, scene_buf.clone());
+        lighting_brush.bind(0, 1, lights_buf.clone());
+        gbuffer.bind(&mut lighting_brush, 1);
+        let mut debug_brush =
+            Brush::from_source(desc(), gpu, include_str!("debug.wgsl").to_string())?;
+        debug_brush.bind(0, 0, scene_buf.clone());
+        debug_brush.bind(0, 1, debug_buf.clone());
+        gbuffer.bind(&mut debug_brush, 1);
+
+        let mut renderer = Self {
+            gbuffer,
+            scene_buf,
+            lights_buf,
+            debug_buf,
+            lighting_brush,
+            debug_brush,
+            quad: ShapeBatch::new()
+                .add_2d_square(Vec3::ZERO, 2.0, 2.0, Color::WHITE)
+                .bake_buffers(gpu),
+            inv_view_proj: Mat4::IDENTITY,
+            camera_position: Vec3::ZERO,
+            znear: 0.1,
+            zfar: 100.0,
+            ambient: Color::new(0.05, 0.05, 0.05, 1.0),
+            light_count: 0,
+        };
+        renderer.write_scene(gpu);
+        renderer.lighting_brush.update(gpu);
+        renderer.debug_brush.update(gpu);
+        Ok(renderer)
+    }
+
+    pub fn gbuffer(&self) -> &GBuffer { &self.gbuffer }
+
+    /// Create a geometry pass brush. Shader must write the G-buffer outputs, see
+    /// [`GEOMETRY_SHADER_TEMPLATE`].
+    pub fn geometry_brush(
+        &self, gpu: &GpuCtx, shader_source: String,
+    ) -> Result<Brush, Box<dyn Error>> {
+        Brush::from_source(GBuffer::brush_desc(), gpu, shader_source)
+    }
+
+    /// Recreate the G-buffer with a new size. Should be called when the window is resized.
+    pub fn resize(&mut self, gpu: &GpuCtx, size: UVec2) {
+        self.gbuffer = GBuffer::new(gpu, size);
+        self.gbuffer.bind(&mut self.lighting_brush, 1);
+        self.gbuffer.bind(&mut self.debug_brush, 1);
+        self.lighting_brush.update(gpu);
+        self.debug_brush.update(gpu);
+    }
+
+    /// Camera used to reconstruct pixel positions. Must be the same used in the geometry pass.
+    pub fn set_camera(&mut self, gpu: &GpuCtx, camera: &Camera) {
+        self.inv_view_proj = camera.build_camera_matrix().inverse();
+        self.camera_position = camera.view.build_matrix().inverse().w_axis.truncate();
+        self.znear = camera.proj.znear();
+        self.zfar = camera.proj.zfar();
+        self.write_scene(gpu);
+    }
+
+    /// Light added to all surfaces regardless of the point lights.
+    pub fn set_ambient(&mut self, gpu: &GpuCtx, ambient: Color) {
+        self.ambient = ambient;
+        self.write_scene(gpu);
+    }
+
+    /// Replace all point lights of the scene.
+    pub fn set_lights(&mut self, gpu: &GpuCtx, lights: &[PointLight]) {
+        if lights.len() as u64 > self.lights_buf.len() {
+            let len = (lights.len() as u64).next_power_of_two();
+            self.lights_buf = GpuBuffer::new(gpu, GpuBufferDesc::storage(true), len);
+            self.lighting_brush.bind(0, 1, self.lights_buf.clone());
+            self.lighting_brush.update(gpu);
+        }
+        let data = lights.iter().map(GpuPointLight::from).collect::<Vec<_>>();
+        self.lights_buf.write_slice(gpu, &data);
+        self.light_count = lights.len() as u32;
+        self.write_scene(gpu);
+    }
+
+    /// Start a geometry pass clearing the G-buffer.
+    pub fn build_geometry_pass<'a>(&'a self, builder: &'a mut RenderPassBuilder) -> RenderPass<'a> {
+        self.gbuffer.build_pass(builder)
+    }
+
+    /// Light the G-buffer contents into the given pass with a fullscreen quad.
+    pub fn render_lighting<'a>(&'a self, pass: &mut RenderPass<'a>) {
+        pass.render_shapes_cached(&self.lighting_brush, &self.quad);
+    }
+
+    /// Display a single G-buffer channel into the given pass with a fullscreen quad. The channel
+    /// is uploaded when called, so only one view can be displayed per frame.
+    pub fn render_debug<'a>(&'a self, gpu: &GpuCtx, pass: &mut RenderPass<'a>, view: GBufferView) {
+        self.debug_buf.write_slice(gpu, &[view as u32, 0, 0, 0]);
+        pass.render_shapes_cached(&self.debug_brush, &self.quad);
+    }
+
+    fn write_scene(&self, gpu: &GpuCtx) {
+        let bytes = GpuStructWriter::new(GpuLayout::Std140)
+            .field(&self.inv_view_proj)
+            .field(&self.camera_position)
+            .field(&self.light_count)
+            .field(&self.ambient)
+            .field(&self.znear)
+            .field(&self.zfar)
+            .finish();
+        self.scene_buf.write_slice(gpu, &bytes);
+    }
+}
@@ -0,0 +1,179 @@
+use std::mem::size_of;
+
+use wgpu::{Buffer, BufferUsages, IndexFormat};
+
+use crate::{Aabb, GpuCtx, ShapeBatch, ShapeBuffer, Vertex};
+use glam::Vec3;
+
+/// Shape buffers that can be updated in place, for geometry that changes every frame. Buffers
+/// only grow, to the next power of two, when uploaded data doesn't fit.
+///
+/// Indices are stored as `u16` while they fit, halving their size. Submesh indices are relative
+/// to their first vertex, so batches split in submeshes can use `u16` with more vertices.
+pub struct DynamicShapeBuffer {
+    buffer: ShapeBuffer,
+    vertex_len: u32,
+    vertex_capacity: u64,
+    /// Index buffer size in bytes.
+    index_capacity: u64,
+    /// Copy of the uploaded indices, used to keep `u16` writes aligned to 4 bytes.
+    indices: Vec<u32>,
+}
+
+impl DynamicShapeBuffer {
+    const VERTEX_SIZE: u64 = size_of::<Vertex>() as u64;
+
+    /// Create empty buffers with space for the given amount of vertices and indices.
+    pub fn new(gpu: &GpuCtx, vertex_capacity: u64, index_capacity: u64) -> Self {
+        let vertex_capacity = vertex_capacity.max(1);
+        let index_capacity = Self::index_bytes(index_capacity.max(2), IndexFormat::Uint32);
+        Self {
+            buffer: ShapeBuffer {
+                vertex_buffer: Self::create_buffer(gpu, vertex_capacity * Self::VERTEX_SIZE, true),
+                index_buffer: Self::create_buffer(gpu, index_capacity, false),
+                index_len: 0,
+                index_format: IndexFormat::Uint16,
+                submeshes: Vec::new(),
+                aabb: None,
+            },
+            vertex_len: 0,
+            vertex_capacity,
+            index_capacity,
+            indices: Vec::new(),
+        }
+    }
+
+    /// Create buffers with the batch contents.
+    pub fn from_batch(gpu: &GpuCtx, batch: &ShapeBatch) -> Self {
+        let mut buffer = Self::new(gpu, batch.vertices.len() as u64, batch.indices.len() as u64);
+        buffer.upload(gpu, batch);
+        buffer
+    }
+
+    /// Replace all contents with the batch, growing the buffers if needed.
+    pub fn upload(&mut self, gpu: &GpuCtx, batch: &ShapeBatch) {
+        let vertex_len = batch.vertices.len() as u64;
+        if vertex_len > self.vertex_capacity {
+            self.vertex_capacity = vertex_len.next_power_of_two();
+            self.buffer.vertex_buffer =
+                Self::create_buffer(gpu, self.vertex_capacity * Self::VERTEX_SIZE, true);
+        }
+        gpu.queue.write_buffer(
+            &self.buffer.vertex_buffer,
+            0,
+            bytemuck::cast_slice(&batch.vertices),
+        );
+        self.vertex_len = batch.vertices.len() as u32;
+        self.buffer.aabb = batch.aabb();
+
+        let max_index = batch.indices.iter().max().copied().unwrap_or(0);
+        self.buffer.index_format = match max_index <= u16::MAX as u32 {
+            true => IndexFormat::Uint16,
+            false => IndexFormat::Uint32,
+        };
+        let index_bytes = Self::index_bytes(batch.indices.len() as u64, self.buffer.index_format);
+        if index_bytes > self.index_capacity {
+            self.index_capacity = index_bytes.next_power_of_two();
+            self.buffer.index_buffer = Self::create_buffer(gpu, self.index_capacity, false);
+        }
+        self.indices.clear();
+        self.indices.extend(&batch.indices);
+        self.buffer.index_len = batch.indices.len() as u32;
+        self.buffer.submeshes = batch.get_submeshes();
+        self.upload_indices(gpu, 0, self.indices.len());
+    }
+
+    /// Update vertices starting at `first`. Written vertices must be within the uploaded ones.
+    pub fn write_vertices(&mut self, gpu: &GpuCtx, first: u32, vertices: &[Vertex]) {
+        assert!(
+            first as usize + vertices.len() <= self.vertex_len as usize,
+            "Writing vertices out of the uploaded range."
+        );
+        gpu.queue.write_buffer(
+            &self.buffer.vertex_buffer,
+            first as u64 * Self::VERTEX_SIZE,
+            bytemuck::cast_slice(vertices),
+        );
+        //Bounds only grow, as the replaced vertices are not known.
+        let written = Aabb::from_points(vertices.iter().map(|x| Vec3::from(x.pos)));
+        self.buffer.aabb = match (self.buffer.aabb, written) {
+            (Some(aabb), Some(written)) => Some(aabb.union(&written)),
+            (aabb, written) => aabb.or(written),
+        };
+    }
+
+    /// Update indices starting at `first`. Written indices must be within the uploaded ones and
+    /// fit in the current [`DynamicShapeBuffer::index_format`].
+    pub fn write_indices(&mut self, gpu: &GpuCtx, first: u32, indices: &[u32]) {
+        let first = first as usize;
+        assert!(
+            first + indices.len() <= self.indices.len(),
+            "Writing indices out of the uploaded range."
+        );
+        let max_index = match self.buffer.index_format {
+            IndexFormat::Uint16 => u16::MAX as u32,
+            IndexFormat::Uint32 => u32::MAX,
+        };
+        assert!(
+            indices.iter().all(|x| *x <= max_index),
+            "Index doesn't fit in the index format."
+        );
+        self.indices[first..first + indices.len()].copy_from_slice(indices);
+        self.upload_indices(gpu, first, first + indices.len());
+    }
+
+    /// Buffers to draw with [`crate::RenderPass::render_shapes`].
+    pub fn shape_buffer(&self) -> &ShapeBuffer { &self.buffer }
+    pub fn vertex_len(&self) -> u32 { self.vertex_len }
+    pub fn index_len(&self) -> u32 { self.buffer.index_len }
+    pub fn vertex_capacity(&self) -> u64 { self.vertex_capacity }
+    pub fn index_format(&self) -> IndexFormat { self.buffer.index_format }
+
+    /// Upload indices in `start..end` from the CPU copy. Buffer writes need 4 byte alignment, so
+    /// `u16` ranges are extended to even bounds.
+    fn upload_indices(&self, gpu: &GpuCtx, start: usize, end: usize) {
+        match self.buffer.index_format {
+            IndexFormat::Uint32 => gpu.queue.write_buffer(
+                &self.buffer.index_buffer,
+                start as u64 * 4,
+                bytemuck::cast_slice(&self.indices[start..end]),
+            ),
+            IndexFormat::Uint16 => {
+                let start = start & !1;
+                let mut data = self.indices[start..end]
+                    .iter()
+                    .map(|x| *x as u16)
+                    .collect::<Vec<_>>();
+                if data.len() % 2 == 1 {
+                    data.push(self.indices.get(end).map_or(0, |x| *x as u16));
+                }
+                gpu.queue.write_buffer(
+                    &self.buffer.index_buffer,
+                    start as u64 * 2,
+                    bytemuck::cast_slice(&data),
+                );
+            }
+        }
+    }
+
+    /// Size in bytes of `len` indices, padded to 4 bytes.
+    fn index_bytes(len: u64, format: IndexFormat) -> u64 {
+        match format {
+            IndexFormat::Uint16 => len.div_ceil(2) * 4,
+            IndexFormat::Uint32 => len * 4,
+        }
+    }
+
+    fn create_buffer(gpu: &GpuCtx, size: u64, vertex: bool) -> Buffer {
+        let usage = match vertex {
+            true => BufferUsages::VERTEX,
+            false => BufferUsages::INDEX,
+        };
+        gpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size,
+            usage: usage | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+}
@@ -0,0 +1,683 @@
+use std::{
+    collections::HashSet,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use base64::Engine;
+use glam::{Mat4, Quat, Vec3};
+use gltf::{
+    mesh::Mode,
+    texture::{MagFilter, MinFilter, WrappingMode},
+};
+use wgpu::{AddressMode, FilterMode, TextureFormat};
+
+use crate::{
+    BrushId, Camera, Color, GpuCtx, Mesh, Node, NodeId, NodeTree, Projection, Sampler, Scene,
+    ShapeBatch, Texture, TextureDesc, TextureSize, TextureUsage, Transform, Vertex,
+};
+
+/// glTF texture image with the sampler it should be read with, see [`GltfImage::upload`].
+#[derive(Debug, Clone)]
+pub struct GltfImage {
+    pub name: Option<String>,
+    pub image: image::RgbaImage,
+    /// Whether the image stores data, like normal maps, instead of sRGB colors.
+    pub linear: bool,
+    pub sampler: wgpu::SamplerDescriptor<'static>,
+}
+
+impl GltfImage {
+    /// Upload the image to the GPU.
+    pub fn upload(&self, gpu: &GpuCtx) -> GltfTexture {
+        let desc = TextureDesc {
+            size: TextureSize::D2(self.image.dimensions().into()),
+            usage: TextureUsage::TEXTURE_BIND | TextureUsage::DESTINATION,
+            format: if self.linear {
+                TextureFormat::Rgba8Unorm
+            } else {
+                TextureFormat::Rgba8UnormSrgb
+            },
+            mip_level_count: 1,
+        };
+        GltfTexture {
+            texture: Texture::init(gpu, desc, self.image.as_raw(), self.name.as_deref()),
+            sampler: Sampler::new(gpu, &self.sampler),
+        }
+    }
+}
+
+/// glTF texture: image uploaded to the GPU with the sampler it should be read with.
+pub struct GltfTexture {
+    pub texture: Texture,
+    pub sampler: Sampler,
+}
+
+/// glTF PBR metallic-roughness material. Texture fields index [`GltfAsset::images`].
+#[derive(Debug, Clone)]
+pub struct GltfMaterial {
+    pub name: Option<String>,
+    pub base_color: Color,
+    pub base_color_texture: Option<usize>,
+    pub metallic: f32,
+    pub roughness: f32,
+    /// Roughness in the green channel and metalness in the blue channel.
+    pub metallic_roughness_texture: Option<usize>,
+    pub normal_texture: Option<usize>,
+    pub occlusion_texture: Option<usize>,
+    pub emissive: Vec3,
+    pub emissive_texture: Option<usize>,
+    pub double_sided: bool,
+    /// Whether the material needs alpha blending.
+    pub transparent: bool,
+    /// Fragments with lower alpha should be discarded.
+    pub alpha_cutoff: Option<f32>,
+}
+
+/// Part of a glTF mesh drawn with a single material.
+#[derive(Debug, Clone)]
+pub struct GltfPrimitive {
+    pub mesh: Mesh,
+    /// Index of [`GltfAsset::materials`].
+    pub material: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GltfMesh {
+    pub name: Option<String>,
+    pub primitives: Vec<GltfPrimitive>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GltfNode {
+    pub name: Option<String>,
+    /// Transform relative to the parent node.
+    pub transform: Transform,
+    /// Index of [`GltfAsset::meshes`].
+    pub mesh: Option<usize>,
+    pub children: Vec<usize>,
+}
+
+/// Meshes, materials, textures, cameras and node hierarchy loaded from a glTF 2.0 file. Loading
+/// doesn't need the GPU, textures are uploaded with [`GltfAsset::upload_textures`].
+///
+/// glTF uses right handed coordinates, so positions, normals and transforms are mirrored in the Z
+/// axis to match the left handed coordinates used by tridify. Mirroring also turns the glTF front
+/// faces into tridify front faces, so triangle indices are kept.
+pub struct GltfAsset {
+    pub meshes: Vec<GltfMesh>,
+    pub materials: Vec<GltfMaterial>,
+    pub images: Vec<GltfImage>,
+    pub nodes: Vec<GltfNode>,
+    /// Root nodes of the default scene.
+    pub roots: Vec<usize>,
+    /// Cameras placed in the default scene with their node world transform.
+    pub cameras: Vec<Camera>,
+}
+
+impl GltfAsset {
+    /// Load `.gltf` or `.glb` file. External buffers and images are loaded relative to the file,
+    /// decoding `%XX` escapes in their URIs.
+    ///
+    /// ```
+    /// use std::fs;
+    ///
+    /// use tridify_rs::GltfAsset;
+    ///
+    /// let dir = std::env::temp_dir();
+    /// let buffer = [1.0f32, 2.0, 3.0].map(f32::to_le_bytes).concat();
+    /// fs::write(dir.join("tridify gltf buffer.bin"), &buffer).unwrap();
+    /// let gltf = r#"{
+    ///     "asset": { "version": "2.0" },
+    ///     "buffers": [{ "byteLength": 12, "uri": "tridify%20gltf%20buffer.bin" }],
+    ///     "bufferViews": [{ "buffer": 0, "byteLength": 12 }],
+    ///     "accessors": [{
+    ///         "bufferView": 0, "componentType": 5126, "count": 1, "type": "VEC3",
+    ///         "min": [1, 2, 3], "max": [1, 2, 3]
+    ///     }],
+    ///     "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0 }, "mode": 0 }] }]
+    /// }"#;
+    /// let path = dir.join("tridify_gltf_external.gltf");
+    /// fs::write(&path, gltf).unwrap();
+    /// let asset = GltfAsset::from_path(&path).unwrap();
+    /// //Point primitives are skipped.
+    /// assert_eq!(asset.meshes[0].primitives.len(), 0);
+    ///
+    /// //External files can't be found without a base directory.
+    /// let err = GltfAsset::from_bytes(gltf.as_bytes(), None).err().unwrap();
+    /// assert_eq!(err.to_string(), "External glTF resources need a base directory.");
+    /// ```
+    pub fn from_path(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+        Self::from_bytes(&fs::read(path)?, Some(base_dir))
+    }
+
+    /// Load glTF from memory. `base_dir` is used to find external buffers and images, which are
+    /// not allowed when `None`. Base64 data URIs are always allowed.
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    ///
+    /// use base64::{engine::general_purpose::STANDARD, Engine};
+    /// use glam::Vec3;
+    /// use image::{ImageOutputFormat, Rgba, RgbaImage};
+    /// use tridify_rs::{Color, GltfAsset};
+    /// use wgpu::{AddressMode, FilterMode};
+    ///
+    /// //Quad at z = 2 facing +Z, with normals, fan indices and triangle list indices.
+    /// let mut buffer = Vec::new();
+    /// for [x, y] in [[0.0f32, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 1.0]] {
+    ///     buffer.extend([x, y, 2.0].map(f32::to_le_bytes).concat());
+    /// }
+    /// for _ in 0..4 {
+    ///     buffer.extend([0.0f32, 0.0, 1.0].map(f32::to_le_bytes).concat());
+    /// }
+    /// for i in [0u16, 1, 3, 2, 0, 1, 2, 2, 1, 3] {
+    ///     buffer.extend(i.to_le_bytes());
+    /// }
+    /// let mut png = Vec::new();
+    /// RgbaImage::from_pixel(1, 1, Rgba([10, 20, 30, 255]))
+    ///     .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+    ///     .unwrap();
+    ///
+    /// let gltf = r#"{
+    ///     "asset": { "version": "2.0" },
+    ///     "buffers": [{ "byteLength": 116, "uri": "data:application/octet-stream;base64,BUFFER" }],
+    ///     "bufferViews": [
+    ///         { "buffer": 0, "byteLength": 48 },
+    ///         { "buffer": 0, "byteOffset": 48, "byteLength": 48 },
+    ///         { "buffer": 0, "byteOffset": 96, "byteLength": 8 },
+    ///         { "buffer": 0, "byteOffset": 104, "byteLength": 12 }
+    ///     ],
+    ///     "accessors": [
+    ///         { "bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3",
+    ///           "min": [0, 0, 2], "max": [1, 1, 2] },
+    ///         { "bufferView": 1, "componentType": 5126, "count": 4, "type": "VEC3" },
+    ///         { "bufferView": 2, "componentType": 5123, "count": 4, "type": "SCALAR" },
+    ///         { "bufferView": 3, "componentType": 5123, "count": 6, "type": "SCALAR" }
+    ///     ],
+    ///     "images": [{ "uri": "data:image/png;base64,IMAGE" }],
+    ///     "samplers": [{ "magFilter": 9728, "wrapS": 33648 }],
+    ///     "textures": [{ "source": 0 }, { "source": 0, "sampler": 0 }],
+    ///     "materials": [{
+    ///         "pbrMetallicRoughness": { "baseColorFactor": [1, 0, 0, 1], "baseColorTexture": { "index": 0 } },
+    ///         "normalTexture": { "index": 1 }
+    ///     }],
+    ///     "meshes": [{ "primitives": [
+    ///         { "attributes": { "POSITION": 0, "NORMAL": 1 }, "indices": 3, "material": 0 },
+    ///         { "attributes": { "POSITION": 0 }, "mode": 5 },
+    ///         { "attributes": { "POSITION": 0 }, "indices": 2, "mode": 6 }
+    ///     ] }],
+    ///     "cameras": [{ "type": "perspective",
+    ///                   "perspective": { "yfov": 0.5, "znear": 0.1, "zfar": 100, "aspectRatio": 2 } }],
+    ///     "nodes": [
+    ///         { "translation": [1, 2, 3], "rotation": [0, 0.70710677, 0, 0.70710677], "children": [1] },
+    ///         { "camera": 0, "translation": [0, 0, 5] }
+    ///     ],
+    ///     "scenes": [{ "nodes": [0] }]
+    /// }"#
+    /// .replace("BUFFER", &STANDARD.encode(&buffer))
+    /// .replace("IMAGE", &STANDARD.encode(&png));
+    /// let asset = GltfAsset::from_bytes(gltf.as_bytes(), None).unwrap();
+    ///
+    /// //Positions and normals are mirrored in Z, keeping the triangles facing the normals.
+    /// let primitives = &asset.meshes[0].primitives;
+    /// let list = &primitives[0].mesh;
+    /// assert_eq!(list.vertices[3].pos, [1.0, 1.0, -2.0]);
+    /// assert_eq!(list.vertices[3].normal, [0.0, 0.0, -1.0]);
+    /// assert_eq!(list.tris, [0, 1, 2, 2, 1, 3]);
+    /// //Strips alternate the order of each triangle and fans share the first vertex.
+    /// assert_eq!(primitives[1].mesh.tris, [0, 1, 2, 2, 1, 3]);
+    /// assert_eq!(primitives[2].mesh.tris, [0, 1, 3, 0, 3, 2]);
+    /// for primitive in primitives {
+    ///     for tri in 0..primitive.mesh.tris.len() / 3 {
+    ///         assert_eq!(primitive.mesh.face_normal(tri), -Vec3::Z);
+    ///     }
+    /// }
+    ///
+    /// //Textures used as data are kept linear.
+    /// assert_eq!(primitives[0].material, Some(0));
+    /// let material = &asset.materials[0];
+    /// assert_eq!(material.base_color, Color::new(1.0, 0.0, 0.0, 1.0));
+    /// assert_eq!((material.base_color_texture, material.normal_texture), (Some(0), Some(1)));
+    /// assert!(!asset.images[0].linear && asset.images[1].linear);
+    /// assert_eq!(asset.images[0].image.get_pixel(0, 0), &Rgba([10, 20, 30, 255]));
+    /// assert_eq!(asset.images[1].sampler.mag_filter, FilterMode::Nearest);
+    /// assert_eq!(asset.images[1].sampler.address_mode_u, AddressMode::MirrorRepeat);
+    ///
+    /// //The camera, 5 units behind its parent rotated 90 degrees, looks along the parent -X.
+    /// let camera = &asset.cameras[0];
+    /// let eye = camera.view.inverse();
+    /// assert!(eye.position().abs_diff_eq(Vec3::new(6.0, 2.0, -3.0), 1e-5));
+    /// assert!(eye.forward().abs_diff_eq(-Vec3::X, 1e-5));
+    /// assert_eq!((camera.proj.znear(), camera.proj.zfar()), (0.1, 100.0));
+    /// ```
+    pub fn from_bytes(bytes: &[u8], base_dir: Option<&Path>) -> Result<Self, Box<dyn Error>> {
+        let gltf::Gltf { document, mut blob } = gltf::Gltf::from_slice(bytes)?;
+
+        let mut buffers = Vec::new();
+        for buffer in document.buffers() {
+            let mut data = match buffer.source() {
+                gltf::buffer::Source::Bin => blob.take().ok_or("Missing glTF binary chunk.")?,
+                gltf::buffer::Source::Uri(uri) => load_uri(uri, base_dir)?,
+            };
+            if data.len() < buffer.length() {
+                return Err(format!("glTF buffer {} is too short.", buffer.index()).into());
+            }
+            //Buffers are padded to 4 bytes.
+            data.truncate(buffer.length());
+            buffers.push(data);
+        }
+
+        let materials = document.materials().map(load_material).collect::<Vec<_>>();
+        let linear_textures = materials
+            .iter()
+            .flat_map(|x| {
+                [
+                    x.metallic_roughness_texture,
+                    x.normal_texture,
+                    x.occlusion_texture,
+                ]
+            })
+            .flatten()
+            .collect::<HashSet<_>>();
+        let images = document
+            .textures()
+            .map(|texture| {
+                let linear = linear_textures.contains(&texture.index());
+                load_image(&texture, &buffers, base_dir, linear)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let meshes = document
+            .meshes()
+            .map(|mesh| load_mesh(&mesh, &buffers))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let nodes = document
+            .nodes()
+            .map(|node| {
+                let (t, r, s) = node.transform().decomposed();
+                GltfNode {
+                    name: node.name().map(str::to_string),
+                    transform: Transform::new(
+                        Vec3::new(t[0], t[1], -t[2]),
+                        Quat::from_xyzw(-r[0], -r[1], r[2], r[3]),
+                        Vec3::from(s),
+                    ),
+                    mesh: node.mesh().map(|x| x.index()),
+                    children: node.children().map(|x| x.index()).collect(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let roots: Vec<usize> = match document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+        {
+            Some(scene) => scene.nodes().map(|x| x.index()).collect(),
+            None => {
+                let children = nodes
+                    .iter()
+                    .flat_map(|x| x.children.iter().copied())
+                    .collect::<HashSet<_>>();
+                (0..nodes.len()).filter(|x| !children.contains(x)).collect()
+            }
+        };
+
+        let node_cameras = document.nodes().map(|x| x.camera()).collect::<Vec<_>>();
+        let mut cameras = Vec::new();
+        //Reversed so nodes are popped in document order.
+        let mut stack = roots
+            .iter()
+            .rev()
+            .map(|x| (*x, Mat4::IDENTITY))
+            .collect::<Vec<_>>();
+        while let Some((index, parent)) = stack.pop() {
+            let world = parent * nodes[index].transform.build_matrix();
+            if let Some(camera) = &node_cameras[index] {
+                cameras.push(load_camera(camera, world));
+            }
+            stack.extend(nodes[index].children.iter().rev().map(|x| (*x, world)));
+        }
+
+        Ok(Self {
+            meshes,
+            materials,
+            images,
+            nodes,
+            roots,
+            cameras,
+        })
+    }
+
+    /// Upload all images, in the same order as [`GltfAsset::images`].
+    pub fn upload_textures(&self, gpu: &GpuCtx) -> Vec<GltfTexture> {
+        self.images.iter().map(|x| x.upload(gpu)).collect()
+    }
+
+    /// Add all nodes of the default scene to `scene`, returning the created root nodes. Meshes
+    /// with multiple primitives create a child node for each one. `brush` chooses the brush used
+    /// for each [`GltfAsset::materials`] index, so brushes need to be added to the scene first.
+    pub fn add_to_scene(
+        &self, gpu: &GpuCtx, scene: &mut Scene, mut brush: impl FnMut(Option<usize>) -> BrushId,
+    ) -> Vec<NodeId> {
+        let meshes = self
+            .meshes
+            .iter()
+            .map(|mesh| {
+                mesh.primitives
+                    .iter()
+                    .map(|primitive| {
+                        let buffer = ShapeBatch::new()
+                            .add_mesh(primitive.mesh.clone())
+                            .bake_buffers(gpu);
+                        (scene.add_mesh(buffer), brush(primitive.material))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        self.add_nodes(scene.nodes_mut(), |node, mesh, primitive| {
+            let (mesh, brush) = meshes[mesh][primitive];
+            node.with_mesh(mesh, brush)
+        })
+    }
+
+    /// Add all nodes of the default scene to `tree` in document order, returning the created
+    /// root nodes. `attach` is called for each node drawing a primitive with the indices of the
+    /// mesh and the primitive, usually to add the mesh with [`Node::with_mesh`]. Meshes with
+    /// multiple primitives create a child node for each one.
+    ///
+    /// ```
+    /// use base64::Engine;
+    /// use glam::Vec3;
+    /// use tridify_rs::{GltfAsset, NodeTree};
+    ///
+    /// //Two triangles at z = 1, the first one also used by a second primitive.
+    /// let mut buffer = Vec::new();
+    /// for x in [0.0f32, 0.0, 1.0, 1.0, 0.0, 1.0, 0.0, 1.0, 1.0] {
+    ///     buffer.extend(x.to_le_bytes());
+    /// }
+    /// let gltf = r#"{
+    ///     "asset": { "version": "2.0" },
+    ///     "buffers": [{ "byteLength": 36, "uri": "data:application/octet-stream;base64,BUFFER" }],
+    ///     "bufferViews": [{ "buffer": 0, "byteLength": 36 }],
+    ///     "accessors": [{
+    ///         "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+    ///         "min": [0, 0, 1], "max": [1, 1, 1]
+    ///     }],
+    ///     "meshes": [
+    ///         { "primitives": [{ "attributes": { "POSITION": 0 } }] },
+    ///         { "primitives": [
+    ///             { "attributes": { "POSITION": 0 } },
+    ///             { "attributes": { "POSITION": 0 } }
+    ///         ] }
+    ///     ],
+    ///     "nodes": [
+    ///         { "name": "root", "translation": [1, 2, 3], "scale": [2, 2, 2], "children": [1, 2] },
+    ///         { "name": "single", "mesh": 0, "translation": [0, 0, 1] },
+    ///         { "name": "double", "mesh": 1 },
+    ///         { "name": "other root" }
+    ///     ],
+    ///     "scenes": [{ "nodes": [0, 3] }]
+    /// }"#;
+    /// let data = base64::engine::general_purpose::STANDARD.encode(&buffer);
+    /// let asset = GltfAsset::from_bytes(gltf.replace("BUFFER", &data).as_bytes(), None).unwrap();
+    ///
+    /// let mut tree = NodeTree::new();
+    /// let mut attached = Vec::new();
+    /// let roots = asset.add_nodes(&mut tree, |node, mesh, primitive| {
+    ///     attached.push((node.name.clone(), mesh, primitive));
+    ///     node
+    /// });
+    /// let name = |id| tree.node(id).name.as_str();
+    /// assert_eq!(roots.iter().map(|x| name(*x)).collect::<Vec<_>>(), ["root", "other root"]);
+    /// assert_eq!(
+    ///     attached,
+    ///     [("single".into(), 0, 0), ("double".into(), 1, 0), ("double".into(), 1, 1)]
+    /// );
+    ///
+    /// //Children keep the document order, translations are mirrored in Z.
+    /// let children = tree.node(roots[0]).children().to_vec();
+    /// assert_eq!(name(children[0]), "single");
+    /// let world = tree.world_transform(children[0]);
+    /// assert!(world.position().abs_diff_eq(Vec3::new(1.0, 2.0, -5.0), 1e-6));
+    /// assert!(world.scale().abs_diff_eq(Vec3::splat(2.0), 1e-6));
+    ///
+    /// //Each primitive of the second mesh gets its own child node.
+    /// assert_eq!(tree.node(children[1]).children().len(), 2);
+    /// ```
+    pub fn add_nodes(
+        &self, tree: &mut NodeTree, mut attach: impl FnMut(Node, usize, usize) -> Node,
+    ) -> Vec<NodeId> {
+        let mut roots = Vec::new();
+        //Reversed so nodes are popped in document order.
+        let mut stack = self
+            .roots
+            .iter()
+            .rev()
+            .map(|x| (*x, None))
+            .collect::<Vec<_>>();
+        while let Some((index, parent)) = stack.pop() {
+            let gltf_node = &self.nodes[index];
+            let name = gltf_node.name.clone().unwrap_or_default();
+            let mut node = Node::new(&name).with_transform(gltf_node.transform);
+            let primitives = gltf_node
+                .mesh
+                .map_or(0, |x| self.meshes[x].primitives.len());
+            if let (Some(mesh), 1) = (gltf_node.mesh, primitives) {
+                node = attach(node, mesh, 0);
+            }
+            let id = tree.add_node(node, parent);
+            if let (Some(mesh), 2..) = (gltf_node.mesh, primitives) {
+                for primitive in 0..primitives {
+                    tree.add_node(attach(Node::new(&name), mesh, primitive), Some(id));
+                }
+            }
+            if parent.is_none() {
+                roots.push(id);
+            }
+            stack.extend(gltf_node.children.iter().rev().map(|x| (*x, Some(id))));
+        }
+        roots
+    }
+}
+
+/// Read data from a base64 data URI or from a file relative to `base_dir`.
+fn load_uri(uri: &str, base_dir: Option<&Path>) -> Result<Vec<u8>, Box<dyn Error>> {
+    if let Some(data) = uri.strip_prefix("data:") {
+        let (_, data) = data
+            .split_once(";base64,")
+            .ok_or("Only base64 data URIs are supported.")?;
+        return Ok(base64::engine::general_purpose::STANDARD.decode(data)?);
+    }
+    let base_dir = base_dir.ok_or("External glTF resources need a base directory.")?;
+    let path: PathBuf = base_dir.join(percent_decode(uri));
+    fs::read(&path).map_err(|e| format!("Error loading {:?}: {}", path, e).into())
+}
+
+/// Decode `%XX` escapes used in relative URIs.
+fn percent_decode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|x| std::str::from_utf8(x).ok())
+            .and_then(|x| u8::from_str_radix(x, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(value)) => {
+                out.push(value);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn load_material(material: gltf::Material) -> GltfMaterial {
+    let pbr = material.pbr_metallic_roughness();
+    let [r, g, b, a] = pbr.base_color_factor();
+    GltfMaterial {
+        name: material.name().map(str::to_string),
+        base_color: Color::new(r, g, b, a),
+        base_color_texture: pbr.base_color_texture().map(|x| x.texture().index()),
+        metallic: pbr.metallic_factor(),
+        roughness: pbr.roughness_factor(),
+        metallic_roughness_texture: pbr
+            .metallic_roughness_texture()
+            .map(|x| x.texture().index()),
+        normal_texture: material.normal_texture().map(|x| x.texture().index()),
+        occlusion_texture: material.occlusion_texture().map(|x| x.texture().index()),
+        emissive: Vec3::from(material.emissive_factor()),
+        emissive_texture: material.emissive_texture().map(|x| x.texture().index()),
+        double_sided: material.double_sided(),
+        transparent: material.alpha_mode() == gltf::material::AlphaMode::Blend,
+        alpha_cutoff: match material.alpha_mode() {
+            gltf::material::AlphaMode::Mask => Some(material.alpha_cutoff().unwrap_or(0.5)),
+            _ => None,
+        },
+    }
+}
+
+/// Decode texture image. Color textures are stored in sRGB while data textures, like normal
+/// maps, are stored linearly.
+fn load_image(
+    texture: &gltf::Texture, buffers: &[Vec<u8>], base_dir: Option<&Path>, linear: bool,
+) -> Result<GltfImage, Box<dyn Error>> {
+    let image = match texture.source().source() {
+        gltf::image::Source::View { view, .. } => {
+            let buffer = &buffers[view.buffer().index()];
+            image::load_from_memory(&buffer[view.offset()..view.offset() + view.length()])?
+        }
+        gltf::image::Source::Uri { uri, .. } => image::load_from_memory(&load_uri(uri, base_dir)?)?,
+    }
+    .into_rgba8();
+
+    let sampler = texture.sampler();
+    let address_mode = |mode: WrappingMode| match mode {
+        WrappingMode::ClampToEdge => AddressMode::ClampToEdge,
+        WrappingMode::MirroredRepeat => AddressMode::MirrorRepeat,
+        WrappingMode::Repeat => AddressMode::Repeat,
+    };
+    let mag_filter = match sampler.mag_filter() {
+        Some(MagFilter::Nearest) => FilterMode::Nearest,
+        _ => FilterMode::Linear,
+    };
+    let min_filter = match sampler.min_filter() {
+        Some(MinFilter::Nearest)
+        | Some(MinFilter::NearestMipmapNearest)
+        | Some(MinFilter::NearestMipmapLinear) => FilterMode::Nearest,
+        _ => FilterMode::Linear,
+    };
+    Ok(GltfImage {
+        name: texture.name().map(str::to_string),
+        image,
+        linear,
+        sampler: wgpu::SamplerDescriptor {
+            address_mode_u: address_mode(sampler.wrap_s()),
+            address_mode_v: address_mode(sampler.wrap_t()),
+            mag_filter,
+            min_filter,
+            ..Default::default()
+        },
+    })
+}
+
+fn load_mesh(mesh: &gltf::Mesh, buffers: &[Vec<u8>]) -> Result<GltfMesh, Box<dyn Error>> {
+    let mut primitives = Vec::new();
+    for primitive in mesh.primitives() {
+        let mode = primitive.mode();
+        if !matches!(
+            mode,
+            Mode::Triangles | Mode::TriangleStrip | Mode::TriangleFan
+        ) {
+            log::warn!("Skipping glTF primitive with {:?} mode.", mode);
+            continue;
+        }
+        let reader = primitive.reader(|x| buffers.get(x.index()).map(|x| x.as_slice()));
+        let positions = reader
+            .read_positions()
+            .ok_or("glTF primitive without positions.")?
+            .collect::<Vec<_>>();
+        let normals = reader
+            .read_normals()
+            .map(|x| x.collect::<Vec<_>>())
+            .unwrap_or_default();
+        let uvs = reader
+            .read_tex_coords(0)
+            .map(|x| x.into_f32().collect::<Vec<_>>())
+            .unwrap_or_default();
+        let colors = reader
+            .read_colors(0)
+            .map(|x| x.into_rgba_f32().collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        let vertices = positions
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let color = colors.get(i).map(|c| Color::new(c[0], c[1], c[2], c[3]));
+                let normal = normals
+                    .get(i)
+                    .map_or(Vec3::ZERO, |n| Vec3::new(n[0], n[1], -n[2]));
+                Vertex::new(p[0], p[1], -p[2], color, uvs.get(i).copied()).with_normal(normal)
+            })
+            .collect::<Vec<_>>();
+        let indices = match reader.read_indices() {
+            Some(indices) => indices.into_u32().collect::<Vec<_>>(),
+            None => (0..vertices.len() as u32).collect(),
+        };
+        let tris = match mode {
+            Mode::TriangleStrip => (2..indices.len())
+                .flat_map(|i| match i % 2 {
+                    0 => [indices[i - 2], indices[i - 1], indices[i]],
+                    _ => [indices[i - 1], indices[i - 2], indices[i]],
+                })
+                .collect(),
+            Mode::TriangleFan => (2..indices.len())
+                .flat_map(|i| [indices[0], indices[i - 1], indices[i]])
+                .collect(),
+            _ => indices,
+        };
+        if tris.iter().any(|x| *x as usize >= vertices.len()) {
+            return Err("glTF primitive index out of bounds.".into());
+        }
+        primitives.push(GltfPrimitive {
+            mesh: Mesh::new(vertices, tris),
+            material: primitive.material().index(),
+        });
+    }
+    Ok(GltfMesh {
+        name: mesh.name().map(str::to_string),
+        primitives,
+    })
+}
+
+/// Camera looking through the node local +Z axis, which is the glTF -Z axis once mirrored.
+fn load_camera(camera: &gltf::Camera, world: Mat4) -> Camera {
+    let (_, rotation, position) = world.to_scale_rotation_translation();
+    let view = Transform::from_look_to(position, rotation * Vec3::Z, rotation * Vec3::Y);
+    let proj = match camera.projection() {
+        gltf::camera::Projection::Perspective(perspective) => Projection::new(
+            perspective.aspect_ratio().unwrap_or(16.0 / 9.0),
+            perspective.yfov().to_degrees(),
+            perspective.znear(),
+            perspective.zfar().unwrap_or(Projection::default().zfar()),
+        ),
+        //Magnifications are half the view size.
+        gltf::camera::Projection::Orthographic(orthographic) => Projection::orthographic(
+            orthographic.xmag() / orthographic.ymag(),
+            orthographic.ymag() * 2.0,
+            orthographic.znear(),
+            orthographic.zfar(),
+        ),
+    };
+    Camera::new(view, proj)
+}
@@ -1,63 +1,310 @@
-use std::rc::Rc;
+use std::{marker::PhantomData, mem::size_of, rc::Rc};
 
-use wgpu::{util::DeviceExt, Buffer};
+use bytemuck::Pod;
+pub use tridify_derive::ToGpuBuf;
+use wgpu::{util::DeviceExt, Buffer, BufferUsages, ShaderStages, COPY_BUFFER_ALIGNMENT};
 
-use crate::{GpuCtx, ToBinder, Window};
+use crate::{GpuCtx, GpuLayout, ToBinder};
 
+bitflags::bitflags! {
+    /// Specifies how the buffer will be used.
+    pub struct GpuBufferUsage: u32 {
+        const DESTINATION = 1 << 0;
+        const SOURCE = 1 << 1;
+        const UNIFORM = 1 << 2;
+        const STORAGE = 1 << 3;
+        const VERTEX = 1 << 4;
+        const INDEX = 1 << 5;
+        const INDIRECT = 1 << 6;
+        const READ = 1 << 7;
+    }
+}
+
+/// How the buffer is accessed from shaders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpuBufferBinding {
+    /// `var<uniform>` in WGSL.
+    Uniform,
+    /// `var<storage, read>` or `var<storage, read_write>` in WGSL.
+    Storage { read_only: bool },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GpuBufferDesc {
+    pub usage: GpuBufferUsage,
+    pub binding: GpuBufferBinding,
+    /// Shader stages that can access the buffer when binded.
+    pub visibility: ShaderStages,
+}
+impl GpuBufferDesc {
+    /// Uniform buffer visible from vertex and fragment shaders.
+    pub fn uniform() -> Self {
+        Self {
+            usage: GpuBufferUsage::UNIFORM | GpuBufferUsage::DESTINATION,
+            binding: GpuBufferBinding::Uniform,
+            visibility: ShaderStages::VERTEX_FRAGMENT,
+        }
+    }
+
+    /// Storage buffer visible from all shader stages. Read only storage buffers can be used from
+    /// vertex shaders while read-write buffers can only be used from fragment and compute shaders.
+    /// Not available on WebGL2, whose limits don't allow storage buffers in any shader stage.
+    pub fn storage(read_only: bool) -> Self {
+        let visibility = if read_only {
+            ShaderStages::all()
+        } else {
+            ShaderStages::FRAGMENT | ShaderStages::COMPUTE
+        };
+        Self {
+            usage: GpuBufferUsage::STORAGE | GpuBufferUsage::DESTINATION | GpuBufferUsage::SOURCE,
+            binding: GpuBufferBinding::Storage { read_only },
+            visibility,
+        }
+    }
+
+    pub fn with_usage(mut self, usage: GpuBufferUsage) -> Self {
+        self.usage |= usage;
+        self
+    }
+
+    pub fn with_visibility(mut self, visibility: ShaderStages) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
+    fn get_wgpu_usage(&self) -> BufferUsages {
+        let mut usage = BufferUsages::empty();
+        if self.usage.contains(GpuBufferUsage::DESTINATION) {
+            usage |= BufferUsages::COPY_DST;
+        }
+        if self.usage.contains(GpuBufferUsage::SOURCE) {
+            usage |= BufferUsages::COPY_SRC;
+        }
+        if self.usage.contains(GpuBufferUsage::UNIFORM) {
+            usage |= BufferUsages::UNIFORM;
+        }
+        if self.usage.contains(GpuBufferUsage::STORAGE) {
+            usage |= BufferUsages::STORAGE;
+        }
+        if self.usage.contains(GpuBufferUsage::VERTEX) {
+            usage |= BufferUsages::VERTEX;
+        }
+        if self.usage.contains(GpuBufferUsage::INDEX) {
+            usage |= BufferUsages::INDEX;
+        }
+        if self.usage.contains(GpuBufferUsage::INDIRECT) {
+            usage |= BufferUsages::INDIRECT;
+        }
+        if self.usage.contains(GpuBufferUsage::READ) {
+            usage |= BufferUsages::MAP_READ;
+        }
+        usage
+    }
+}
+impl Default for GpuBufferDesc {
+    fn default() -> Self { Self::uniform() }
+}
+
+/// Types that can be packed into bytes to be sent to the GPU. Can be derived for structs whose
+/// fields implement [`crate::GpuField`], packing them in declaration order.
+///
+/// #Examples
+/// ``` rust
+/// use glam::Vec3;
+/// use tridify_rs::{Color, GpuLayout, ToGpuBuf};
+///
+/// #[derive(ToGpuBuf)]
+/// struct Light {
+///     position: Vec3,
+///     intensity: f32,
+///     color: Color,
+/// }
+///
+/// let light = Light { position: Vec3::ONE, intensity: 2.0, color: Color::WHITE };
+/// //Intensity fits in the padding after the position.
+/// assert_eq!(light.to_gpu_bytes(GpuLayout::Std140).len(), 32);
+///
+/// #[derive(ToGpuBuf)]
+/// struct Weights([f32; 3]);
+/// //Array elements are aligned to 16 bytes in std140 only.
+/// assert_eq!(Weights([1.0; 3]).to_gpu_bytes(GpuLayout::Std140).len(), 48);
+/// assert_eq!(Weights([1.0; 3]).to_gpu_bytes(GpuLayout::Std430).len(), 12);
+/// ```
 pub trait ToGpuBuf {
-    fn build_buffer(&self, wnd: &GpuCtx) -> GpuBuffer;
+    /// Pack data following the given memory layout rules.
+    fn to_gpu_bytes(&self, layout: GpuLayout) -> Vec<u8>;
+
+    /// Create a uniform buffer with the data packed using std140 layout.
+    fn build_buffer(&self, wnd: &GpuCtx) -> GpuBuffer {
+        GpuBuffer::init(wnd, &self.to_gpu_bytes(GpuLayout::Std140))
+    }
 }
 
-/// Handle to a GPU buffer.
-pub struct GpuBuffer {
+/// Handle to a GPU buffer storing elements of type `T`. Cloning it is cheap and points to the same
+/// GPU data.
+pub struct GpuBuffer<T: Pod = u8> {
     buffer: Rc<Buffer>,
+    desc: GpuBufferDesc,
+    len: u64,
+    _data: PhantomData<T>,
 }
 
-impl GpuBuffer {
-    /// Creates a new buffer with uninitialized data.
-    fn new(wnd: &GpuCtx) -> Self {
+impl<T: Pod> GpuBuffer<T> {
+    /// Creates a new buffer able to store `len` elements. Data is initialized to zero.
+    pub fn new(wnd: &GpuCtx, desc: GpuBufferDesc, len: u64) -> Self {
         let buffer = wnd.device.create_buffer(&wgpu::BufferDescriptor {
             label: None,
-            size: todo!(),
-            usage: todo!(),
-            mapped_at_creation: todo!(),
+            size: Self::padded_size(len),
+            usage: desc.get_wgpu_usage(),
+            mapped_at_creation: false,
         });
         Self {
             buffer: Rc::new(buffer),
+            desc,
+            len,
+            _data: PhantomData,
         }
     }
 
-    /// Creates a buffer with the given bytes.
-    pub fn init(wnd: &GpuCtx, data: &[u8]) -> Self {
+    /// Creates a uniform buffer with the given data.
+    pub fn init(wnd: &GpuCtx, data: &[T]) -> Self {
+        Self::init_with_desc(wnd, GpuBufferDesc::uniform(), data)
+    }
+
+    /// Creates a buffer with the given data and description.
+    pub fn init_with_desc(wnd: &GpuCtx, desc: GpuBufferDesc, data: &[T]) -> Self {
         let buffer = wnd
             .device
             .create_buffer_init(&wgpu::util::BufferInitDescriptor {
                 label: None,
-                contents: data,
-                //TODO: User config
-                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                contents: bytemuck::cast_slice(data),
+                usage: desc.get_wgpu_usage(),
             });
 
         Self {
             buffer: Rc::new(buffer),
+            desc,
+            len: data.len() as u64,
+            _data: PhantomData,
         }
     }
 
-    /// Update buffer GPU data with bytes provided.
-    pub fn write(&mut self, wnd: &GpuCtx, data: &[u8]) {
-        wnd.queue.write_buffer(&self.buffer, 0, data);
+    /// Update first element of the buffer.
+    pub fn write(&self, wnd: &GpuCtx, value: &T) {
+        self.write_at(wnd, 0, std::slice::from_ref(value));
+    }
+
+    /// Update buffer GPU data with the elements provided.
+    pub fn write_slice(&self, wnd: &GpuCtx, data: &[T]) { self.write_at(wnd, 0, data); }
+
+    /// Update buffer GPU data starting at the given element index. The written bytes need to
+    /// start and end at multiples of 4, except for writes reaching the end of the buffer which are
+    /// padded with zeros into the unused space allocated after their last element.
+    ///
+    /// ``` no_run
+    /// # use tridify_rs::{GpuBuffer, GpuBufferDesc, GpuCtx};
+    /// # fn write(gpu: &GpuCtx) {
+    /// let bytes = GpuBuffer::<u8>::new(gpu, GpuBufferDesc::storage(false), 6);
+    /// //Writes bytes 4 and 5, plus two padding bytes past the end of the buffer.
+    /// bytes.write_at(gpu, 4, &[1, 2]);
+    /// bytes.write_at(gpu, 0, &[1, 2, 3, 4]);
+    /// //Would panic instead of zeroing bytes 2 and 3.
+    /// //bytes.write_at(gpu, 0, &[1, 2]);
+    /// # }
+    /// ```
+    pub fn write_at(&self, wnd: &GpuCtx, index: u64, data: &[T]) {
+        let end = index + data.len() as u64;
+        assert!(end <= self.len, "Writing out of GPU buffer bounds.");
+        let offset = Self::byte_size(index);
+        assert!(
+            offset.is_multiple_of(COPY_BUFFER_ALIGNMENT),
+            "GPU buffer writes need to start at a multiple of {COPY_BUFFER_ALIGNMENT} bytes."
+        );
+        assert!(
+            end == self.len || Self::byte_size(end).is_multiple_of(COPY_BUFFER_ALIGNMENT),
+            "GPU buffer writes need to end at a multiple of {COPY_BUFFER_ALIGNMENT} bytes or at \
+             the end of the buffer."
+        );
+        let bytes: &[u8] = bytemuck::cast_slice(data);
+        if (bytes.len() as u64).is_multiple_of(COPY_BUFFER_ALIGNMENT) {
+            wnd.queue.write_buffer(&self.buffer, offset, bytes);
+        } else {
+            let mut padded = bytes.to_vec();
+            padded.resize(align_to_copy(bytes.len() as u64) as usize, 0);
+            wnd.queue.write_buffer(&self.buffer, offset, &padded);
+        }
     }
+
+    /// Change the amount of elements the buffer can store. A new GPU buffer is created, so any
+    /// brush using this buffer needs to bind it again. Old data is kept when the buffer has
+    /// `SOURCE` and `DESTINATION` usages.
+    pub fn resize(&mut self, wnd: &GpuCtx, len: u64) {
+        let new_buffer = Self::new(wnd, self.desc, len);
+        let can_copy = self
+            .desc
+            .usage
+            .contains(GpuBufferUsage::SOURCE | GpuBufferUsage::DESTINATION);
+        if can_copy {
+            let mut encoder = wnd
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+            encoder.copy_buffer_to_buffer(
+                &self.buffer,
+                0,
+                &new_buffer.buffer,
+                0,
+                Self::padded_size(self.len.min(len)),
+            );
+            wnd.queue.submit(Some(encoder.finish()));
+        }
+        *self = new_buffer;
+    }
+
+    /// Amount of elements the buffer can store.
+    pub fn len(&self) -> u64 { self.len }
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Size of the buffer in bytes.
+    pub fn size(&self) -> u64 { Self::byte_size(self.len) }
+
+    pub fn desc(&self) -> &GpuBufferDesc { &self.desc }
+
+    /// Handle to the same GPU data binded as a read only storage buffer, visible from all shader
+    /// stages. Allows reading from vertex shaders a buffer written by compute shaders.
+    pub fn as_read_only(&self) -> Self {
+        assert!(
+            self.desc.usage.contains(GpuBufferUsage::STORAGE),
+            "Buffer needs STORAGE usage to be binded as read only storage."
+        );
+        let mut buffer = self.clone();
+        buffer.desc.binding = GpuBufferBinding::Storage { read_only: true };
+        buffer.desc.visibility = ShaderStages::all();
+        buffer
+    }
+
+    pub(crate) fn buffer(&self) -> &Buffer { &self.buffer }
+
+    fn byte_size(len: u64) -> u64 { len * size_of::<T>() as u64 }
+
+    /// Size in bytes allocated for `len` elements. GPU copies work in multiples of 4 bytes.
+    fn padded_size(len: u64) -> u64 { align_to_copy(Self::byte_size(len)) }
 }
 
-impl ToBinder for GpuBuffer {
+fn align_to_copy(size: u64) -> u64 { size.div_ceil(COPY_BUFFER_ALIGNMENT) * COPY_BUFFER_ALIGNMENT }
+
+impl<T: Pod> ToBinder for GpuBuffer<T> {
     fn get_layout(&self, index: u32) -> wgpu::BindGroupLayoutEntry {
+        let ty = match self.desc.binding {
+            GpuBufferBinding::Uniform => wgpu::BufferBindingType::Uniform,
+            GpuBufferBinding::Storage { read_only } => {
+                wgpu::BufferBindingType::Storage { read_only }
+            }
+        };
         wgpu::BindGroupLayoutEntry {
             binding: index,
-            //TODO: User should be able to config this.
-            visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+            visibility: self.desc.visibility,
             ty: wgpu::BindingType::Buffer {
-                //TODO: User should be able to config this.
-                ty: wgpu::BufferBindingType::Uniform,
+                ty,
                 has_dynamic_offset: false,
                 min_binding_size: None,
             },
@@ -65,7 +312,7 @@ impl ToBinder for GpuBuffer {
         }
     }
 
-    fn get_group(&self, index: u32) -> wgpu::BindGroupEntry {
+    fn get_group(&self, index: u32) -> wgpu::BindGroupEntry<'_> {
         wgpu::BindGroupEntry {
             binding: index,
             resource: self.buffer.as_entire_binding(),
@@ -75,10 +322,13 @@ impl ToBinder for GpuBuffer {
     fn debug_name(&self) -> &'static str { "GPU Buffer" }
 }
 
-impl Clone for GpuBuffer {
+impl<T: Pod> Clone for GpuBuffer<T> {
     fn clone(&self) -> Self {
         Self {
             buffer: Rc::clone(&self.buffer),
+            desc: self.desc,
+            len: self.len,
+            _data: PhantomData,
         }
     }
 }
@@ -0,0 +1,135 @@
+use glam::{IVec2, IVec3, IVec4, Mat3, Mat4, UVec2, UVec3, UVec4, Vec2, Vec3, Vec4};
+
+use crate::Color;
+
+/// Memory layout rules used to pack data sent to the GPU. WGSL uniform buffers follow `Std140`
+/// rules while storage buffers follow `Std430`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpuLayout {
+    Std140,
+    Std430,
+}
+
+/// Value that can be written as a field of a GPU struct.
+pub trait GpuField {
+    /// Alignment in bytes of the field.
+    fn gpu_align(layout: GpuLayout) -> usize;
+    /// Size in bytes of the field, without trailing padding.
+    fn gpu_size(layout: GpuLayout) -> usize;
+    fn write_gpu_bytes(&self, layout: GpuLayout, out: &mut Vec<u8>);
+}
+
+macro_rules! impl_gpu_field {
+    ($ty:ty, $align:expr, $size:expr, |$value:ident| $bytes:expr) => {
+        impl GpuField for $ty {
+            fn gpu_align(_: GpuLayout) -> usize { $align }
+            fn gpu_size(_: GpuLayout) -> usize { $size }
+            fn write_gpu_bytes(&self, _: GpuLayout, out: &mut Vec<u8>) {
+                let $value = self;
+                out.extend_from_slice(bytemuck::cast_slice(&$bytes));
+            }
+        }
+    };
+}
+
+impl_gpu_field!(f32, 4, 4, |x| [*x]);
+impl_gpu_field!(u32, 4, 4, |x| [*x]);
+impl_gpu_field!(i32, 4, 4, |x| [*x]);
+impl_gpu_field!(Vec2, 8, 8, |x| x.to_array());
+impl_gpu_field!(UVec2, 8, 8, |x| x.to_array());
+impl_gpu_field!(IVec2, 8, 8, |x| x.to_array());
+impl_gpu_field!(Vec3, 16, 12, |x| x.to_array());
+impl_gpu_field!(UVec3, 16, 12, |x| x.to_array());
+impl_gpu_field!(IVec3, 16, 12, |x| x.to_array());
+impl_gpu_field!(Vec4, 16, 16, |x| x.to_array());
+impl_gpu_field!(UVec4, 16, 16, |x| x.to_array());
+impl_gpu_field!(IVec4, 16, 16, |x| x.to_array());
+impl_gpu_field!(Color, 16, 16, |x| [x.r, x.g, x.b, x.a]);
+impl_gpu_field!(Mat4, 16, 64, |x| x.to_cols_array());
+//Each column of a 3x3 matrix is padded as a vec4.
+impl_gpu_field!(Mat3, 16, 48, |x| [
+    x.x_axis.extend(0.),
+    x.y_axis.extend(0.),
+    x.z_axis.extend(0.)
+]
+.map(|c| c.to_array()));
+
+impl<T: GpuField, const N: usize> GpuField for [T; N] {
+    fn gpu_align(layout: GpuLayout) -> usize {
+        match layout {
+            GpuLayout::Std140 => T::gpu_align(layout).max(16),
+            GpuLayout::Std430 => T::gpu_align(layout),
+        }
+    }
+
+    fn gpu_size(layout: GpuLayout) -> usize { array_stride::<T, N>(layout) * N }
+
+    fn write_gpu_bytes(&self, layout: GpuLayout, out: &mut Vec<u8>) {
+        let stride = array_stride::<T, N>(layout);
+        for value in self.iter() {
+            let start = out.len();
+            value.write_gpu_bytes(layout, out);
+            out.resize(start + stride, 0);
+        }
+    }
+}
+
+/// Distance in bytes between array elements.
+fn array_stride<T: GpuField, const N: usize>(layout: GpuLayout) -> usize {
+    round_up(T::gpu_size(layout), <[T; N]>::gpu_align(layout))
+}
+
+fn round_up(value: usize, align: usize) -> usize { value.div_ceil(align) * align }
+
+/// Packs fields one after another adding the padding required by the layout rules.
+///
+/// #Examples
+/// ``` rust
+/// use tridify_rs::{GpuLayout, GpuStructWriter};
+/// let bytes = GpuStructWriter::new(GpuLayout::Std140)
+///     .field(&1.0f32)
+///     .field(&[2.0f32, 3.0])
+///     .finish();
+/// //Array elements are aligned to 16 bytes in std140.
+/// assert_eq!(bytes.len(), 48);
+///
+/// let bytes = GpuStructWriter::new(GpuLayout::Std430)
+///     .field(&1.0f32)
+///     .field(&[2.0f32, 3.0])
+///     .finish();
+/// assert_eq!(bytes.len(), 12);
+/// ```
+pub struct GpuStructWriter {
+    layout: GpuLayout,
+    bytes: Vec<u8>,
+    align: usize,
+}
+impl GpuStructWriter {
+    pub fn new(layout: GpuLayout) -> Self {
+        Self {
+            layout,
+            bytes: Vec::new(),
+            align: 1,
+        }
+    }
+
+    /// Append a field aligning it according to the layout rules.
+    pub fn field<T: GpuField>(&mut self, value: &T) -> &mut Self {
+        let align = T::gpu_align(self.layout);
+        self.bytes.resize(round_up(self.bytes.len(), align), 0);
+        value.write_gpu_bytes(self.layout, &mut self.bytes);
+        self.align = self.align.max(align);
+        self
+    }
+
+    /// Return packed bytes with the struct trailing padding.
+    pub fn finish(&mut self) -> Vec<u8> {
+        let align = match self.layout {
+            GpuLayout::Std140 => self.align.max(16),
+            GpuLayout::Std430 => self.align,
+        };
+        let mut bytes = std::mem::take(&mut self.bytes);
+        bytes.resize(round_up(bytes.len(), align), 0);
+        bytes
+    }
+}
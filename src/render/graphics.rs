@@ -6,8 +6,8 @@ use std::{
 
 use glam::UVec2;
 use wgpu::{
-    Adapter, Backends, Device, DeviceDescriptor, Features, InstanceDescriptor, Limits, Queue,
-    RequestAdapterOptions, Surface, SurfaceConfiguration, TextureFormat, TextureUsages,
+    DeviceDescriptor, Features, InstanceDescriptor, Limits, RequestAdapterOptions,
+    SurfaceConfiguration, TextureUsages,
 };
 use winit::{
     event::{Event, WindowEvent},
@@ -15,7 +15,7 @@ use winit::{
     window::WindowId,
 };
 
-use crate::{GpuCtx, RenderOptions, RenderPass, Texture, Window};
+use crate::{GpuCtx, Window, TEXTURE_COMPRESSION_FEATURES};
 
 /// Represents basic information for a given windows rendering frame.
 pub struct FrameContext<'a> {
@@ -24,6 +24,9 @@ pub struct FrameContext<'a> {
     pub delta_time: f64,
     pub elapsed_time: f64,
     pub winit_event: &'a Event<'a, ()>,
+    /// Window events received since the previous frame, in order. Scale factor changes are not
+    /// included.
+    pub input_events: &'a [Event<'static, ()>],
     eloop: &'a EventLoopWindowTarget<()>,
 }
 
@@ -33,6 +36,9 @@ pub struct Tridify {
     wb: Option<EventLoop<()>>,
     wgpu: wgpu::Instance,
 }
+impl Default for Tridify {
+    fn default() -> Self { Self::new() }
+}
 impl Tridify {
     pub fn new() -> Self {
         // cfg_if::cfg_if! {
@@ -70,11 +76,14 @@ impl Tridify {
         }))
         .ok_or("Error requesting adapter.")?;
 
+        //Optional features are only requested if the adapter supports them.
+        let features =
+            adapter.features() & (TEXTURE_COMPRESSION_FEATURES | Features::MULTI_DRAW_INDIRECT);
         let (device, queue) = pollster::block_on(adapter.request_device(
             &DeviceDescriptor {
                 label: None,
-                features: Features::empty(),
-                limits: Limits::downlevel_webgl2_defaults(),
+                features,
+                limits: Limits::downlevel_defaults().using_resolution(adapter.limits()),
             },
             None,
         ))?;
@@ -108,9 +117,11 @@ impl Tridify {
 
         let window = Window {
             user_loop: None,
+            input_events: Vec::new(),
             ctx: GpuCtx {
                 created_time: Instant::now(),
                 last_draw_time: Instant::now(),
+                last_frame_time: Instant::now(),
                 winit_wnd: wnd,
                 adapter,
                 device,
@@ -159,9 +170,27 @@ impl Tridify {
                     }
                     _ => {}
                 }
+
+                //Keep events for the next frame of the window. Cursor moves following each other
+                //only keep the last position, so windows not drawn for a while don't pile them up.
+                if let Ok(wnd) = self.get_window_mut(&window_id) {
+                    let is_cursor_move = |x: Option<&Event<()>>| {
+                        matches!(
+                            x,
+                            Some(Event::WindowEvent {
+                                event: WindowEvent::CursorMoved { .. },
+                                ..
+                            })
+                        )
+                    };
+                    if is_cursor_move(Some(&event)) && is_cursor_move(wnd.input_events.last()) {
+                        wnd.input_events.pop();
+                    }
+                    wnd.input_events.extend(event.to_static());
+                }
             }
             Event::MainEventsCleared => {
-                for (id, wnd) in self.windows.iter_mut() {
+                for wnd in self.windows.values_mut() {
                     //TODO: User configurable
                     if wnd.ctx().last_draw_time.elapsed() >= Duration::from_millis(16.6 as u64) {
                         wnd.view_mut().redraw();
@@ -171,10 +200,17 @@ impl Tridify {
             }
             Event::RedrawRequested(id) => {
                 let wnd = self.get_window_mut(&id).unwrap();
+                let input_events = std::mem::take(&mut wnd.input_events);
+                //Redraws are requested right after resetting the draw time, so the delta time is
+                //measured from the start of the previous frame instead.
+                let now = Instant::now();
+                let delta_time = now - wnd.ctx().last_frame_time;
+                wnd.view_mut().last_frame_time = now;
                 let frame_ctx = FrameContext {
-                    delta_time: wnd.ctx().last_draw_time.elapsed().as_secs_f64(),
+                    delta_time: delta_time.as_secs_f64(),
                     elapsed_time: wnd.ctx().time_running().as_secs_f64(),
                     winit_event: &event,
+                    input_events: &input_events,
                     // user_ctx: &user_ctx,
                     eloop,
                 };
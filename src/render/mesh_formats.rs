@@ -0,0 +1,652 @@
+use std::{collections::HashMap, error::Error, fs, path::Path};
+
+use glam::Vec3;
+
+use crate::{Color, Mesh, Vertex};
+
+/// Position, uv, normal and material indices of an OBJ face vertex.
+type ObjVertexKey = (usize, Option<usize>, Option<usize>, Option<usize>);
+
+impl Mesh {
+    /// Load Wavefront OBJ file. Polygons are triangulated and the diffuse color and opacity of
+    /// the materials found in `mtllib` files are multiplied into the vertex colors.
+    ///
+    /// Like glTF, OBJ uses right handed coordinates, so positions and normals are mirrored in the
+    /// Z axis. Malformed lines are reported with their line number.
+    ///
+    /// ```
+    /// use std::fs;
+    ///
+    /// use tridify_rs::{Color, Mesh};
+    ///
+    /// let dir = std::env::temp_dir();
+    /// fs::write(dir.join("tridify_from_obj.mtl"), "newmtl red\nKd 1 0 0\nd 0.5\n").unwrap();
+    /// let path = dir.join("tridify_from_obj.obj");
+    /// let obj = "\
+    /// mtllib tridify_from_obj.mtl
+    /// v 0 0 0
+    /// v 1 0 0
+    /// v 1 1 0
+    /// v 0 1 1
+    /// vt 0 0
+    /// vn 0 0 1
+    /// usemtl red
+    /// # Negative indices count back from the last vertex read.
+    /// f -4/1/1 -3/1/1 -2/1/1 -1/1/1
+    /// ";
+    /// fs::write(&path, obj).unwrap();
+    /// let mesh = Mesh::from_obj(&path).unwrap();
+    ///
+    /// //The quad is split into a triangle fan.
+    /// assert_eq!(mesh.vertices.len(), 4);
+    /// assert_eq!(mesh.tris, [0, 1, 2, 0, 2, 3]);
+    /// assert_eq!(mesh.vertices[3].pos, [0.0, 1.0, -1.0]);
+    /// assert_eq!(mesh.vertices[0].normal, [0.0, 0.0, -1.0]);
+    /// assert_eq!(mesh.vertices[0].uv, [0.0, 1.0]);
+    /// assert_eq!(mesh.vertices[0].color, Color::new(1.0, 0.0, 0.0, 0.5));
+    ///
+    /// fs::write(&path, "v 0 0 0\nv 1 0 0\n\nf 1 2 3\n").unwrap();
+    /// let err = Mesh::from_obj(&path).unwrap_err().to_string();
+    /// assert!(err.ends_with(":4: Face index 3 out of range."), "{}", err);
+    /// ```
+    pub fn from_obj(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let source = fs::read_to_string(path)?;
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+        let mut positions = Vec::new();
+        let mut colors = Vec::new();
+        let mut uvs = Vec::new();
+        let mut normals = Vec::new();
+        let mut materials: Vec<(String, Color)> = Vec::new();
+        let mut material = None;
+        //Each vertex, uv, normal and material combination is a different mesh vertex.
+        let mut indices: HashMap<ObjVertexKey, u32> = HashMap::new();
+        let mut mesh = Mesh::default();
+
+        for (i, line) in source.lines().enumerate() {
+            let err = |msg: String| -> Box<dyn Error> {
+                format!("{}:{}: {}", path.display(), i + 1, msg).into()
+            };
+            let mut tokens = line.split('#').next().unwrap_or("").split_whitespace();
+            let Some(keyword) = tokens.next() else {
+                continue;
+            };
+            let args = tokens.collect::<Vec<_>>();
+            match keyword {
+                "v" => {
+                    let v = parse_floats(&args).map_err(err)?;
+                    let color = match v.len() {
+                        3 | 4 => None,
+                        6 => Some(Color::new(v[3], v[4], v[5], 1.0)),
+                        n => return Err(err(format!("Expected 3 or 6 values, found {}.", n))),
+                    };
+                    positions.push(Vec3::new(v[0], v[1], -v[2]));
+                    colors.push(color);
+                }
+                "vt" => {
+                    let v = parse_floats(&args).map_err(err)?;
+                    if !(1..=3).contains(&v.len()) {
+                        return Err(err(format!("Expected 1 to 3 values, found {}.", v.len())));
+                    }
+                    //OBJ texture coordinates start at the bottom left corner.
+                    uvs.push([v[0], 1.0 - v.get(1).unwrap_or(&0.0)]);
+                }
+                "vn" => {
+                    let v = parse_floats(&args).map_err(err)?;
+                    if v.len() != 3 {
+                        return Err(err(format!("Expected 3 values, found {}.", v.len())));
+                    }
+                    normals.push(Vec3::new(v[0], v[1], -v[2]));
+                }
+                "f" => {
+                    if args.len() < 3 {
+                        return Err(err("Face needs at least 3 vertices.".to_string()));
+                    }
+                    let face = args
+                        .iter()
+                        .map(|x| parse_face_vertex(x, positions.len(), uvs.len(), normals.len()))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(err)?;
+                    let tint = material.map_or(Color::WHITE, |x: usize| materials[x].1);
+                    let face = face
+                        .into_iter()
+                        .map(|(v, vt, vn)| {
+                            *indices.entry((v, vt, vn, material)).or_insert_with(|| {
+                                let color = multiply(colors[v].unwrap_or(Color::WHITE), tint);
+                                let uv = vt.map(|x| uvs[x]);
+                                let normal = vn.map_or(Vec3::ZERO, |x| normals[x]);
+                                mesh.vertices.push(
+                                    Vertex::from_vec(positions[v], Some(color), uv)
+                                        .with_normal(normal),
+                                );
+                                mesh.vertices.len() as u32 - 1
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    triangulate(&face, &mut mesh.tris);
+                }
+                "mtllib" => {
+                    let mtl_path = base_dir.join(args.join(" "));
+                    if mtl_path.exists() {
+                        materials.extend(parse_mtl(&mtl_path)?);
+                    } else {
+                        log::warn!("Material library {:?} not found.", mtl_path);
+                    }
+                }
+                "usemtl" => {
+                    let name = args.join(" ");
+                    material = materials.iter().rposition(|(x, _)| *x == name);
+                    if material.is_none() {
+                        log::warn!("{}:{}: Unknown material {}.", path.display(), i + 1, name);
+                    }
+                }
+                //Groups, smoothing, lines and points don't affect the triangle mesh.
+                _ => {}
+            }
+        }
+        Ok(mesh)
+    }
+
+    /// Load PLY file in ASCII or binary format. Vertex positions, normals, texture coordinates
+    /// and colors are read from the `vertex` element and polygons from the `face` element.
+    ///
+    /// Positions and normals are mirrored in the Z axis like [`Mesh::from_obj`]. Malformed data
+    /// is reported with its line number, or its byte offset for binary files.
+    ///
+    /// ```
+    /// use std::fs;
+    ///
+    /// use tridify_rs::{Color, Mesh};
+    ///
+    /// let path = std::env::temp_dir().join("tridify_from_ply.ply");
+    /// let header = "\
+    /// ply
+    /// format ascii 1.0
+    /// element vertex 4
+    /// property float x
+    /// property float y
+    /// property float z
+    /// property uchar red
+    /// property uchar green
+    /// property uchar blue
+    /// element face 2
+    /// property list uchar int vertex_indices
+    /// end_header
+    /// ";
+    /// let body = "\
+    /// 0 0 0 255 0 0
+    /// 1 0 0 0 255 0
+    /// 1 1 1 0 0 255
+    /// 0 1 0 255 255 255
+    /// 3 0 1 2
+    /// 4 0 1 2 3
+    /// ";
+    /// fs::write(&path, format!("{header}{body}")).unwrap();
+    /// let mesh = Mesh::from_ply(&path).unwrap();
+    /// assert_eq!(mesh.vertices.len(), 4);
+    /// assert_eq!(mesh.tris, [0, 1, 2, 0, 1, 2, 0, 2, 3]);
+    /// assert_eq!(mesh.vertices[2].pos, [1.0, 1.0, -1.0]);
+    /// assert_eq!(mesh.vertices[0].color, Color::new(1.0, 0.0, 0.0, 1.0));
+    ///
+    /// //Errors point to the line of the element.
+    /// fs::write(&path, format!("{header}{}", body.replace("4 0 1 2 3", "4 0 1 2 7"))).unwrap();
+    /// let err = Mesh::from_ply(&path).unwrap_err().to_string();
+    /// assert!(err.ends_with(":18: Vertex index 7 out of range."), "{}", err);
+    /// fs::write(&path, header.replace("ascii", "text")).unwrap();
+    /// let err = Mesh::from_ply(&path).unwrap_err().to_string();
+    /// assert!(err.ends_with(":2: Unknown format text."), "{}", err);
+    /// ```
+    ///
+    /// Binary files:
+    ///
+    /// ```
+    /// use std::fs;
+    ///
+    /// use tridify_rs::Mesh;
+    ///
+    /// let path = std::env::temp_dir().join("tridify_from_ply_binary.ply");
+    /// let header = "\
+    /// ply
+    /// format binary_little_endian 1.0
+    /// element vertex 3
+    /// property float x
+    /// property float y
+    /// property float z
+    /// element face 1
+    /// property list uchar uint vertex_indices
+    /// end_header
+    /// ";
+    /// let mut data = header.as_bytes().to_vec();
+    /// for x in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 2.0] {
+    ///     data.extend(x.to_le_bytes());
+    /// }
+    /// data.push(3);
+    /// for x in [0u32, 2, 1] {
+    ///     data.extend(x.to_le_bytes());
+    /// }
+    /// fs::write(&path, &data).unwrap();
+    /// let mesh = Mesh::from_ply(&path).unwrap();
+    /// assert_eq!(mesh.tris, [0, 2, 1]);
+    /// assert_eq!(mesh.vertices[2].pos, [0.0, 1.0, -2.0]);
+    ///
+    /// //Errors point to the byte offset, here the missing last index.
+    /// fs::write(&path, &data[..data.len() - 4]).unwrap();
+    /// let err = Mesh::from_ply(&path).unwrap_err().to_string();
+    /// let offset = header.len() + 9 * 4 + 1 + 2 * 4;
+    /// assert!(err.ends_with(&format!(":byte {offset}: Unexpected end of file.")), "{}", err);
+    /// ```
+    pub fn from_ply(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let data = fs::read(path)?;
+        let err = |location: String, msg: String| -> Box<dyn Error> {
+            format!("{}:{}: {}", path.display(), location, msg).into()
+        };
+        let header = PlyHeader::parse(&data).map_err(|(line, msg)| err(line.to_string(), msg))?;
+        let vertex_count = header
+            .elements
+            .iter()
+            .find(|x| x.name == "vertex")
+            .map_or(0, |x| x.count);
+        let mut reader = PlyReader::new(&header, &data)
+            .map_err(|msg| err(header.line_count.to_string(), msg))?;
+
+        let mut mesh = Mesh::default();
+        let mut face = Vec::new();
+        for element in header.elements.iter() {
+            for _ in 0..element.count {
+                reader
+                    .begin_element()
+                    .map_err(|msg| err(reader.location(), msg))?;
+                let mut vertex = Vertex::new(0.0, 0.0, 0.0, None, None);
+                face.clear();
+                for property in element.properties.iter() {
+                    match property {
+                        PlyProperty::Scalar { name, ty } => {
+                            let value = reader
+                                .read(*ty)
+                                .map_err(|msg| err(reader.location(), msg))?;
+                            let value = value as f32;
+                            match name.as_str() {
+                                "x" => vertex.pos[0] = value,
+                                "y" => vertex.pos[1] = value,
+                                "z" => vertex.pos[2] = -value,
+                                "nx" => vertex.normal[0] = value,
+                                "ny" => vertex.normal[1] = value,
+                                "nz" => vertex.normal[2] = -value,
+                                "s" | "u" | "texture_s" | "texture_u" => vertex.uv[0] = value,
+                                "t" | "v" | "texture_t" | "texture_v" => vertex.uv[1] = 1.0 - value,
+                                "red" => vertex.color.r = value / ty.color_max(),
+                                "green" => vertex.color.g = value / ty.color_max(),
+                                "blue" => vertex.color.b = value / ty.color_max(),
+                                "alpha" => vertex.color.a = value / ty.color_max(),
+                                _ => {}
+                            }
+                        }
+                        PlyProperty::List {
+                            name,
+                            count_ty,
+                            item_ty,
+                        } => {
+                            let count = reader
+                                .read(*count_ty)
+                                .map_err(|msg| err(reader.location(), msg))?;
+                            let is_face = element.name == "face"
+                                && (name == "vertex_indices" || name == "vertex_index");
+                            for _ in 0..count as usize {
+                                let value = reader
+                                    .read(*item_ty)
+                                    .map_err(|msg| err(reader.location(), msg))?;
+                                if !is_face {
+                                    continue;
+                                }
+                                if value < 0.0 || value as usize >= vertex_count {
+                                    return Err(err(
+                                        reader.location(),
+                                        format!("Vertex index {} out of range.", value),
+                                    ));
+                                }
+                                face.push(value as u32);
+                            }
+                        }
+                    }
+                }
+                reader
+                    .end_element()
+                    .map_err(|msg| err(reader.location(), msg))?;
+                match element.name.as_str() {
+                    "vertex" => mesh.vertices.push(vertex),
+                    "face" if face.len() < 3 => {
+                        return Err(err(
+                            reader.location(),
+                            "Face needs at least 3 vertices.".to_string(),
+                        ))
+                    }
+                    "face" => triangulate(&face, &mut mesh.tris),
+                    _ => {}
+                }
+            }
+        }
+        Ok(mesh)
+    }
+}
+
+/// Split a convex polygon into a triangle fan.
+fn triangulate(polygon: &[u32], tris: &mut Vec<u32>) {
+    for i in 1..polygon.len() - 1 {
+        tris.extend([polygon[0], polygon[i], polygon[i + 1]]);
+    }
+}
+
+fn multiply(a: Color, b: Color) -> Color { Color::new(a.r * b.r, a.g * b.g, a.b * b.b, a.a * b.a) }
+
+fn parse_floats(args: &[&str]) -> Result<Vec<f32>, String> {
+    args.iter()
+        .map(|x| x.parse().map_err(|_| format!("Invalid number {}.", x)))
+        .collect()
+}
+
+/// Parse `v`, `v/vt`, `v//vn` or `v/vt/vn` into 0 based indices. Negative indices are relative
+/// to the end of the lists read so far.
+fn parse_face_vertex(
+    token: &str, positions: usize, uvs: usize, normals: usize,
+) -> Result<(usize, Option<usize>, Option<usize>), String> {
+    let resolve = |index: &str, len: usize| -> Result<usize, String> {
+        let value: i64 = index
+            .parse()
+            .map_err(|_| format!("Invalid face index {}.", index))?;
+        let resolved = if value < 0 {
+            len as i64 + value
+        } else {
+            value - 1
+        };
+        if value == 0 || resolved < 0 || resolved >= len as i64 {
+            return Err(format!("Face index {} out of range.", value));
+        }
+        Ok(resolved as usize)
+    };
+    let optional = |index: Option<&str>, len: usize| match index {
+        None | Some("") => Ok(None),
+        Some(index) => resolve(index, len).map(Some),
+    };
+    let mut parts = token.split('/');
+    let v = resolve(parts.next().unwrap_or(""), positions)?;
+    let vt = optional(parts.next(), uvs)?;
+    let vn = optional(parts.next(), normals)?;
+    if parts.next().is_some() {
+        return Err(format!("Invalid face vertex {}.", token));
+    }
+    Ok((v, vt, vn))
+}
+
+/// Read the diffuse color and opacity of all materials in a MTL file.
+fn parse_mtl(path: &Path) -> Result<Vec<(String, Color)>, Box<dyn Error>> {
+    let source = fs::read_to_string(path)?;
+    let mut materials: Vec<(String, Color)> = Vec::new();
+    for (i, line) in source.lines().enumerate() {
+        let err = |msg: String| -> Box<dyn Error> {
+            format!("{}:{}: {}", path.display(), i + 1, msg).into()
+        };
+        let mut tokens = line.split('#').next().unwrap_or("").split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+        let args = tokens.collect::<Vec<_>>();
+        if keyword == "newmtl" {
+            materials.push((args.join(" "), Color::WHITE));
+            continue;
+        }
+        if !matches!(keyword, "Kd" | "d" | "Tr") {
+            continue;
+        }
+        let Some((_, color)) = materials.last_mut() else {
+            return Err(err(format!("{} before newmtl.", keyword)));
+        };
+        let v = parse_floats(&args).map_err(err)?;
+        match (keyword, v.as_slice()) {
+            ("Kd", [r, g, b]) => (color.r, color.g, color.b) = (*r, *g, *b),
+            ("d", [d]) => color.a = *d,
+            ("Tr", [tr]) => color.a = 1.0 - tr,
+            _ => return Err(err(format!("Invalid {} values.", keyword))),
+        }
+    }
+    Ok(materials)
+}
+
+#[derive(Debug, Clone, Copy)]
+enum PlyType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+impl PlyType {
+    fn parse(name: &str) -> Result<Self, String> {
+        Ok(match name {
+            "char" | "int8" => Self::I8,
+            "uchar" | "uint8" => Self::U8,
+            "short" | "int16" => Self::I16,
+            "ushort" | "uint16" => Self::U16,
+            "int" | "int32" => Self::I32,
+            "uint" | "uint32" => Self::U32,
+            "float" | "float32" => Self::F32,
+            "double" | "float64" => Self::F64,
+            _ => return Err(format!("Unknown property type {}.", name)),
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Self::I8 | Self::U8 => 1,
+            Self::I16 | Self::U16 => 2,
+            Self::I32 | Self::U32 | Self::F32 => 4,
+            Self::F64 => 8,
+        }
+    }
+
+    /// Value of a fully saturated color channel.
+    fn color_max(self) -> f32 {
+        match self {
+            Self::U8 => u8::MAX as f32,
+            Self::U16 => u16::MAX as f32,
+            Self::F32 | Self::F64 => 1.0,
+            Self::I8 => i8::MAX as f32,
+            Self::I16 => i16::MAX as f32,
+            Self::I32 => i32::MAX as f32,
+            Self::U32 => u32::MAX as f32,
+        }
+    }
+
+    fn decode(self, bytes: &[u8], big_endian: bool) -> f64 {
+        macro_rules! read {
+            ($ty:ty) => {{
+                let bytes = bytes.try_into().unwrap();
+                match big_endian {
+                    true => <$ty>::from_be_bytes(bytes) as f64,
+                    false => <$ty>::from_le_bytes(bytes) as f64,
+                }
+            }};
+        }
+        match self {
+            Self::I8 => read!(i8),
+            Self::U8 => read!(u8),
+            Self::I16 => read!(i16),
+            Self::U16 => read!(u16),
+            Self::I32 => read!(i32),
+            Self::U32 => read!(u32),
+            Self::F32 => read!(f32),
+            Self::F64 => read!(f64),
+        }
+    }
+}
+
+enum PlyProperty {
+    Scalar {
+        name: String,
+        ty: PlyType,
+    },
+    List {
+        name: String,
+        count_ty: PlyType,
+        item_ty: PlyType,
+    },
+}
+
+struct PlyElement {
+    name: String,
+    count: usize,
+    properties: Vec<PlyProperty>,
+}
+
+#[derive(PartialEq, Eq)]
+enum PlyFormat {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+struct PlyHeader {
+    format: PlyFormat,
+    elements: Vec<PlyElement>,
+    /// Byte offset where the element data starts.
+    body_start: usize,
+    line_count: usize,
+}
+
+impl PlyHeader {
+    /// Parse header lines up to `end_header`. Errors contain the line number.
+    fn parse(data: &[u8]) -> Result<Self, (usize, String)> {
+        let mut format = None;
+        let mut elements: Vec<PlyElement> = Vec::new();
+        let mut offset = 0;
+        let mut line_number = 0;
+        loop {
+            line_number += 1;
+            let end = data[offset..]
+                .iter()
+                .position(|x| *x == b'\n')
+                .ok_or((line_number, "Missing end_header.".to_string()))?;
+            let line = String::from_utf8_lossy(&data[offset..offset + end]);
+            offset += end + 1;
+            let err = |msg: String| (line_number, msg);
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+            match tokens.as_slice() {
+                ["ply"] if line_number == 1 => {}
+                _ if line_number == 1 => return Err(err("Not a PLY file.".to_string())),
+                ["format", name, _] => {
+                    format = Some(match *name {
+                        "ascii" => PlyFormat::Ascii,
+                        "binary_little_endian" => PlyFormat::BinaryLittleEndian,
+                        "binary_big_endian" => PlyFormat::BinaryBigEndian,
+                        _ => return Err(err(format!("Unknown format {}.", name))),
+                    })
+                }
+                ["element", name, count] => elements.push(PlyElement {
+                    name: name.to_string(),
+                    count: count
+                        .parse()
+                        .map_err(|_| err(format!("Invalid element count {}.", count)))?,
+                    properties: Vec::new(),
+                }),
+                ["property", "list", count_ty, item_ty, name] => elements
+                    .last_mut()
+                    .ok_or(err("Property before element.".to_string()))?
+                    .properties
+                    .push(PlyProperty::List {
+                        name: name.to_string(),
+                        count_ty: PlyType::parse(count_ty).map_err(err)?,
+                        item_ty: PlyType::parse(item_ty).map_err(err)?,
+                    }),
+                ["property", ty, name] => elements
+                    .last_mut()
+                    .ok_or(err("Property before element.".to_string()))?
+                    .properties
+                    .push(PlyProperty::Scalar {
+                        name: name.to_string(),
+                        ty: PlyType::parse(ty).map_err(err)?,
+                    }),
+                ["end_header"] => break,
+                ["comment", ..] | ["obj_info", ..] | [] => {}
+                _ => return Err(err(format!("Invalid header line {}.", line.trim()))),
+            }
+        }
+        Ok(Self {
+            format: format.ok_or((line_number, "Missing format line.".to_string()))?,
+            elements,
+            body_start: offset,
+            line_count: line_number,
+        })
+    }
+}
+
+/// Reads element values from the data after the header, one line per element in ASCII files.
+struct PlyReader<'a> {
+    header: &'a PlyHeader,
+    data: &'a [u8],
+    offset: usize,
+    lines: std::iter::Enumerate<std::str::Lines<'a>>,
+    line_number: usize,
+    tokens: std::str::SplitWhitespace<'a>,
+}
+
+impl<'a> PlyReader<'a> {
+    fn new(header: &'a PlyHeader, data: &'a [u8]) -> Result<Self, String> {
+        let body = &data[header.body_start..];
+        let text = match header.format {
+            PlyFormat::Ascii => std::str::from_utf8(body).map_err(|e| e.to_string())?,
+            _ => "",
+        };
+        Ok(Self {
+            header,
+            data: body,
+            offset: 0,
+            lines: text.lines().enumerate(),
+            line_number: header.line_count,
+            tokens: "".split_whitespace(),
+        })
+    }
+
+    fn location(&self) -> String {
+        match self.header.format {
+            PlyFormat::Ascii => self.line_number.to_string(),
+            _ => format!("byte {}", self.header.body_start + self.offset),
+        }
+    }
+
+    fn begin_element(&mut self) -> Result<(), String> {
+        if self.header.format != PlyFormat::Ascii {
+            return Ok(());
+        }
+        for (i, line) in self.lines.by_ref() {
+            self.line_number = self.header.line_count + i + 1;
+            if !line.trim().is_empty() {
+                self.tokens = line.split_whitespace();
+                return Ok(());
+            }
+        }
+        Err("Unexpected end of file.".to_string())
+    }
+
+    fn end_element(&mut self) -> Result<(), String> {
+        match self.tokens.next() {
+            Some(token) => Err(format!("Unexpected value {}.", token)),
+            None => Ok(()),
+        }
+    }
+
+    fn read(&mut self, ty: PlyType) -> Result<f64, String> {
+        if self.header.format == PlyFormat::Ascii {
+            let token = self.tokens.next().ok_or("Missing values.")?;
+            return token
+                .parse()
+                .map_err(|_| format!("Invalid number {}.", token));
+        }
+        let bytes = self
+            .data
+            .get(self.offset..self.offset + ty.size())
+            .ok_or("Unexpected end of file.")?;
+        self.offset += ty.size();
+        Ok(ty.decode(bytes, self.header.format == PlyFormat::BinaryBigEndian))
+    }
+}
@@ -0,0 +1,503 @@
+use std::collections::HashMap;
+
+use bevy_mikktspace::Geometry;
+use glam::{IVec3, Mat3, Vec3};
+
+use crate::{Aabb, BoundingSphere, Mesh, Ray, RayHit, Transform, Vertex};
+
+/// Vertex cache size simulated by [`Mesh::optimize_vertex_cache`].
+const CACHE_SIZE: usize = 32;
+
+impl Mesh {
+    /// Append the vertices and triangles of another mesh.
+    ///
+    /// ```
+    /// use tridify_rs::{Mesh, Vertex};
+    ///
+    /// let triangle = |x| {
+    ///     let v = |x, y| Vertex::new(x, y, 0.0, None, None);
+    ///     Mesh::new(vec![v(x, 0.0), v(x, 1.0), v(x + 1.0, 0.0)], vec![0, 1, 2])
+    /// };
+    /// let mut mesh = triangle(0.0);
+    /// mesh.merge(&triangle(2.0));
+    /// assert_eq!(mesh.vertices.len(), 6);
+    /// assert_eq!(mesh.tris, vec![0, 1, 2, 3, 4, 5]);
+    /// assert_eq!(mesh.vertices[3].pos, [2.0, 0.0, 0.0]);
+    /// ```
+    pub fn merge(&mut self, other: &Mesh) {
+        let offset = self.vertices.len() as u32;
+        self.vertices.extend(&other.vertices);
+        self.tris.extend(other.tris.iter().map(|x| x + offset));
+    }
+
+    /// Set each vertex normal to the area weighted average of the triangles using it. Vertices
+    /// are not shared across UV seams or hard edges, use [`Mesh::weld`] first to smooth them.
+    ///
+    /// ```
+    /// use glam::Vec3;
+    /// use tridify_rs::{Mesh, Vertex};
+    ///
+    /// //Two triangles folded at a right angle along the X axis, facing +Y and +Z.
+    /// let v = |x, y, z| Vertex::new(x, y, z, None, None);
+    /// let mut mesh = Mesh::new(
+    ///     vec![v(0.0, 0.0, 0.0), v(1.0, 0.0, 0.0), v(0.0, 0.0, 1.0), v(0.0, 1.0, 0.0)],
+    ///     vec![0, 1, 2, 0, 3, 1],
+    /// );
+    /// mesh.compute_smooth_normals();
+    /// let normals = mesh.vertices.iter().map(|x| Vec3::from(x.normal)).collect::<Vec<_>>();
+    /// assert!(normals[0].abs_diff_eq(Vec3::new(0.0, 1.0, 1.0).normalize(), 1e-6));
+    /// assert!(normals[1].abs_diff_eq(Vec3::new(0.0, 1.0, 1.0).normalize(), 1e-6));
+    /// assert_eq!(normals[2], Vec3::Y);
+    /// assert_eq!(normals[3], Vec3::Z);
+    /// ```
+    pub fn compute_smooth_normals(&mut self) {
+        let mut normals = vec![Vec3::ZERO; self.vertices.len()];
+        for tri in self.tris.chunks_exact(3) {
+            let normal = face_normal(self.triangle(tri));
+            for i in tri {
+                normals[*i as usize] += normal;
+            }
+        }
+        for (vertex, normal) in self.vertices.iter_mut().zip(normals) {
+            vertex.normal = normal.normalize_or_zero().to_array();
+        }
+    }
+
+    /// Give each triangle its own vertices with the triangle normal, for a faceted look.
+    ///
+    /// ```
+    /// use glam::Vec3;
+    /// use tridify_rs::{Mesh, Vertex};
+    ///
+    /// let v = |x, y, z| Vertex::new(x, y, z, None, None);
+    /// let mut mesh = Mesh::new(
+    ///     vec![v(0.0, 0.0, 0.0), v(1.0, 0.0, 0.0), v(0.0, 0.0, 1.0), v(0.0, 1.0, 0.0)],
+    ///     vec![0, 1, 2, 0, 3, 1],
+    /// );
+    /// mesh.compute_flat_normals();
+    /// assert_eq!(mesh.tris, vec![0, 1, 2, 3, 4, 5]);
+    /// assert!(mesh.vertices[..3].iter().all(|x| x.normal == Vec3::Y.to_array()));
+    /// assert!(mesh.vertices[3..].iter().all(|x| x.normal == Vec3::Z.to_array()));
+    /// assert_eq!(mesh.vertices[4].pos, [0.0, 1.0, 0.0]);
+    /// ```
+    pub fn compute_flat_normals(&mut self) {
+        let mut vertices = Vec::with_capacity(self.tris.len());
+        for tri in self.tris.chunks_exact(3) {
+            let normal = face_normal(self.triangle(tri)).normalize_or_zero();
+            vertices.extend(
+                tri.iter()
+                    .map(|x| self.vertices[*x as usize].with_normal(normal)),
+            );
+        }
+        self.tris = (0..vertices.len() as u32).collect();
+        self.vertices = vertices;
+    }
+
+    /// Generate a tangent for each vertex from its normal and UV with MikkTSpace, the algorithm
+    /// used by glTF and most baking tools. `w` stores the bitangent sign, so the bitangent is
+    /// `cross(normal, tangent.xyz) * tangent.w`. Normals must be set first.
+    ///
+    /// Tangents are returned instead of stored in [`Vertex`] so meshes without normal maps keep
+    /// smaller vertices. Returns `None` if the mesh has no valid triangles.
+    ///
+    /// ```
+    /// use glam::Vec3;
+    /// use tridify_rs::{Mesh, Vertex};
+    ///
+    /// //Quad facing +Z with U along +X and V along +Y.
+    /// let v = |x: f32, y: f32| Vertex::new(x, y, 0.0, None, Some([x, y])).with_normal(Vec3::Z);
+    /// let mesh = Mesh::new(
+    ///     vec![v(0.0, 0.0), v(1.0, 0.0), v(0.0, 1.0), v(1.0, 1.0)],
+    ///     vec![0, 2, 1, 1, 2, 3],
+    /// );
+    /// let tangents = mesh.generate_tangents().unwrap();
+    /// assert_eq!(tangents.len(), 4);
+    /// for tangent in tangents {
+    ///     assert!(Vec3::from_slice(&tangent).abs_diff_eq(Vec3::X, 1e-5));
+    ///     assert_eq!(tangent[3], 1.0);
+    /// }
+    /// ```
+    pub fn generate_tangents(&self) -> Option<Vec<[f32; 4]>> {
+        let mut geometry = TangentGeometry {
+            mesh: self,
+            tangents: vec![[0.0; 4]; self.vertices.len()],
+        };
+        bevy_mikktspace::generate_tangents(&mut geometry).then_some(geometry.tangents)
+    }
+
+    /// Merge vertices whose attributes are all within `epsilon`, removing triangles that become
+    /// degenerate.
+    ///
+    /// ```
+    /// use tridify_rs::{Mesh, Vertex};
+    ///
+    /// let v = |x, y| Vertex::new(x, y, 0.0, None, None);
+    /// let mut mesh = Mesh::new(
+    ///     vec![v(0., 0.), v(1., 0.), v(0., 1.), v(0., 1.), v(1., 0.0001), v(1., 1.)],
+    ///     vec![0, 1, 2, 3, 4, 5],
+    /// );
+    /// mesh.weld(0.001);
+    /// assert_eq!(mesh.vertices.len(), 4);
+    /// assert_eq!(mesh.tris, vec![0, 1, 2, 2, 1, 3]);
+    /// ```
+    pub fn weld(&mut self, epsilon: f32) {
+        //Close vertices are always in the same or adjacent cells.
+        let cell_size = if epsilon > 0.0 { epsilon } else { 1.0 };
+        let cell = |vertex: &Vertex| (Vec3::from(vertex.pos) / cell_size).floor().as_ivec3();
+        let mut grid: HashMap<IVec3, Vec<u32>> = HashMap::new();
+        let mut vertices: Vec<Vertex> = Vec::new();
+        let mut remap = Vec::with_capacity(self.vertices.len());
+        for vertex in self.vertices.iter() {
+            let key = cell(vertex);
+            let found = (-1..=1)
+                .flat_map(|x| {
+                    (-1..=1).flat_map(move |y| (-1..=1).map(move |z| IVec3::new(x, y, z)))
+                })
+                .filter_map(|offset| grid.get(&(key + offset)))
+                .flatten()
+                .find(|x| is_close(&vertices[**x as usize], vertex, epsilon))
+                .copied();
+            let index = found.unwrap_or_else(|| {
+                vertices.push(*vertex);
+                let index = vertices.len() as u32 - 1;
+                grid.entry(key).or_default().push(index);
+                index
+            });
+            remap.push(index);
+        }
+        self.tris = self
+            .tris
+            .chunks_exact(3)
+            .map(|tri| [tri[0], tri[1], tri[2]].map(|x| remap[x as usize]))
+            .filter(|[a, b, c]| a != b && b != c && c != a)
+            .flatten()
+            .collect();
+        self.vertices = vertices;
+    }
+
+    /// Reorder triangles to reuse recently transformed vertices using Tom Forsyth's linear-speed
+    /// vertex cache optimization, then reorder vertices by first use. Vertices not used by any
+    /// triangle are removed.
+    ///
+    /// ```
+    /// use tridify_rs::{Mesh, Vertex};
+    ///
+    /// //4x4 grid of vertices with an extra unused vertex.
+    /// let mut vertices = Vec::new();
+    /// let mut tris = Vec::new();
+    /// for i in 0..16 {
+    ///     vertices.push(Vertex::new((i % 4) as f32, (i / 4) as f32, 0.0, None, None));
+    ///     if i % 4 < 3 && i < 12 {
+    ///         tris.extend([i, i + 1, i + 4, i + 1, i + 5, i + 4]);
+    ///     }
+    /// }
+    /// vertices.push(Vertex::new(9.0, 9.0, 0.0, None, None));
+    /// let mut mesh = Mesh::new(vertices, tris);
+    ///
+    /// //Triangles as corner positions, which are kept in the same order.
+    /// let triangles = |mesh: &Mesh| {
+    ///     let corner = |i: &u32| mesh.vertices[*i as usize].pos.map(|x| x as i32);
+    ///     let mut triangles = mesh.tris.chunks(3).map(|x| x.iter().map(corner).collect::<Vec<_>>()).collect::<Vec<_>>();
+    ///     triangles.sort();
+    ///     triangles
+    /// };
+    /// let before = triangles(&mesh);
+    /// mesh.optimize_vertex_cache();
+    /// assert_eq!(mesh.vertices.len(), 16);
+    /// assert_eq!(triangles(&mesh), before);
+    /// ```
+    pub fn optimize_vertex_cache(&mut self) {
+        let tri_count = self.tris.len() / 3;
+        let mut vertex_tris = vec![Vec::new(); self.vertices.len()];
+        for (tri, corners) in self.tris.chunks_exact(3).enumerate() {
+            for i in corners {
+                vertex_tris[*i as usize].push(tri);
+            }
+        }
+        let mut vertex_score = vertex_tris
+            .iter()
+            .map(|x| cache_score(None, x.len()))
+            .collect::<Vec<_>>();
+        let tri_score = |tri: usize, vertex_score: &[f32]| -> f32 {
+            self.tris[tri * 3..tri * 3 + 3]
+                .iter()
+                .map(|x| vertex_score[*x as usize])
+                .sum()
+        };
+        let mut added = vec![false; tri_count];
+        let mut cache: Vec<u32> = Vec::with_capacity(CACHE_SIZE + 3);
+        let mut output = Vec::with_capacity(tri_count * 3);
+        let mut best = (0..tri_count)
+            .max_by(|a, b| tri_score(*a, &vertex_score).total_cmp(&tri_score(*b, &vertex_score)));
+        let mut next_unadded = 0;
+        while output.len() < tri_count * 3 {
+            //When no cached vertex has triangles left, continue from any other triangle.
+            let tri = best.unwrap_or_else(|| {
+                while added[next_unadded] {
+                    next_unadded += 1;
+                }
+                next_unadded
+            });
+            added[tri] = true;
+            let corners = [0, 1, 2].map(|x| self.tris[tri * 3 + x]);
+            output.extend(corners);
+            for i in corners {
+                vertex_tris[i as usize].retain(|x| *x != tri);
+            }
+
+            let previous = std::mem::replace(&mut cache, corners.to_vec());
+            cache.extend(previous.iter().filter(|x| !corners.contains(x)));
+            let evicted = cache.split_off(cache.len().min(CACHE_SIZE));
+            for (position, i) in cache.iter().enumerate() {
+                let i = *i as usize;
+                vertex_score[i] = cache_score(Some(position), vertex_tris[i].len());
+            }
+            for i in evicted.iter() {
+                vertex_score[*i as usize] = cache_score(None, vertex_tris[*i as usize].len());
+            }
+            best = cache
+                .iter()
+                .flat_map(|x| vertex_tris[*x as usize].iter().copied())
+                .max_by(|a, b| {
+                    tri_score(*a, &vertex_score).total_cmp(&tri_score(*b, &vertex_score))
+                });
+        }
+
+        let mut remap = vec![u32::MAX; self.vertices.len()];
+        let mut vertices = Vec::with_capacity(self.vertices.len());
+        for i in output.iter_mut() {
+            if remap[*i as usize] == u32::MAX {
+                remap[*i as usize] = vertices.len() as u32;
+                vertices.push(self.vertices[*i as usize]);
+            }
+            *i = remap[*i as usize];
+        }
+        self.vertices = vertices;
+        self.tris = output;
+    }
+
+    /// Turn the mesh inside out: reverse the triangle winding so back faces become front faces
+    /// and flip normals to match.
+    ///
+    /// ```
+    /// use glam::Vec3;
+    /// use tridify_rs::{Mesh, Vertex};
+    ///
+    /// let v = |x, z| Vertex::new(x, 0.0, z, None, None).with_normal(Vec3::Y);
+    /// let mut mesh = Mesh::new(vec![v(0.0, 0.0), v(1.0, 0.0), v(0.0, 1.0)], vec![0, 1, 2]);
+    /// mesh.flip_winding();
+    /// assert_eq!(mesh.tris, vec![0, 2, 1]);
+    /// assert!(mesh.vertices.iter().all(|x| x.normal == [0.0, -1.0, 0.0]));
+    /// ```
+    pub fn flip_winding(&mut self) {
+        self.reverse_triangles();
+        for vertex in self.vertices.iter_mut() {
+            vertex.normal = (-Vec3::from(vertex.normal)).to_array();
+        }
+    }
+
+    /// Move vertices and normals by the transform. Mirroring transforms also reverse the triangle
+    /// winding so the same faces stay visible.
+    ///
+    /// ```
+    /// use glam::{Quat, Vec3};
+    /// use tridify_rs::{Mesh, Transform, Vertex};
+    ///
+    /// //Slanted triangle, normals stay perpendicular to it under non-uniform scale.
+    /// let normal = Vec3::new(1.0, 1.0, 0.0).normalize();
+    /// let v = |x, y, z| Vertex::new(x, y, z, None, None).with_normal(normal);
+    /// let mut mesh = Mesh::new(
+    ///     vec![v(1.0, 0.0, 0.0), v(0.0, 1.0, 0.0), v(0.0, 1.0, 1.0)],
+    ///     vec![0, 1, 2],
+    /// );
+    /// mesh.apply_transform(&Transform::new(Vec3::Z, Quat::IDENTITY, Vec3::new(2.0, 1.0, 1.0)));
+    /// assert_eq!(mesh.vertices[0].pos, [2.0, 0.0, 1.0]);
+    /// let edge = Vec3::from(mesh.vertices[1].pos) - Vec3::from(mesh.vertices[0].pos);
+    /// let normal = Vec3::from(mesh.vertices[0].normal);
+    /// assert!(normal.abs_diff_eq(Vec3::new(0.5, 1.0, 0.0).normalize(), 1e-6));
+    /// assert!(normal.dot(edge).abs() < 1e-6);
+    /// assert_eq!(mesh.tris, vec![0, 1, 2]);
+    ///
+    /// //Mirroring keeps the same side of the triangles visible.
+    /// mesh.apply_transform(&Transform::new(Vec3::ZERO, Quat::IDENTITY, Vec3::new(-1.0, 1.0, 1.0)));
+    /// assert_eq!(mesh.tris, vec![0, 2, 1]);
+    /// ```
+    pub fn apply_transform(&mut self, transform: &Transform) {
+        let matrix = transform.build_matrix();
+        let linear = Mat3::from_mat4(matrix);
+        let normal_matrix = linear.inverse().transpose();
+        let mirrored = linear.determinant() < 0.0;
+        for vertex in self.vertices.iter_mut() {
+            vertex.pos = matrix.transform_point3(vertex.pos.into()).to_array();
+            vertex.normal = (normal_matrix * Vec3::from(vertex.normal))
+                .normalize_or_zero()
+                .to_array();
+        }
+        if mirrored {
+            self.reverse_triangles();
+        }
+    }
+
+    /// Box containing all vertices, or `None` if the mesh is empty.
+    ///
+    /// ```
+    /// use glam::Vec3;
+    /// use tridify_rs::{Mesh, Vertex};
+    ///
+    /// let mesh = Mesh::new(
+    ///     vec![Vertex::new(-1.0, 0.0, 2.0, None, None), Vertex::new(3.0, 1.0, 0.0, None, None)],
+    ///     Vec::new(),
+    /// );
+    /// let aabb = mesh.aabb().unwrap();
+    /// assert_eq!(aabb.min, Vec3::new(-1.0, 0.0, 0.0));
+    /// assert_eq!(aabb.max, Vec3::new(3.0, 1.0, 2.0));
+    /// ```
+    pub fn aabb(&self) -> Option<Aabb> {
+        Aabb::from_points(self.vertices.iter().map(|x| Vec3::from(x.pos)))
+    }
+
+    /// Sphere containing all vertices centered on the [`Mesh::aabb`] center, or `None` if the
+    /// mesh is empty.
+    ///
+    /// ```
+    /// use glam::Vec3;
+    /// use tridify_rs::{Mesh, Vertex};
+    ///
+    /// let v = |x, y| Vertex::new(x, y, 0.0, None, None);
+    /// let mesh = Mesh::new(vec![v(-1.0, 0.0), v(3.0, 0.0), v(1.0, 2.0)], Vec::new());
+    /// let sphere = mesh.bounding_sphere().unwrap();
+    /// assert_eq!(sphere.center, Vec3::new(1.0, 1.0, 0.0));
+    /// assert_eq!(sphere.radius, 5f32.sqrt());
+    /// assert!(Mesh::default().bounding_sphere().is_none());
+    /// ```
+    pub fn bounding_sphere(&self) -> Option<BoundingSphere> {
+        let center = self.aabb()?.center();
+        let radius = self
+            .vertices
+            .iter()
+            .map(|x| Vec3::from(x.pos).distance_squared(center))
+            .fold(0.0, f32::max)
+            .sqrt();
+        Some(BoundingSphere::new(center, radius))
+    }
+
+    /// Unit direction the front of the triangle at `index` faces, the side not culled by brushes.
+    ///
+    /// ```
+    /// use glam::Vec3;
+    /// use tridify_rs::{Mesh, Vertex};
+    ///
+    /// let v = |x, y| Vertex::new(x, y, 0.0, None, None);
+    /// let mesh = Mesh::new(vec![v(0.0, 0.0), v(2.0, 0.0), v(0.0, 2.0)], vec![0, 1, 2, 0, 2, 1]);
+    /// //Counter clockwise on screen for a camera looking towards +Z.
+    /// assert_eq!(mesh.face_normal(0), -Vec3::Z);
+    /// assert_eq!(mesh.face_normal(1), Vec3::Z);
+    /// ```
+    pub fn face_normal(&self, index: usize) -> Vec3 {
+        face_normal(self.triangle(&self.tris[index * 3..index * 3 + 3])).normalize_or_zero()
+    }
+
+    /// Closest triangle hit by the ray, in the same space as the vertices. Use
+    /// [`crate::Ray::transformed`] with the inverse model matrix to test a placed mesh.
+    ///
+    /// ```
+    /// use glam::Vec3;
+    /// use tridify_rs::{Mesh, Ray, Vertex};
+    ///
+    /// //Two overlapping triangles, the first one further from the ray origin.
+    /// let v = |x, y, z| Vertex::new(x, y, z, None, None);
+    /// let mesh = Mesh::new(
+    ///     vec![v(-1.0, -1.0, 5.0), v(1.0, -1.0, 5.0), v(0.0, 1.0, 5.0)]
+    ///         .into_iter()
+    ///         .chain([v(-1.0, -1.0, 2.0), v(1.0, -1.0, 2.0), v(0.0, 1.0, 2.0)])
+    ///         .collect(),
+    ///     vec![0, 1, 2, 3, 4, 5],
+    /// );
+    /// let hit = mesh.raycast(&Ray::new(Vec3::ZERO, Vec3::Z)).unwrap();
+    /// assert_eq!((hit.triangle, hit.distance), (1, 2.0));
+    /// assert_eq!(hit.point, Vec3::new(0.0, 0.0, 2.0));
+    /// //From between them, only the one in front is hit.
+    /// let hit = mesh.raycast(&Ray::new(Vec3::new(0.0, 0.0, 3.0), Vec3::Z)).unwrap();
+    /// assert_eq!((hit.triangle, hit.distance), (0, 2.0));
+    /// assert_eq!(mesh.raycast(&Ray::new(Vec3::new(5.0, 0.0, 0.0), Vec3::Z)), None);
+    /// ```
+    pub fn raycast(&self, ray: &Ray) -> Option<RayHit> {
+        self.tris
+            .chunks_exact(3)
+            .enumerate()
+            .filter_map(|(i, tri)| {
+                let distance = ray.intersect_triangle(self.triangle(tri))?;
+                Some(RayHit {
+                    distance,
+                    point: ray.at(distance),
+                    triangle: i,
+                })
+            })
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+
+    fn triangle(&self, tri: &[u32]) -> [Vec3; 3] {
+        [0, 1, 2].map(|x| Vec3::from(self.vertices[tri[x] as usize].pos))
+    }
+
+    fn reverse_triangles(&mut self) {
+        for tri in self.tris.chunks_exact_mut(3) {
+            tri.swap(1, 2);
+        }
+    }
+}
+
+/// Mesh triangles read by MikkTSpace, writing the tangent of each corner into its vertex.
+/// MikkTSpace expects counter-clockwise front faces, so the corners are read in reverse.
+struct TangentGeometry<'a> {
+    mesh: &'a Mesh,
+    tangents: Vec<[f32; 4]>,
+}
+
+impl TangentGeometry<'_> {
+    fn index(&self, face: usize, vert: usize) -> usize {
+        self.mesh.tris[face * 3 + 2 - vert] as usize
+    }
+    fn vertex(&self, face: usize, vert: usize) -> &Vertex {
+        &self.mesh.vertices[self.index(face, vert)]
+    }
+}
+
+impl Geometry for TangentGeometry<'_> {
+    fn num_faces(&self) -> usize { self.mesh.tris.len() / 3 }
+    fn num_vertices_of_face(&self, _face: usize) -> usize { 3 }
+    fn position(&self, face: usize, vert: usize) -> [f32; 3] { self.vertex(face, vert).pos }
+    fn normal(&self, face: usize, vert: usize) -> [f32; 3] { self.vertex(face, vert).normal }
+    fn tex_coord(&self, face: usize, vert: usize) -> [f32; 2] { self.vertex(face, vert).uv }
+    fn set_tangent_encoded(&mut self, tangent: [f32; 4], face: usize, vert: usize) {
+        let index = self.index(face, vert);
+        self.tangents[index] = tangent;
+    }
+}
+
+/// Direction a triangle faces when seen from its front side by the `Brush` back face culling,
+/// scaled by twice its area.
+pub(crate) fn face_normal([a, b, c]: [Vec3; 3]) -> Vec3 { (c - a).cross(b - a) }
+
+fn is_close(a: &Vertex, b: &Vertex, epsilon: f32) -> bool {
+    let a: &[f32] = bytemuck::cast_slice(std::slice::from_ref(a));
+    let b: &[f32] = bytemuck::cast_slice(std::slice::from_ref(b));
+    a.iter().zip(b).all(|(a, b)| (a - b).abs() <= epsilon)
+}
+
+/// Forsyth vertex score: vertices used by the last triangle get a fixed score, older cached
+/// vertices decay with their cache position and vertices with few triangles left are boosted.
+fn cache_score(cache_position: Option<usize>, remaining_tris: usize) -> f32 {
+    if remaining_tris == 0 {
+        return -1.0;
+    }
+    let cache = match cache_position {
+        None => 0.0,
+        Some(position) if position < 3 => 0.75,
+        Some(position) => {
+            let scale = 1.0 / (CACHE_SIZE - 3) as f32;
+            (1.0 - (position - 3) as f32 * scale).powf(1.5)
+        }
+    };
+    cache + 2.0 * (remaining_tris as f32).powf(-0.5)
+}
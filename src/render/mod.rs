@@ -1,26 +1,61 @@
+mod atlas;
 mod binders;
 mod brush;
 mod buffers;
+mod canvas;
+mod compressed_texture;
+mod compute_brush;
+mod compute_pass;
+mod culling;
+mod debug_draw;
+mod deferred;
+mod dynamic_buffer;
 
 #[cfg(feature = "egui")]
 mod egui;
 
+mod gltf_import;
 mod gpu_buffer;
+mod gpu_layout;
 mod graphics;
+mod mesh_formats;
+mod mesh_utils;
+mod particles;
+mod picking;
 mod render_pass;
 mod sampler;
+mod scene;
+mod shapes;
+mod text;
 mod texture;
+mod uniform_arena;
 mod vertex;
 
 #[cfg(feature = "egui")]
 pub use self::egui::*;
 
+pub use atlas::*;
 pub use binders::*;
 pub use brush::*;
 pub use buffers::*;
+pub use canvas::*;
+pub use compressed_texture::*;
+pub use compute_brush::*;
+pub use compute_pass::*;
+pub use culling::*;
+pub use debug_draw::*;
+pub use deferred::*;
+pub use dynamic_buffer::*;
+pub use gltf_import::*;
 pub use gpu_buffer::*;
+pub use gpu_layout::*;
 pub use graphics::*;
+pub use particles::*;
+pub use picking::*;
 pub use render_pass::*;
 pub use sampler::*;
+pub use scene::*;
+pub use text::*;
 pub use texture::*;
+pub use uniform_arena::*;
 pub use vertex::*;
@@ -0,0 +1,74 @@
+use crate::ParticleSystemDesc;
+
+/// Particles emitted by a single simulation step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmitStep {
+    pub count: u32,
+    /// Seed of the random values of the particles emitted in the step. Each particle combines it
+    /// with its emission index.
+    pub seed: u32,
+}
+
+/// Decides how many particles a [`crate::ParticleSystem`] emits each simulation step and the
+/// random seed used to spawn them. Doesn't need the GPU.
+///
+/// ```
+/// use tridify_rs::{ParticleEmitter, ParticleSystemDesc};
+///
+/// let desc = ParticleSystemDesc { emission_rate: 30.0, seed: 7, ..Default::default() };
+/// let run = |emitter: &mut ParticleEmitter, desc: &ParticleSystemDesc| {
+///     emitter.burst(5);
+///     [0.016, 0.05, 0.033, 0.1].map(|dt| emitter.step(desc, dt))
+/// };
+///
+/// //Same seed and time steps emit the same particles.
+/// let mut emitter = ParticleEmitter::new();
+/// let steps = run(&mut emitter, &desc);
+/// assert_eq!(steps, run(&mut ParticleEmitter::new(), &desc));
+/// assert_eq!(steps.map(|x| x.count), [5, 1, 1, 3]);
+///
+/// //Also after resetting.
+/// emitter.reset();
+/// assert_eq!(steps, run(&mut emitter, &desc));
+///
+/// //Other seeds give other random values.
+/// let other = ParticleSystemDesc { seed: 8, ..desc.clone() };
+/// let other_steps = run(&mut ParticleEmitter::new(), &other);
+/// assert_eq!(other_steps.map(|x| x.count), steps.map(|x| x.count));
+/// assert!(steps.iter().zip(other_steps.iter()).all(|(a, b)| a.seed != b.seed));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ParticleEmitter {
+    step: u32,
+    accumulator: f32,
+    pending_burst: u32,
+}
+
+impl ParticleEmitter {
+    pub fn new() -> Self { Self::default() }
+
+    /// Emit the given amount of particles in the next step.
+    pub fn burst(&mut self, count: u32) { self.pending_burst += count; }
+
+    /// Forget pending particles and restart the random sequence.
+    pub fn reset(&mut self) { *self = Self::default(); }
+
+    /// Particles to emit after `delta_time` seconds, limited by the system capacity.
+    pub fn step(&mut self, desc: &ParticleSystemDesc, delta_time: f32) -> EmitStep {
+        self.accumulator += desc.emission_rate * delta_time;
+        let emitted = self.accumulator.floor();
+        self.accumulator -= emitted;
+        let count = (emitted as u32 + self.pending_burst).min(desc.capacity);
+        self.pending_burst = 0;
+        let seed = pcg(desc.seed ^ pcg(self.step));
+        self.step = self.step.wrapping_add(1);
+        EmitStep { count, seed }
+    }
+}
+
+/// Same hash used by the simulation shader.
+fn pcg(value: u32) -> u32 {
+    let state = value.wrapping_mul(747796405).wrapping_add(2891336453);
+    let word = ((state >> ((state >> 28) + 4)) ^ state).wrapping_mul(277803737);
+    (word >> 22) ^ word
+}
@@ -0,0 +1,317 @@
+mod emitter;
+
+use std::{error::Error, ops::Range};
+
+use bytemuck::{Pod, Zeroable};
+use glam::{Mat4, UVec3, Vec2, Vec3};
+use wgpu::BlendState;
+
+pub use emitter::*;
+
+use crate::{
+    AlphaBlend, Brush, BrushDesc, Camera, Color, ComputeBrush, ComputePass, GpuBuffer,
+    GpuBufferDesc, GpuBufferUsage, GpuCtx, GpuLayout, GpuStructWriter, ShapeBatch, ShapeBuffer,
+};
+
+/// Max amount of keys in [`ParticleSystemDesc::color_over_life`].
+pub const MAX_PARTICLE_COLOR_KEYS: usize = 8;
+
+const WORKGROUP_SIZE: u32 = 64;
+
+/// Area where new particles are spawned.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EmitterShape {
+    /// Particles spawn at the emitter position moving in any direction.
+    Point,
+    /// Particles spawn inside the sphere moving outwards.
+    Sphere { radius: f32 },
+    /// Particles spawn on a disc facing the emitter direction and move inside the cone. Angle is
+    /// in radians.
+    Cone { angle: f32, radius: f32 },
+    /// Particles spawn inside the box moving along the emitter direction.
+    Box { half_extents: Vec3 },
+}
+
+/// Configuration of a [`ParticleSystem`].
+#[derive(Debug, Clone)]
+pub struct ParticleSystemDesc {
+    /// Max amount of particles alive at the same time. Can't be changed after creation.
+    pub capacity: u32,
+    /// Particles emitted per second.
+    pub emission_rate: f32,
+    pub shape: EmitterShape,
+    pub position: Vec3,
+    /// Axis used by cone and box emitters.
+    pub direction: Vec3,
+    /// Initial speed range.
+    pub speed: Range<f32>,
+    /// Lifetime range in seconds.
+    pub lifetime: Range<f32>,
+    pub gravity: Vec3,
+    /// Fraction of the velocity lost per second.
+    pub drag: f32,
+    /// Billboard size when the particle is emitted.
+    pub start_size: f32,
+    /// Billboard size at the end of the particle life, interpolated linearly from `start_size`.
+    pub end_size: f32,
+    /// Color keys over normalized life time, sorted by time. Colors are interpolated linearly.
+    pub color_over_life: Vec<(f32, Color)>,
+    /// Blending used when drawing. Usually [`AlphaBlend::Additive`] or
+    /// [`AlphaBlend::SoftAdditive`]. Can't be changed after creation.
+    pub blend: AlphaBlend,
+    /// Seed used to generate random values. Systems with the same seed updated with the same time
+    /// steps spawn the same particles.
+    pub seed: u32,
+}
+impl Default for ParticleSystemDesc {
+    fn default() -> Self {
+        Self {
+            capacity: 10_000,
+            emission_rate: 100.0,
+            shape: EmitterShape::Point,
+            position: Vec3::ZERO,
+            direction: Vec3::Y,
+            speed: 1.0..2.0,
+            lifetime: 1.0..2.0,
+            gravity: Vec3::new(0.0, -9.8, 0.0),
+            drag: 0.0,
+            start_size: 0.1,
+            end_size: 0.0,
+            color_over_life: vec![(0.0, Color::WHITE), (1.0, Color::new(1.0, 1.0, 1.0, 0.0))],
+            blend: AlphaBlend::Additive,
+            seed: 0,
+        }
+    }
+}
+
+/// Particle state as stored in the GPU.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+struct GpuParticle {
+    position: [f32; 3],
+    age: f32,
+    velocity: [f32; 3],
+    lifetime: f32,
+}
+
+/// Indirect draw arguments of the particle quad. Last value stores the particles alive before the
+/// current simulation step.
+const INITIAL_DRAW_ARGS: [u32; 6] = [6, 0, 0, 0, 0, 0];
+
+/// Simulation step resources reading from one particle buffer and writing into the other one.
+struct SimulationBrushes {
+    begin: ComputeBrush,
+    simulate: ComputeBrush,
+    emit: ComputeBrush,
+}
+
+/// Particles emitted, simulated and compacted on the GPU using compute shaders and drawn as
+/// camera facing billboards.
+///
+/// Call [`ParticleSystem::simulate`] once per frame before rendering it with
+/// [`crate::RenderPass::render_particles`].
+pub struct ParticleSystem {
+    desc: ParticleSystemDesc,
+    sim_params: GpuBuffer,
+    render_params: GpuBuffer,
+    draw_args: GpuBuffer<u32>,
+    sim_brushes: [SimulationBrushes; 2],
+    render_brushes: [Brush; 2],
+    quad: ShapeBuffer,
+    /// Index of the particle buffer with the latest state.
+    current: usize,
+    emitter: ParticleEmitter,
+    view_proj: Mat4,
+    camera_right: Vec3,
+    camera_up: Vec3,
+}
+
+impl ParticleSystem {
+    pub fn new(gpu: &GpuCtx, desc: ParticleSystemDesc) -> Result<Self, Box<dyn Error>> {
+        assert!(
+            desc.color_over_life.len() <= MAX_PARTICLE_COLOR_KEYS,
+            "Particle systems support up to {} color keys.",
+            MAX_PARTICLE_COLOR_KEYS
+        );
+        let sim_params = GpuBuffer::init(
+            gpu,
+            &Self::build_sim_params(&desc, 0.0, EmitStep { count: 0, seed: 0 }),
+        );
+        let render_params = GpuBuffer::init(
+            gpu,
+            &Self::build_render_params(&desc, Mat4::IDENTITY, Vec3::X, Vec3::Y),
+        );
+        let draw_args = GpuBuffer::init_with_desc(
+            gpu,
+            GpuBufferDesc::storage(false).with_usage(GpuBufferUsage::INDIRECT),
+            &INITIAL_DRAW_ARGS,
+        );
+        let particles = [
+            GpuBuffer::<GpuParticle>::new(gpu, GpuBufferDesc::storage(false), desc.capacity as u64),
+            GpuBuffer::<GpuParticle>::new(gpu, GpuBufferDesc::storage(false), desc.capacity as u64),
+        ];
+
+        let sim_brush = |src: usize, entry_point: &str| -> Result<ComputeBrush, Box<dyn Error>> {
+            let mut brush = ComputeBrush::from_source(
+                gpu,
+                include_str!("simulate.wgsl").to_string(),
+                entry_point,
+            )?;
+            brush.bind(0, 0, sim_params.clone());
+            brush.bind(0, 1, particles[src].as_read_only());
+            brush.bind(0, 2, particles[1 - src].clone());
+            brush.bind(0, 3, draw_args.clone());
+            brush.update(gpu);
+            Ok(brush)
+        };
+        let sim_brushes = |src: usize| -> Result<SimulationBrushes, Box<dyn Error>> {
+            Ok(SimulationBrushes {
+                begin: sim_brush(src, "begin")?,
+                simulate: sim_brush(src, "simulate")?,
+                emit: sim_brush(src, "emit")?,
+            })
+        };
+
+        let blend = BlendState {
+            color: desc.blend.into(),
+            alpha: desc.blend.into(),
+        };
+        let render_brush = |current: usize| -> Result<Brush, Box<dyn Error>> {
+            let mut brush = Brush::from_source(
+                BrushDesc {
+                    blend,
+                    ..Default::default()
+                },
+                gpu,
+                include_str!("render.wgsl").to_string(),
+            )?;
+            brush.bind(0, 0, render_params.clone());
+            brush.bind(0, 1, particles[current].as_read_only());
+            brush.update(gpu);
+            Ok(brush)
+        };
+
+        Ok(Self {
+            sim_brushes: [sim_brushes(0)?, sim_brushes(1)?],
+            render_brushes: [render_brush(0)?, render_brush(1)?],
+            quad: ShapeBatch::new()
+                .add_2d_square(Vec3::ZERO, 1.0, 1.0, Color::WHITE)
+                .bake_buffers(gpu),
+            desc,
+            sim_params,
+            render_params,
+            draw_args,
+            current: 0,
+            emitter: ParticleEmitter::new(),
+            view_proj: Mat4::IDENTITY,
+            camera_right: Vec3::X,
+            camera_up: Vec3::Y,
+        })
+    }
+
+    pub fn desc(&self) -> &ParticleSystemDesc { &self.desc }
+
+    /// Modify the system configuration. Changes are uploaded in the next simulation step. Capacity
+    /// and blending are fixed at creation.
+    pub fn desc_mut(&mut self) -> &mut ParticleSystemDesc { &mut self.desc }
+
+    /// Camera used to orient the billboards.
+    pub fn set_camera(&mut self, camera: &Camera) {
+        let view = camera.view.build_matrix();
+        self.view_proj = camera.build_camera_matrix();
+        self.camera_right = view.row(0).truncate().normalize();
+        self.camera_up = view.row(1).truncate().normalize();
+    }
+
+    /// Emit the given amount of particles in the next simulation step.
+    pub fn burst(&mut self, count: u32) { self.emitter.burst(count); }
+
+    /// Kill all particles and restart the random sequence.
+    pub fn reset(&mut self, gpu: &GpuCtx) {
+        self.draw_args.write_slice(gpu, &INITIAL_DRAW_ARGS);
+        self.current = 0;
+        self.emitter.reset();
+    }
+
+    /// Record one simulation step into the compute pass: ages and moves alive particles, removes
+    /// dead ones and emits new particles. Parameters are uploaded when called, so only one step
+    /// should be recorded per submit.
+    pub fn simulate<'a>(&'a mut self, gpu: &GpuCtx, pass: &mut ComputePass<'a>, delta_time: f32) {
+        let emit = self.emitter.step(&self.desc, delta_time);
+        self.sim_params
+            .write_slice(gpu, &Self::build_sim_params(&self.desc, delta_time, emit));
+        self.render_params.write_slice(
+            gpu,
+            &Self::build_render_params(
+                &self.desc,
+                self.view_proj,
+                self.camera_right,
+                self.camera_up,
+            ),
+        );
+        let src = self.current;
+        self.current = 1 - src;
+
+        let brushes = &self.sim_brushes[src];
+        pass.dispatch_cached(&brushes.begin, UVec3::ONE);
+        pass.dispatch_cached(
+            &brushes.simulate,
+            UVec3::new(self.desc.capacity.div_ceil(WORKGROUP_SIZE), 1, 1),
+        );
+        if emit.count > 0 {
+            pass.dispatch_cached(
+                &brushes.emit,
+                UVec3::new(emit.count.div_ceil(WORKGROUP_SIZE), 1, 1),
+            );
+        }
+    }
+
+    pub(crate) fn render_brush(&self) -> &Brush { &self.render_brushes[self.current] }
+    pub(crate) fn quad(&self) -> &ShapeBuffer { &self.quad }
+    pub(crate) fn draw_args(&self) -> &GpuBuffer<u32> { &self.draw_args }
+
+    fn build_sim_params(desc: &ParticleSystemDesc, delta_time: f32, emit: EmitStep) -> Vec<u8> {
+        let (shape, shape_params) = match desc.shape {
+            EmitterShape::Point => (0u32, Vec3::ZERO),
+            EmitterShape::Sphere { radius } => (1, Vec3::new(radius, 0.0, 0.0)),
+            EmitterShape::Cone { angle, radius } => (2, Vec3::new(angle, radius, 0.0)),
+            EmitterShape::Box { half_extents } => (3, half_extents),
+        };
+        GpuStructWriter::new(GpuLayout::Std140)
+            .field(&desc.position)
+            .field(&shape)
+            .field(&desc.direction.normalize_or_zero())
+            .field(&delta_time)
+            .field(&shape_params.extend(0.0))
+            .field(&desc.gravity)
+            .field(&desc.drag)
+            .field(&Vec2::new(desc.speed.start, desc.speed.end))
+            .field(&Vec2::new(desc.lifetime.start, desc.lifetime.end))
+            .field(&emit.count)
+            .field(&desc.capacity)
+            .field(&emit.seed)
+            .finish()
+    }
+
+    fn build_render_params(
+        desc: &ParticleSystemDesc, view_proj: Mat4, right: Vec3, up: Vec3,
+    ) -> Vec<u8> {
+        let mut colors = [Color::WHITE; MAX_PARTICLE_COLOR_KEYS];
+        let mut times = [0.0f32; MAX_PARTICLE_COLOR_KEYS];
+        let keys = &desc.color_over_life[..desc.color_over_life.len().min(MAX_PARTICLE_COLOR_KEYS)];
+        for (i, (time, color)) in keys.iter().enumerate() {
+            colors[i] = *color;
+            times[i] = *time;
+        }
+        GpuStructWriter::new(GpuLayout::Std140)
+            .field(&view_proj)
+            .field(&right)
+            .field(&desc.start_size)
+            .field(&up)
+            .field(&desc.end_size)
+            .field(&colors)
+            .field(&times)
+            .field(&(keys.len() as u32))
+            .finish()
+    }
+}
@@ -0,0 +1,73 @@
+struct Particle {
+    position: vec3<f32>,
+    age: f32,
+    velocity: vec3<f32>,
+    lifetime: f32,
+};
+
+struct RenderParams {
+    view_proj: mat4x4<f32>,
+    camera_right: vec3<f32>,
+    size_start: f32,
+    camera_up: vec3<f32>,
+    size_end: f32,
+    colors: array<vec4<f32>, 8>,
+    color_times: array<vec4<f32>, 8>,
+    color_count: u32,
+};
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) uv: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) uv: vec2<f32>,
+};
+
+@group(0) @binding(0) var<uniform> u_render: RenderParams;
+@group(0) @binding(1) var<storage, read> particles: array<Particle>;
+
+fn color_over_life(t: f32) -> vec4<f32> {
+    let count = u_render.color_count;
+    if count == 0u {
+        return vec4<f32>(1.0);
+    }
+    if t <= u_render.color_times[0].x {
+        return u_render.colors[0];
+    }
+    for (var i = 1u; i < count; i += 1u) {
+        let end = u_render.color_times[i].x;
+        if t <= end {
+            let start = u_render.color_times[i - 1u].x;
+            let f = (t - start) / max(end - start, 0.00001);
+            return mix(u_render.colors[i - 1u], u_render.colors[i], f);
+        }
+    }
+    return u_render.colors[count - 1u];
+}
+
+@vertex
+fn vs_main(model: VertexInput, @builtin(instance_index) instance: u32) -> VertexOutput {
+    let p = particles[instance];
+    let t = clamp(p.age / p.lifetime, 0.0, 1.0);
+    let size = mix(u_render.size_start, u_render.size_end, t);
+    let corner = u_render.camera_right * model.position.x + u_render.camera_up * model.position.y;
+
+    var out: VertexOutput;
+    out.clip_position = u_render.view_proj * vec4<f32>(p.position + corner * size, 1.0);
+    out.color = color_over_life(t) * model.color;
+    out.uv = model.uv;
+    return out;
+}
+
+// Round sprite with soft edges.
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let dist = length(in.uv * 2.0 - 1.0);
+    let alpha = in.color.a * (1.0 - smoothstep(0.5, 1.0, dist));
+    return vec4<f32>(in.color.rgb * alpha, alpha);
+}
@@ -0,0 +1,147 @@
+struct Particle {
+    position: vec3<f32>,
+    age: f32,
+    velocity: vec3<f32>,
+    lifetime: f32,
+};
+
+struct Params {
+    emitter_position: vec3<f32>,
+    shape: u32,
+    emitter_direction: vec3<f32>,
+    delta_time: f32,
+    shape_params: vec4<f32>,
+    gravity: vec3<f32>,
+    drag: f32,
+    speed: vec2<f32>,
+    lifetime: vec2<f32>,
+    emit_count: u32,
+    capacity: u32,
+    // Seed of the current step, derived from the system seed on the CPU.
+    step_seed: u32,
+};
+
+// Indirect draw arguments followed by the amount of particles alive before the current step.
+struct DrawArgs {
+    index_count: u32,
+    instance_count: atomic<u32>,
+    first_index: u32,
+    base_vertex: i32,
+    first_instance: u32,
+    alive_before: u32,
+};
+
+@group(0) @binding(0) var<uniform> u_params: Params;
+@group(0) @binding(1) var<storage, read> src_particles: array<Particle>;
+@group(0) @binding(2) var<storage, read_write> dst_particles: array<Particle>;
+@group(0) @binding(3) var<storage, read_write> draw_args: DrawArgs;
+
+const PI: f32 = 3.14159265;
+
+var<private> rng_state: u32;
+
+fn pcg(v: u32) -> u32 {
+    let state = v * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+fn rand() -> f32 {
+    rng_state = pcg(rng_state);
+    return f32(rng_state) / 4294967295.0;
+}
+
+fn rand_range(range: vec2<f32>) -> f32 {
+    return mix(range.x, range.y, rand());
+}
+
+fn rand_unit_vector() -> vec3<f32> {
+    let z = rand() * 2.0 - 1.0;
+    let phi = rand() * 2.0 * PI;
+    let r = sqrt(max(1.0 - z * z, 0.0));
+    return vec3<f32>(r * cos(phi), r * sin(phi), z);
+}
+
+// Two vectors perpendicular to n and to each other.
+fn tangents(n: vec3<f32>) -> mat2x3<f32> {
+    let up = select(vec3<f32>(0.0, 1.0, 0.0), vec3<f32>(1.0, 0.0, 0.0), abs(n.y) > 0.99);
+    let t = normalize(cross(up, n));
+    return mat2x3<f32>(t, cross(n, t));
+}
+
+// Reset the particle counter, remembering how many particles were alive.
+@compute @workgroup_size(1)
+fn begin() {
+    draw_args.alive_before = atomicLoad(&draw_args.instance_count);
+    atomicStore(&draw_args.instance_count, 0u);
+}
+
+// Update alive particles and copy them compacted into the destination buffer.
+@compute @workgroup_size(64)
+fn simulate(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= draw_args.alive_before {
+        return;
+    }
+    var p = src_particles[id.x];
+    let dt = u_params.delta_time;
+    p.age += dt;
+    if p.age >= p.lifetime {
+        return;
+    }
+    p.velocity += u_params.gravity * dt;
+    p.velocity *= max(1.0 - u_params.drag * dt, 0.0);
+    p.position += p.velocity * dt;
+    let index = atomicAdd(&draw_args.instance_count, 1u);
+    dst_particles[index] = p;
+}
+
+// Spawn new particles after the alive ones. Random values only depend on the step seed and the
+// emission index so results are deterministic.
+@compute @workgroup_size(64)
+fn emit(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= u_params.emit_count {
+        return;
+    }
+    let index = atomicAdd(&draw_args.instance_count, 1u);
+    if index >= u_params.capacity {
+        atomicSub(&draw_args.instance_count, 1u);
+        return;
+    }
+    rng_state = pcg(u_params.step_seed ^ pcg(id.x));
+
+    let axis = normalize(u_params.emitter_direction);
+    var offset = vec3<f32>(0.0);
+    var direction = rand_unit_vector();
+    switch u_params.shape {
+        // Sphere
+        case 1u: {
+            offset = direction * u_params.shape_params.x * pow(rand(), 1.0 / 3.0);
+        }
+        // Cone
+        case 2u: {
+            let basis = tangents(axis);
+            let cos_theta = mix(1.0, cos(u_params.shape_params.x), rand());
+            let sin_theta = sqrt(max(1.0 - cos_theta * cos_theta, 0.0));
+            let phi = rand() * 2.0 * PI;
+            direction = basis * vec2<f32>(cos(phi), sin(phi)) * sin_theta + axis * cos_theta;
+            let r = u_params.shape_params.y * sqrt(rand());
+            let base_phi = rand() * 2.0 * PI;
+            offset = basis * vec2<f32>(cos(base_phi), sin(base_phi)) * r;
+        }
+        // Box
+        case 3u: {
+            let unit = vec3<f32>(rand(), rand(), rand()) * 2.0 - 1.0;
+            offset = unit * u_params.shape_params.xyz;
+            direction = axis;
+        }
+        // Point
+        default: {}
+    }
+
+    var p: Particle;
+    p.position = u_params.emitter_position + offset;
+    p.velocity = direction * rand_range(u_params.speed);
+    p.age = 0.0;
+    p.lifetime = rand_range(u_params.lifetime);
+    dst_particles[index] = p;
+}
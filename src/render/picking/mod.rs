@@ -0,0 +1,140 @@
+use std::cell::{Cell, RefCell};
+
+use bytemuck::{Pod, Zeroable};
+use glam::{Mat4, UVec2, UVec3, Vec2};
+use wgpu::TextureFormat;
+
+use crate::{
+    Brush, BrushDesc, Camera, DepthDesc, GpuCtx, NodeId, Texture, TextureDesc, TextureSize,
+    TextureUsage, UniformArena,
+};
+
+/// Max amount of objects drawn per frame by [`GpuPicker::new`].
+const DEFAULT_DRAW_CAPACITY: u64 = 1024;
+
+/// Data uploaded for each drawn object.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+struct PickUniform {
+    view_proj: [f32; 16],
+    model: [f32; 16],
+    id: u32,
+    reverse_z: u32,
+    _padding: [u32; 2],
+}
+
+/// Finds the object under a pixel by drawing object IDs into an `R32Uint` texture and reading
+/// it back. Exact for any mesh shape, at the cost of a GPU round trip.
+///
+/// IDs are drawn in a pass created with [`crate::RenderPassBuilder::build_picking_pass`] using
+/// [`crate::RenderPass::render_pick_id`] or [`crate::RenderPass::render_scene_ids`], and read
+/// with [`GpuPicker::pick`] once the frame is finished.
+pub struct GpuPicker {
+    ids: Texture,
+    depth: Texture,
+    brush: Brush,
+    draws: RefCell<UniformArena<PickUniform>>,
+    /// Camera matrix of the current pass and whether it uses reverse-Z.
+    camera: Cell<(Mat4, bool)>,
+}
+
+impl GpuPicker {
+    /// Create picker covering the window.
+    pub fn new(gpu: &GpuCtx) -> Self { Self::with_capacity(gpu, DEFAULT_DRAW_CAPACITY) }
+
+    /// Create picker able to draw up to `draw_capacity` objects per frame.
+    pub fn with_capacity(gpu: &GpuCtx, draw_capacity: u64) -> Self {
+        let draws = UniformArena::new(gpu, draw_capacity);
+        let desc = BrushDesc {
+            targets: Some(vec![TextureFormat::R32Uint]),
+            depth: Some(DepthDesc::default()),
+            ..Default::default()
+        };
+        let mut brush = Brush::from_source(desc, gpu, include_str!("shader.wgsl").to_string())
+            .expect("Error creating picking brush.");
+        brush.bind(0, 0, draws.clone());
+        brush.update(gpu);
+        let (ids, depth) = Self::create_targets(gpu);
+        Self {
+            ids,
+            depth,
+            brush,
+            draws: RefCell::new(draws),
+            camera: Cell::new((Mat4::IDENTITY, false)),
+        }
+    }
+
+    /// Match the window size, usually after it is resized.
+    pub fn resize(&mut self, gpu: &GpuCtx) { (self.ids, self.depth) = Self::create_targets(gpu); }
+
+    /// ID of the object drawn under the pixel, measured from the top left corner of the window.
+    /// Waits for the GPU, so it should be called after [`crate::RenderPassBuilder::finish_render`]
+    /// and at most a few times per frame.
+    pub fn pick(&self, gpu: &GpuCtx, pixel: Vec2) -> Option<u32> {
+        let size = self.ids.desc.size.get_size();
+        if pixel.x < 0.0 || pixel.y < 0.0 || pixel.x >= size.x as f32 || pixel.y >= size.y as f32 {
+            return None;
+        }
+        let origin = pixel.as_uvec2().extend(0);
+        let data = self.ids.read_pixels(gpu, origin, UVec3::ONE);
+        //IDs are stored plus one, leaving 0 for pixels without objects.
+        u32::from_ne_bytes(data[..4].try_into().unwrap()).checked_sub(1)
+    }
+
+    /// Scene node under the pixel, when drawn with [`crate::RenderPass::render_scene_ids`].
+    pub fn pick_node(&self, gpu: &GpuCtx, pixel: Vec2) -> Option<NodeId> {
+        self.pick(gpu, pixel).map(|x| NodeId(x as usize))
+    }
+
+    /// Start drawing IDs seen from the camera.
+    pub(crate) fn begin(&self, camera: &Camera) {
+        self.draws.borrow_mut().begin_frame();
+        self.camera
+            .set((camera.build_camera_matrix(), camera.proj.is_reverse_z()));
+    }
+
+    /// Upload the data of an object and return its dynamic offset.
+    pub(crate) fn push(&self, gpu: &GpuCtx, model: Mat4, id: u32) -> u32 {
+        assert!(id < u32::MAX, "Picking ID u32::MAX is reserved.");
+        let (view_proj, reverse_z) = self.camera.get();
+        self.draws.borrow_mut().push(
+            gpu,
+            &PickUniform {
+                view_proj: view_proj.to_cols_array(),
+                model: model.to_cols_array(),
+                id: id + 1,
+                reverse_z: reverse_z as u32,
+                _padding: [0; 2],
+            },
+        )
+    }
+
+    pub(crate) fn brush(&self) -> &Brush { &self.brush }
+    pub(crate) fn ids(&self) -> &Texture { &self.ids }
+    pub(crate) fn depth(&self) -> &Texture { &self.depth }
+
+    fn create_targets(gpu: &GpuCtx) -> (Texture, Texture) {
+        let size = TextureSize::D2(gpu.get_wnd_size().max(UVec2::ONE));
+        let ids = Texture::new(
+            gpu,
+            TextureDesc {
+                size,
+                usage: TextureUsage::RENDER | TextureUsage::SOURCE,
+                format: TextureFormat::R32Uint,
+                mip_level_count: 1,
+            },
+            Some("Picking IDs"),
+        );
+        let depth = Texture::new(
+            gpu,
+            TextureDesc {
+                size,
+                usage: TextureUsage::RENDER,
+                format: DepthDesc::default().format,
+                mip_level_count: 1,
+            },
+            Some("Picking depth"),
+        );
+        (ids, depth)
+    }
+}
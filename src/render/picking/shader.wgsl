@@ -0,0 +1,30 @@
+// Picking brush. Writes the ID of each drawn object, the camera and object data are bound to
+// group 0, binding 0 using a dynamic offset for each draw.
+
+struct Draw {
+    view_proj: mat4x4<f32>,
+    model: mat4x4<f32>,
+    id: u32,
+    reverse_z: u32,
+};
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+};
+
+@group(0) @binding(0) var<uniform> u_draw: Draw;
+
+@vertex
+fn vs_main(model: VertexInput) -> @builtin(position) vec4<f32> {
+    var position = u_draw.view_proj * u_draw.model * vec4<f32>(model.position, 1.0);
+    // Depth is flipped so the closest object wins using the same depth test for any projection.
+    if u_draw.reverse_z != 0u {
+        position.z = position.w - position.z;
+    }
+    return position;
+}
+
+@fragment
+fn fs_main() -> @location(0) u32 {
+    return u_draw.id;
+}
@@ -1,26 +1,33 @@
 use std::error::Error;
 
+use glam::Mat4;
 use wgpu::{
     CommandEncoder, CommandEncoderDescriptor, Operations, RenderPassColorAttachment,
-    RenderPassDescriptor, SurfaceTexture, TextureView, TextureViewDescriptor,
+    RenderPassDepthStencilAttachment, RenderPassDescriptor, SurfaceTexture, TextureView,
+    TextureViewDescriptor,
 };
 
-use crate::core::Color;
-use crate::GpuCtx;
+use crate::core::{Camera, Color};
 use crate::Rect;
 use crate::ShapeBuffer;
+use crate::{DrawIndexedIndirectArgs, GpuBuffer, GpuBufferUsage, GpuCtx};
 
-use super::Brush;
+use super::{
+    Brush, Canvas, ComputePass, DebugDraw, FrustumCuller, GpuPicker, ParticleSystem, Scene, Texture,
+};
 
 /// Rendering configuration on how to create and represent the given frame.
 pub struct RenderOptions {
     pub clear_color: Color,
+    /// Value depth textures are cleared to.
+    pub clear_depth: f32,
 }
 
 impl Default for RenderOptions {
     fn default() -> Self {
         Self {
             clear_color: Color::BLACK,
+            clear_depth: 1.0,
         }
     }
 }
@@ -36,7 +43,7 @@ impl RenderPassBuilder {
         let frame_view = frame_texture
             .texture
             .create_view(&TextureViewDescriptor::default());
-        let mut draw_cmds = wnd
+        let draw_cmds = wnd
             .device
             .create_command_encoder(&CommandEncoderDescriptor { label: None });
 
@@ -47,7 +54,7 @@ impl RenderPassBuilder {
         })
     }
 
-    pub fn build_render_pass(&mut self, options: RenderOptions) -> RenderPass {
+    pub fn build_render_pass(&mut self, options: RenderOptions) -> RenderPass<'_> {
         let pass = self.draw_cmds.begin_render_pass(&RenderPassDescriptor {
             label: None,
             color_attachments: &[Some(RenderPassColorAttachment {
@@ -63,6 +70,60 @@ impl RenderPassBuilder {
         RenderPass { pass }
     }
 
+    /// Create a render pass drawing into textures instead of the window. All color targets are
+    /// cleared with the same color. Brushes used in the pass need the same target formats and
+    /// depth format, see [`crate::BrushDesc`].
+    pub fn build_texture_pass<'a>(
+        &'a mut self, targets: &[&'a Texture], depth: Option<&'a Texture>, options: RenderOptions,
+    ) -> RenderPass<'a> {
+        let color_attachments = targets
+            .iter()
+            .map(|target| {
+                Some(RenderPassColorAttachment {
+                    view: target.view(),
+                    resolve_target: None,
+                    ops: Operations {
+                        load: wgpu::LoadOp::Clear(options.clear_color.into()),
+                        store: true,
+                    },
+                })
+            })
+            .collect::<Vec<_>>();
+        let pass = self.draw_cmds.begin_render_pass(&RenderPassDescriptor {
+            label: None,
+            color_attachments: &color_attachments,
+            depth_stencil_attachment: depth.map(|depth| RenderPassDepthStencilAttachment {
+                view: depth.view(),
+                depth_ops: Some(Operations {
+                    load: wgpu::LoadOp::Clear(options.clear_depth),
+                    store: true,
+                }),
+                stencil_ops: None,
+            }),
+        });
+        RenderPass { pass }
+    }
+
+    /// Create a render pass drawing object IDs seen from the camera into the picker, see
+    /// [`GpuPicker`]. Objects are drawn with [`RenderPass::render_pick_id`] or
+    /// [`RenderPass::render_scene_ids`].
+    pub fn build_picking_pass<'a>(
+        &'a mut self, picker: &'a GpuPicker, camera: &Camera,
+    ) -> RenderPass<'a> {
+        picker.begin(camera);
+        let options = RenderOptions {
+            clear_color: Color::BLACK,
+            clear_depth: 1.0,
+        };
+        self.build_texture_pass(&[picker.ids()], Some(picker.depth()), options)
+    }
+
+    /// Create a compute pass that runs before the frame is presented, in recording order with the
+    /// render passes of this builder.
+    pub fn build_compute_pass(&mut self) -> ComputePass<'_> {
+        ComputePass::new(&mut self.draw_cmds)
+    }
+
     pub fn finish_render(self, wnd: &GpuCtx) {
         wnd.queue.submit(Some(self.draw_cmds.finish()));
         self.frame_texture.present();
@@ -93,21 +154,226 @@ impl<'a> RenderPass<'a> {
         self.render_shapes_cached(brush, buffer);
     }
 
+    ///Draw batch on the canvas using dynamic offsets for the brush bindings that require them,
+    ///like [`crate::UniformArena`]. Offsets are consumed in group and binding order.
+    pub fn render_shapes_with_offsets(
+        &mut self, wnd: &GpuCtx, brush: &'a mut Brush, buffer: &'a ShapeBuffer, offsets: &[u32],
+    ) {
+        if brush.needs_update() {
+            brush.update(wnd);
+        }
+        self.render_shapes_cached_with_offsets(brush, buffer, offsets);
+    }
+
+    /// Draw batch unless its bounds, placed with the model matrix the brush uses, are outside the
+    /// culler frustum. Returns whether it was drawn.
+    pub fn render_shapes_culled(
+        &mut self, gpu: &GpuCtx, culler: &mut FrustumCuller, brush: &'a mut Brush,
+        buffer: &'a ShapeBuffer, model: &Mat4,
+    ) -> bool {
+        let visible = culler.is_visible(buffer.aabb.as_ref(), model);
+        if visible {
+            self.render_shapes(gpu, brush, buffer);
+        }
+        visible
+    }
+
     /// Draw batch on canvas. Does not check if brush requires any changes.
     pub fn render_shapes_cached(&mut self, brush: &'a Brush, buffer: &'a ShapeBuffer) {
+        self.render_shapes_cached_with_offsets(brush, buffer, &[]);
+    }
+
+    /// Draw batch on canvas with dynamic offsets. Does not check if brush requires any changes.
+    pub fn render_shapes_cached_with_offsets(
+        &mut self, brush: &'a Brush, buffer: &'a ShapeBuffer, offsets: &[u32],
+    ) {
         let pipeline = brush.get_pipeline();
         self.pass.set_pipeline(pipeline);
-        let bind_groups = brush.get_bind_groups();
-        bind_groups
-            .iter()
-            .for_each(|(id, bg)| self.pass.set_bind_group(*id, bg, &[]));
+        brush.set_bind_groups(&mut self.pass, offsets);
+        self.set_shape_buffer(buffer);
+        for submesh in buffer.submeshes.iter() {
+            self.pass
+                .draw_indexed(submesh.indices.clone(), submesh.base_vertex, 0..1);
+        }
+    }
 
+    ///Draw a single submesh of the buffer, see [`crate::ShapeBatch::begin_submesh`].
+    pub fn render_submesh(
+        &mut self, wnd: &GpuCtx, brush: &'a mut Brush, buffer: &'a ShapeBuffer, submesh: usize,
+    ) {
+        if brush.needs_update() {
+            brush.update(wnd);
+        }
+        self.render_submesh_cached_with_offsets(brush, buffer, submesh, &[]);
+    }
+
+    /// Draw a single submesh of the buffer. Does not check if brush requires any changes.
+    pub fn render_submesh_cached(
+        &mut self, brush: &'a Brush, buffer: &'a ShapeBuffer, submesh: usize,
+    ) {
+        self.render_submesh_cached_with_offsets(brush, buffer, submesh, &[]);
+    }
+
+    /// Draw a single submesh of the buffer with dynamic offsets. Does not check if brush requires
+    /// any changes.
+    pub fn render_submesh_cached_with_offsets(
+        &mut self, brush: &'a Brush, buffer: &'a ShapeBuffer, submesh: usize, offsets: &[u32],
+    ) {
+        let submesh = &buffer.submeshes[submesh];
+        self.pass.set_pipeline(brush.get_pipeline());
+        brush.set_bind_groups(&mut self.pass, offsets);
+        self.set_shape_buffer(buffer);
         self.pass
-            .set_vertex_buffer(0, buffer.vertex_buffer.slice(..));
+            .draw_indexed(submesh.indices.clone(), submesh.base_vertex, 0..1);
+    }
+
+    /// Draw shapes with the arguments stored at `index` of a GPU buffer, usually written by a
+    /// compute pass. Does not check if brush requires any changes.
+    ///
+    /// Panics if the adapter lacks the `INDIRECT_EXECUTION` downlevel flag, like WebGL2 and some
+    /// GLES devices. The arguments only exist on the GPU, so there is no direct draw fallback.
+    pub fn render_indirect(
+        &mut self, gpu: &GpuCtx, brush: &'a Brush, buffer: &'a ShapeBuffer,
+        args: &'a GpuBuffer<DrawIndexedIndirectArgs>, index: u64,
+    ) {
+        self.multi_draw_indexed_indirect_impl(gpu, brush, buffer, args, index, 1, false);
+    }
+
+    /// Draw shapes once for each of the `count` arguments starting at `first` in a GPU buffer.
+    /// Uses a single draw call when the device has the `MULTI_DRAW_INDIRECT` feature, falling back
+    /// to an indirect draw per argument otherwise. Does not check if brush requires any changes.
+    ///
+    /// The fallback still draws indirectly, so like [`RenderPass::render_indirect`] this panics
+    /// without the `INDIRECT_EXECUTION` downlevel flag.
+    pub fn multi_draw_indexed_indirect(
+        &mut self, gpu: &GpuCtx, brush: &'a Brush, buffer: &'a ShapeBuffer,
+        args: &'a GpuBuffer<DrawIndexedIndirectArgs>, first: u64, count: u32,
+    ) {
+        let multi_draw = gpu
+            .device
+            .features()
+            .contains(wgpu::Features::MULTI_DRAW_INDIRECT);
+        self.multi_draw_indexed_indirect_impl(gpu, brush, buffer, args, first, count, multi_draw);
+    }
+
+    /// Draw all scene nodes with a mesh and a brush, uploading the camera and the node world
+    /// matrices.
+    pub fn render_scene(&mut self, gpu: &GpuCtx, scene: &'a Scene, camera: &Camera) {
+        for (brush, mesh, offset) in scene.prepare_draws(gpu, camera, None) {
+            self.render_shapes_cached_with_offsets(brush, mesh, &[offset]);
+        }
+    }
+
+    /// Draw the scene nodes whose mesh bounds are inside the camera frustum, counting them in the
+    /// culler. The culler needs to be started with the same camera.
+    pub fn render_scene_culled(
+        &mut self, gpu: &GpuCtx, scene: &'a Scene, camera: &Camera, culler: &mut FrustumCuller,
+    ) {
+        for (brush, mesh, offset) in scene.prepare_draws(gpu, camera, Some(culler)) {
+            self.render_shapes_cached_with_offsets(brush, mesh, &[offset]);
+        }
+    }
+
+    /// Draw particles with their latest simulated state. Instance count is read from the GPU so
+    /// it must be simulated at least once.
+    pub fn render_particles(&mut self, particles: &'a ParticleSystem) {
+        let brush = particles.render_brush();
+        let quad = particles.quad();
+        self.pass.set_pipeline(brush.get_pipeline());
+        brush.set_bind_groups(&mut self.pass, &[]);
+        self.set_shape_buffer(quad);
         self.pass
-            .set_index_buffer(buffer.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            .draw_indexed_indirect(particles.draw_args().buffer(), 0);
+    }
+
+    /// Flush the canvas, drawing all shapes recorded since the last flush. Call it at the end of
+    /// the pass so the canvas is drawn over everything else.
+    pub fn render_canvas(&mut self, gpu: &GpuCtx, canvas: &'a mut Canvas) {
+        canvas.flush(gpu);
+        let canvas: &'a Canvas = canvas;
+        self.set_shape_buffer(canvas.shape_buffer());
+        for (brush, texture_group, indices) in canvas.flushed_draws() {
+            self.pass.set_pipeline(brush.get_pipeline());
+            brush.set_bind_groups(&mut self.pass, &[]);
+            self.pass.set_bind_group(1, texture_group, &[]);
+            self.pass.draw_indexed(indices, 0, 0..1);
+        }
+    }
+
+    /// Draw the lines recorded in the debug drawer as seen from the camera, clearing them for the
+    /// next frame.
+    pub fn render_debug(&mut self, gpu: &GpuCtx, debug: &'a mut DebugDraw, camera: &Camera) {
+        debug.flush(gpu, camera);
+        let debug: &'a DebugDraw = debug;
+        let buffer = debug.shape_buffer();
+        self.pass.set_pipeline(debug.brush().get_pipeline());
+        debug.brush().set_bind_groups(&mut self.pass, &[]);
+        self.set_shape_buffer(buffer);
         self.pass.draw_indexed(0..buffer.index_len, 0, 0..1);
     }
+
+    /// Draw the ID of an object placed with the model matrix, in a pass created with
+    /// [`RenderPassBuilder::build_picking_pass`].
+    pub fn render_pick_id(
+        &mut self, gpu: &GpuCtx, picker: &'a GpuPicker, buffer: &'a ShapeBuffer, model: Mat4,
+        id: u32,
+    ) {
+        let offset = picker.push(gpu, model, id);
+        self.render_shapes_cached_with_offsets(picker.brush(), buffer, &[offset]);
+    }
+
+    /// Draw the ID of every scene node with a mesh, in a pass created with
+    /// [`RenderPassBuilder::build_picking_pass`]. Read them with [`GpuPicker::pick_node`].
+    pub fn render_scene_ids(&mut self, gpu: &GpuCtx, picker: &'a GpuPicker, scene: &'a Scene) {
+        for (id, node) in scene.iter() {
+            if let Some(mesh) = node.mesh {
+                let buffer = scene.mesh(mesh);
+                self.render_pick_id(gpu, picker, buffer, scene.world_matrix(id), id.0 as u32);
+            }
+        }
+    }
+
     pub fn finish(self) {
     }
+
+    #[allow(clippy::too_many_arguments)]
+    fn multi_draw_indexed_indirect_impl(
+        &mut self, gpu: &GpuCtx, brush: &'a Brush, buffer: &'a ShapeBuffer,
+        args: &'a GpuBuffer<DrawIndexedIndirectArgs>, first: u64, count: u32, multi_draw: bool,
+    ) {
+        assert!(
+            gpu.adapter
+                .get_downlevel_capabilities()
+                .flags
+                .contains(wgpu::DownlevelFlags::INDIRECT_EXECUTION),
+            "Indirect draws need the INDIRECT_EXECUTION downlevel flag, which this adapter lacks."
+        );
+        assert!(
+            args.desc().usage.contains(GpuBufferUsage::INDIRECT),
+            "Indirect arguments buffer needs INDIRECT usage."
+        );
+        assert!(
+            first + count as u64 <= args.len(),
+            "Drawing out of the indirect arguments buffer bounds."
+        );
+        self.pass.set_pipeline(brush.get_pipeline());
+        brush.set_bind_groups(&mut self.pass, &[]);
+        self.set_shape_buffer(buffer);
+        let stride = std::mem::size_of::<DrawIndexedIndirectArgs>() as u64;
+        if multi_draw {
+            self.pass
+                .multi_draw_indexed_indirect(args.buffer(), first * stride, count);
+            return;
+        }
+        for i in first..first + count as u64 {
+            self.pass.draw_indexed_indirect(args.buffer(), i * stride);
+        }
+    }
+
+    fn set_shape_buffer(&mut self, buffer: &'a ShapeBuffer) {
+        self.pass
+            .set_vertex_buffer(0, buffer.vertex_buffer.slice(..));
+        self.pass
+            .set_index_buffer(buffer.index_buffer.slice(..), buffer.index_format);
+    }
 }
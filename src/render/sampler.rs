@@ -1,15 +1,18 @@
-use wgpu::{ShaderModel, ShaderStages};
+use std::rc::Rc;
+
+use wgpu::ShaderStages;
 
 use crate::{GpuCtx, ToBinder};
 
 /// Representation on how a texture will be drawn into a shape.
 pub struct Sampler {
-    inner_sampler: wgpu::Sampler,
+    inner_sampler: Rc<wgpu::Sampler>,
 }
 impl Sampler {
     pub fn new_default(gpu: &GpuCtx) -> Self {
-        Self {
-            inner_sampler: gpu.device.create_sampler(&wgpu::SamplerDescriptor {
+        Self::new(
+            gpu,
+            &wgpu::SamplerDescriptor {
                 address_mode_u: wgpu::AddressMode::ClampToEdge,
                 address_mode_v: wgpu::AddressMode::ClampToEdge,
                 address_mode_w: wgpu::AddressMode::ClampToEdge,
@@ -17,7 +20,14 @@ impl Sampler {
                 min_filter: wgpu::FilterMode::Nearest,
                 mipmap_filter: wgpu::FilterMode::Nearest,
                 ..Default::default()
-            }),
+            },
+        )
+    }
+
+    /// Create sampler with custom filtering and addressing modes.
+    pub fn new(gpu: &GpuCtx, desc: &wgpu::SamplerDescriptor) -> Self {
+        Self {
+            inner_sampler: Rc::new(gpu.device.create_sampler(desc)),
         }
     }
 }
@@ -31,7 +41,7 @@ impl ToBinder for Sampler {
         }
     }
 
-    fn get_group(&self, index: u32) -> wgpu::BindGroupEntry {
+    fn get_group(&self, index: u32) -> wgpu::BindGroupEntry<'_> {
         wgpu::BindGroupEntry {
             binding: index,
             resource: wgpu::BindingResource::Sampler(&self.inner_sampler),
@@ -40,3 +50,11 @@ impl ToBinder for Sampler {
 
     fn debug_name(&self) -> &'static str { "Sampler" }
 }
+
+impl Clone for Sampler {
+    fn clone(&self) -> Self {
+        Self {
+            inner_sampler: Rc::clone(&self.inner_sampler),
+        }
+    }
+}
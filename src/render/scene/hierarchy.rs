@@ -0,0 +1,288 @@
+use std::cell::Cell;
+
+use glam::{Affine3A, Mat4};
+
+use crate::{BrushId, MeshId, Transform};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(pub(crate) usize);
+
+/// Element of the scene hierarchy. Nodes with both a mesh and a brush are drawn.
+pub struct Node {
+    pub name: String,
+    pub mesh: Option<MeshId>,
+    pub brush: Option<BrushId>,
+    transform: Transform,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    world: Cell<Mat4>,
+    dirty: Cell<bool>,
+}
+
+impl Node {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            mesh: None,
+            brush: None,
+            transform: Transform::default(),
+            parent: None,
+            children: Vec::new(),
+            world: Cell::new(Mat4::IDENTITY),
+            dirty: Cell::new(true),
+        }
+    }
+
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    /// Draw the mesh with the brush given.
+    pub fn with_mesh(mut self, mesh: MeshId, brush: BrushId) -> Self {
+        self.mesh = Some(mesh);
+        self.brush = Some(brush);
+        self
+    }
+
+    /// Transform relative to the parent node.
+    pub fn transform(&self) -> &Transform { &self.transform }
+    pub fn parent(&self) -> Option<NodeId> { self.parent }
+    pub fn children(&self) -> &[NodeId] { &self.children }
+}
+
+/// Hierarchy of nodes with their own transform, used by [`crate::Scene`]. World matrices are
+/// computed lazily and cached until a transform in the node or its ancestors changes. Doesn't
+/// need the GPU.
+#[derive(Default)]
+pub struct NodeTree {
+    nodes: Vec<Option<Node>>,
+    roots: Vec<NodeId>,
+}
+
+impl NodeTree {
+    pub fn new() -> Self { Self::default() }
+
+    /// Add node as child of `parent` or as a root node.
+    pub fn add_node(&mut self, node: Node, parent: Option<NodeId>) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(Some(node));
+        self.attach(id, parent);
+        id
+    }
+
+    /// Remove node and all its descendants.
+    ///
+    /// ```
+    /// use tridify_rs::{Node, NodeTree};
+    ///
+    /// let mut tree = NodeTree::new();
+    /// let root = tree.add_node(Node::new("root"), None);
+    /// let child = tree.add_node(Node::new("child"), Some(root));
+    /// tree.add_node(Node::new("grandchild"), Some(child));
+    /// tree.remove_node(child);
+    /// assert!(tree.node(root).children().is_empty());
+    /// assert_eq!(tree.find("grandchild"), None);
+    /// assert_eq!(tree.iter().count(), 1);
+    /// ```
+    pub fn remove_node(&mut self, id: NodeId) {
+        self.detach(id);
+        let mut to_remove = vec![id];
+        while let Some(id) = to_remove.pop() {
+            if let Some(node) = self.nodes[id.0].take() {
+                to_remove.extend(node.children);
+            }
+        }
+    }
+
+    /// Move node under a new parent, or to the root when `None`. Local transform is kept.
+    ///
+    /// ```
+    /// use glam::Vec3;
+    /// use tridify_rs::{Node, NodeTree, Transform};
+    ///
+    /// let mut tree = NodeTree::new();
+    /// let a = tree.add_node(Node::new("a").with_transform(Transform::from_pos(Vec3::X)), None);
+    /// let b = tree.add_node(Node::new("b").with_transform(Transform::from_pos(Vec3::Y)), None);
+    /// let child = tree.add_node(Node::new("child"), Some(a));
+    /// assert_eq!(tree.world_transform(child).position(), Vec3::X);
+    ///
+    /// tree.set_parent(child, Some(b));
+    /// assert!(tree.node(a).children().is_empty());
+    /// assert_eq!(tree.node(b).children(), &[child]);
+    /// assert_eq!(tree.node(child).parent(), Some(b));
+    /// assert_eq!(tree.world_transform(child).position(), Vec3::Y);
+    ///
+    /// tree.set_parent(child, None);
+    /// assert_eq!(tree.roots(), &[a, b, child]);
+    /// assert_eq!(tree.world_transform(child).position(), Vec3::ZERO);
+    /// ```
+    ///
+    /// Parenting a node to one of its descendants panics:
+    ///
+    /// ```should_panic
+    /// use tridify_rs::{Node, NodeTree};
+    ///
+    /// let mut tree = NodeTree::new();
+    /// let root = tree.add_node(Node::new("root"), None);
+    /// let child = tree.add_node(Node::new("child"), Some(root));
+    /// tree.set_parent(root, Some(child));
+    /// ```
+    pub fn set_parent(&mut self, id: NodeId, parent: Option<NodeId>) {
+        if let Some(parent) = parent {
+            assert!(
+                !self.is_descendant(parent, id),
+                "Node can't be parented to itself or its descendants."
+            );
+        }
+        self.detach(id);
+        self.attach(id, parent);
+        self.mark_dirty(id);
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        self.nodes[id.0].as_ref().expect("Node was removed.")
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> &mut Node {
+        self.nodes[id.0].as_mut().expect("Node was removed.")
+    }
+
+    /// Change node transform relative to its parent. World matrices of the node and its
+    /// descendants are recomputed the next time they are read.
+    ///
+    /// ```
+    /// use glam::Vec3;
+    /// use tridify_rs::{Node, NodeTree, Transform};
+    ///
+    /// let mut tree = NodeTree::new();
+    /// let root = tree.add_node(Node::new("root"), None);
+    /// let child = tree.add_node(Node::new("child").with_transform(Transform::from_pos(Vec3::Y)), Some(root));
+    /// let leaf = tree.add_node(Node::new("leaf").with_transform(Transform::from_pos(Vec3::Z)), Some(child));
+    /// assert_eq!(tree.world_transform(leaf).position(), Vec3::new(0.0, 1.0, 1.0));
+    ///
+    /// //Cached matrices of all descendants are updated.
+    /// tree.set_transform(root, Transform::from_pos(Vec3::X));
+    /// assert_eq!(tree.world_transform(leaf).position(), Vec3::ONE);
+    /// assert_eq!(tree.world_transform(child).position(), Vec3::new(1.0, 1.0, 0.0));
+    ///
+    /// //Also when only part of the hierarchy was read since the last change.
+    /// tree.set_transform(child, Transform::default());
+    /// tree.set_transform(root, Transform::default());
+    /// assert_eq!(tree.world_transform(child).position(), Vec3::ZERO);
+    /// tree.set_transform(root, Transform::from_pos(Vec3::X));
+    /// assert_eq!(tree.world_transform(leaf).position(), Vec3::new(1.0, 0.0, 1.0));
+    /// ```
+    pub fn set_transform(&mut self, id: NodeId, transform: Transform) {
+        self.node_mut(id).transform = transform;
+        self.mark_dirty(id);
+    }
+
+    /// Matrix transforming from node space to world space.
+    ///
+    /// ```
+    /// use glam::{Quat, Vec3};
+    /// use tridify_rs::{Node, NodeTree, Transform};
+    ///
+    /// let parent = Transform::new(Vec3::X, Quat::from_rotation_z(std::f32::consts::FRAC_PI_2), Vec3::splat(2.0));
+    /// let child = Transform::from_pos(Vec3::X);
+    /// let mut tree = NodeTree::new();
+    /// let parent_id = tree.add_node(Node::new("parent").with_transform(parent), None);
+    /// let child_id = tree.add_node(Node::new("child").with_transform(child), Some(parent_id));
+    ///
+    /// let world = tree.world_matrix(child_id);
+    /// assert_eq!(world, parent.build_matrix() * child.build_matrix());
+    /// assert!(world.transform_point3(Vec3::ZERO).abs_diff_eq(Vec3::new(1.0, 2.0, 0.0), 1e-6));
+    /// ```
+    pub fn world_matrix(&self, id: NodeId) -> Mat4 {
+        let node = self.node(id);
+        if node.dirty.get() {
+            let parent = node
+                .parent
+                .map_or(Mat4::IDENTITY, |parent| self.world_matrix(parent));
+            node.world.set(parent * node.transform.build_matrix());
+            node.dirty.set(false);
+        }
+        node.world.get()
+    }
+
+    /// Transform of the node in world space, combining the transforms of all its ancestors.
+    ///
+    /// ```
+    /// use std::f32::consts::FRAC_PI_2;
+    ///
+    /// use glam::{Quat, Vec3};
+    /// use tridify_rs::{Node, NodeTree, Transform};
+    ///
+    /// let turn = Quat::from_rotation_y(FRAC_PI_2);
+    /// let mut tree = NodeTree::new();
+    /// let parent = Node::new("parent").with_transform(Transform::new(Vec3::X, turn, Vec3::splat(2.0)));
+    /// let parent = tree.add_node(parent, None);
+    /// let child = Node::new("child").with_transform(Transform::new(Vec3::Z, turn, Vec3::ONE));
+    /// let child = tree.add_node(child, Some(parent));
+    ///
+    /// let world = tree.world_transform(child);
+    /// assert!(world.position().abs_diff_eq(Vec3::new(3.0, 0.0, 0.0), 1e-6));
+    /// assert!(world.forward().abs_diff_eq(-Vec3::Z, 1e-6));
+    /// assert!(world.scale().abs_diff_eq(Vec3::splat(2.0), 1e-6));
+    /// ```
+    pub fn world_transform(&self, id: NodeId) -> Transform {
+        Transform::from_affine(Affine3A::from_mat4(self.world_matrix(id)))
+    }
+
+    /// Nodes without parent.
+    pub fn roots(&self) -> &[NodeId] { &self.roots }
+
+    /// First node found with the given name.
+    pub fn find(&self, name: &str) -> Option<NodeId> {
+        self.iter()
+            .find(|(_, node)| node.name == name)
+            .map(|(id, _)| id)
+    }
+
+    /// Iterate all nodes in creation order.
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, &Node)> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, node)| node.as_ref().map(|node| (NodeId(i), node)))
+    }
+
+    fn attach(&mut self, id: NodeId, parent: Option<NodeId>) {
+        self.node_mut(id).parent = parent;
+        match parent {
+            Some(parent) => self.node_mut(parent).children.push(id),
+            None => self.roots.push(id),
+        }
+    }
+
+    fn detach(&mut self, id: NodeId) {
+        match self.node(id).parent {
+            Some(parent) => self.node_mut(parent).children.retain(|x| *x != id),
+            None => self.roots.retain(|x| *x != id),
+        }
+    }
+
+    fn is_descendant(&self, id: NodeId, ancestor: NodeId) -> bool {
+        let mut current = Some(id);
+        while let Some(node) = current {
+            if node == ancestor {
+                return true;
+            }
+            current = self.node(node).parent;
+        }
+        false
+    }
+
+    /// Mark node and its descendants to recompute their world matrix. Clean nodes always have
+    /// clean ancestors, so already dirty subtrees are skipped.
+    fn mark_dirty(&self, id: NodeId) {
+        let node = self.node(id);
+        if node.dirty.replace(true) {
+            return;
+        }
+        for child in node.children.iter() {
+            self.mark_dirty(*child);
+        }
+    }
+}
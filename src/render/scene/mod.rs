@@ -0,0 +1,161 @@
+use std::cell::RefCell;
+
+use bytemuck::{Pod, Zeroable};
+use glam::Mat4;
+
+use crate::{Brush, Camera, FrustumCuller, GpuCtx, ShapeBuffer, Transform, UniformArena};
+
+mod hierarchy;
+pub use hierarchy::*;
+
+/// WGSL source of a brush that can be used by scene nodes. Scenes bind the camera and node
+/// matrices to group 0, binding 0, so custom shaders need to declare the same uniform.
+pub const SCENE_SHADER_TEMPLATE: &str = include_str!("shader.wgsl");
+
+/// Max amount of nodes drawn per frame by [`Scene::new`].
+const DEFAULT_DRAW_CAPACITY: u64 = 1024;
+
+/// Shape buffer stored in a [`Scene`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MeshId(usize);
+
+/// Brush stored in a [`Scene`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BrushId(usize);
+
+/// Data uploaded for each drawn node.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+struct DrawUniform {
+    view_proj: [f32; 16],
+    model: [f32; 16],
+}
+
+/// Hierarchy of nodes with their own transform, mesh and brush. The hierarchy is stored in a
+/// [`NodeTree`], which computes world matrices lazily.
+///
+/// Drawn with [`crate::RenderPass::render_scene`], which uploads the camera and the model matrix
+/// of each node. Brushes need to follow the bindings of [`SCENE_SHADER_TEMPLATE`].
+pub struct Scene {
+    nodes: NodeTree,
+    meshes: Vec<ShapeBuffer>,
+    brushes: Vec<Brush>,
+    draws: RefCell<UniformArena<DrawUniform>>,
+}
+
+impl Scene {
+    pub fn new(gpu: &GpuCtx) -> Self { Self::with_capacity(gpu, DEFAULT_DRAW_CAPACITY) }
+
+    /// Create scene able to draw up to `draw_capacity` nodes per frame.
+    pub fn with_capacity(gpu: &GpuCtx, draw_capacity: u64) -> Self {
+        Self {
+            nodes: NodeTree::new(),
+            meshes: Vec::new(),
+            brushes: Vec::new(),
+            draws: RefCell::new(UniformArena::new(gpu, draw_capacity)),
+        }
+    }
+
+    pub fn add_mesh(&mut self, mesh: ShapeBuffer) -> MeshId {
+        self.meshes.push(mesh);
+        MeshId(self.meshes.len() - 1)
+    }
+
+    pub fn mesh(&self, id: MeshId) -> &ShapeBuffer { &self.meshes[id.0] }
+
+    /// Store brush binding the node data to group 0, binding 0.
+    pub fn add_brush(&mut self, gpu: &GpuCtx, mut brush: Brush) -> BrushId {
+        brush.bind(0, 0, self.draws.borrow().clone());
+        brush.update(gpu);
+        self.brushes.push(brush);
+        BrushId(self.brushes.len() - 1)
+    }
+
+    pub fn brush(&self, id: BrushId) -> &Brush { &self.brushes[id.0] }
+
+    /// Modify a brush. [`Scene::update_brushes`] needs to be called before drawing the scene if
+    /// bindings change.
+    pub fn brush_mut(&mut self, id: BrushId) -> &mut Brush { &mut self.brushes[id.0] }
+
+    /// Update all brushes that have been modified.
+    pub fn update_brushes(&mut self, gpu: &GpuCtx) {
+        for brush in self.brushes.iter_mut().filter(|x| x.needs_update()) {
+            brush.update(gpu);
+        }
+    }
+
+    /// Node hierarchy of the scene.
+    pub fn nodes(&self) -> &NodeTree { &self.nodes }
+    pub fn nodes_mut(&mut self) -> &mut NodeTree { &mut self.nodes }
+
+    /// Add node as child of `parent` or as a root node.
+    pub fn add_node(&mut self, node: Node, parent: Option<NodeId>) -> NodeId {
+        self.nodes.add_node(node, parent)
+    }
+
+    /// Remove node and all its descendants.
+    pub fn remove_node(&mut self, id: NodeId) { self.nodes.remove_node(id) }
+
+    /// Move node under a new parent, or to the root when `None`. Local transform is kept.
+    pub fn set_parent(&mut self, id: NodeId, parent: Option<NodeId>) {
+        self.nodes.set_parent(id, parent)
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node { self.nodes.node(id) }
+    pub fn node_mut(&mut self, id: NodeId) -> &mut Node { self.nodes.node_mut(id) }
+
+    /// Change node transform relative to its parent.
+    pub fn set_transform(&mut self, id: NodeId, transform: Transform) {
+        self.nodes.set_transform(id, transform)
+    }
+
+    /// Matrix transforming from node space to world space.
+    pub fn world_matrix(&self, id: NodeId) -> Mat4 { self.nodes.world_matrix(id) }
+
+    /// Transform of the node in world space, combining the transforms of all its ancestors.
+    pub fn world_transform(&self, id: NodeId) -> Transform { self.nodes.world_transform(id) }
+
+    /// Nodes without parent.
+    pub fn roots(&self) -> &[NodeId] { self.nodes.roots() }
+
+    /// First node found with the given name.
+    pub fn find(&self, name: &str) -> Option<NodeId> { self.nodes.find(name) }
+
+    /// Iterate all nodes in creation order.
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, &Node)> { self.nodes.iter() }
+
+    /// Upload camera and model matrices of all drawable nodes, returning what to draw and the
+    /// dynamic offset of each node data. Uploads are done when called, so scenes should be drawn
+    /// once per frame.
+    pub(crate) fn prepare_draws(
+        &self, gpu: &GpuCtx, camera: &Camera, mut culler: Option<&mut FrustumCuller>,
+    ) -> Vec<(&Brush, &ShapeBuffer, u32)> {
+        let view_proj = camera.build_camera_matrix().to_cols_array();
+        let mut draws = self.draws.borrow_mut();
+        draws.begin_frame();
+        self.iter()
+            .filter_map(|(id, node)| Some((id, node.mesh?, node.brush?)))
+            .filter(|(id, mesh, _)| match culler.as_mut() {
+                Some(culler) => {
+                    culler.is_visible(self.meshes[mesh.0].aabb.as_ref(), &self.world_matrix(*id))
+                }
+                None => true,
+            })
+            .map(|(id, mesh, brush)| {
+                let brush = &self.brushes[brush.0];
+                assert!(
+                    !brush.needs_update(),
+                    "Scene brush was modified. Call Scene::update_brushes before drawing."
+                );
+                let offset = draws.push(
+                    gpu,
+                    &DrawUniform {
+                        view_proj,
+                        model: self.world_matrix(id).to_cols_array(),
+                    },
+                );
+                (brush, &self.meshes[mesh.0], offset)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,756 @@
+use std::{
+    collections::HashMap,
+    f32::consts::{FRAC_PI_2, PI, TAU},
+    ops::Range,
+};
+
+use glam::{Quat, UVec2, Vec2, Vec3};
+
+use super::mesh_utils::face_normal;
+use crate::{Color, Mesh, Rect, ShapeBatch, Vertex};
+
+/// Procedural shapes. Solids are built around the local Y axis and flat shapes lie in the local
+/// XY plane facing -Z, like [`ShapeBatch::add_2d_square`]. `orientation` rotates them around
+/// `center`.
+impl ShapeBatch {
+    ///Add a sphere made of `segments` meridians and `rings` parallels. U wraps around the Y axis
+    ///and V goes from the top to the bottom pole.
+    ///
+    /// ```
+    /// use glam::{Quat, Vec3};
+    /// use tridify_rs::{Color, Mesh, ShapeBatch};
+    ///
+    /// let center = Vec3::new(1.0, 2.0, 3.0);
+    /// let mut batch = ShapeBatch::new();
+    /// batch.add_uv_sphere(center, Quat::from_rotation_z(0.3), 2.0, 8, 6, Color::WHITE);
+    /// let mesh = Mesh::new(batch.vertices, batch.indices);
+    /// //Rings touching a pole have one triangle per segment, the others two.
+    /// assert_eq!(mesh.tris.len() / 3, 8 * 2 + 8 * 4 * 2);
+    /// for (i, tri) in mesh.tris.chunks_exact(3).enumerate() {
+    ///     let pos = tri.iter().map(|x| Vec3::from(mesh.vertices[*x as usize].pos));
+    ///     let centroid = pos.sum::<Vec3>();
+    ///     assert!(mesh.face_normal(i).dot(centroid / 3.0 - center) > 0.0);
+    /// }
+    /// for vertex in &mesh.vertices {
+    ///     let offset = Vec3::from(vertex.pos) - center;
+    ///     assert!((offset.length() - 2.0).abs() < 1e-5);
+    ///     assert!(Vec3::from(vertex.normal).abs_diff_eq(offset / 2.0, 1e-5));
+    ///     assert!(vertex.uv.iter().all(|x| (0.0..=1.0).contains(x)));
+    /// }
+    /// ```
+    pub fn add_uv_sphere(
+        &mut self, center: Vec3, orientation: Quat, radius: f32, segments: u32, rings: u32,
+        color: Color,
+    ) -> &mut ShapeBatch {
+        assert!(
+            segments >= 3 && rings >= 2,
+            "Sphere needs 3 segments and 2 rings."
+        );
+        let rows = (0..=rings)
+            .map(|j| {
+                let v = j as f32 / rings as f32;
+                let (sin, cos) = (v * PI).sin_cos();
+                LatheRow {
+                    radius: radius * sin,
+                    y: radius * cos,
+                    v,
+                    normal: Vec2::new(sin, cos),
+                }
+            })
+            .collect::<Vec<_>>();
+        self.add_oriented(lathe(&rows, segments, color), center, orientation)
+    }
+
+    ///Add a sphere made of evenly sized triangles by subdividing an icosahedron. Each
+    ///subdivision multiplies the triangle count by 4. UVs use the same mapping as
+    ///[`ShapeBatch::add_uv_sphere`].
+    ///
+    /// ```
+    /// use glam::{Quat, Vec3};
+    /// use tridify_rs::{Color, Mesh, ShapeBatch};
+    ///
+    /// let mut batch = ShapeBatch::new();
+    /// batch.add_icosphere(Vec3::ZERO, Quat::IDENTITY, 1.5, 2, Color::WHITE);
+    /// let mesh = Mesh::new(batch.vertices, batch.indices);
+    /// assert_eq!(mesh.tris.len() / 3, 20 * 4 * 4);
+    /// for (i, tri) in mesh.tris.chunks_exact(3).enumerate() {
+    ///     let pos = tri.iter().map(|x| Vec3::from(mesh.vertices[*x as usize].pos));
+    ///     assert!(mesh.face_normal(i).dot(pos.sum()) > 0.0);
+    ///     //Triangles on the seam use vertices past U 1 instead of wrapping around the texture.
+    ///     let us = tri.iter().map(|x| mesh.vertices[*x as usize].uv[0]);
+    ///     let (min, max) = us.fold((f32::MAX, f32::MIN), |(a, b), x| (a.min(x), b.max(x)));
+    ///     assert!(max - min < 0.5);
+    /// }
+    /// for vertex in &mesh.vertices {
+    ///     assert!((Vec3::from(vertex.pos).length() - 1.5).abs() < 1e-5);
+    ///     assert!((0.0..1.5).contains(&vertex.uv[0]) && (0.0..=1.0).contains(&vertex.uv[1]));
+    /// }
+    /// ```
+    pub fn add_icosphere(
+        &mut self, center: Vec3, orientation: Quat, radius: f32, subdivisions: u32, color: Color,
+    ) -> &mut ShapeBatch {
+        self.add_oriented(icosphere(radius, subdivisions, color), center, orientation)
+    }
+
+    ///Add a closed cylinder of the given height along the Y axis.
+    ///
+    /// ```
+    /// use std::f32::consts::FRAC_PI_2;
+    ///
+    /// use glam::{Quat, Vec3};
+    /// use tridify_rs::{Color, Mesh, ShapeBatch};
+    ///
+    /// let center = Vec3::new(0.0, 1.0, 0.0);
+    /// let mut batch = ShapeBatch::new();
+    /// batch.add_cylinder(center, Quat::from_rotation_x(FRAC_PI_2), 1.0, 4.0, 6, Color::WHITE);
+    /// let mesh = Mesh::new(batch.vertices, batch.indices);
+    /// //Two triangles per side and one per segment on each cap.
+    /// assert_eq!(mesh.tris.len() / 3, 6 * 2 + 6 * 2);
+    /// for (i, tri) in mesh.tris.chunks_exact(3).enumerate() {
+    ///     let pos = tri.iter().map(|x| Vec3::from(mesh.vertices[*x as usize].pos));
+    ///     let centroid = pos.sum::<Vec3>();
+    ///     assert!(mesh.face_normal(i).dot(centroid / 3.0 - center) > 0.0);
+    /// }
+    /// //The Y axis is rotated onto Z.
+    /// let z = mesh.vertices.iter().map(|x| x.pos[2]);
+    /// assert!((z.fold(f32::MIN, f32::max) - 2.0).abs() < 1e-6);
+    /// assert!(mesh.vertices.iter().flat_map(|x| x.uv).all(|x| (0.0..=1.0).contains(&x)));
+    /// ```
+    pub fn add_cylinder(
+        &mut self, center: Vec3, orientation: Quat, radius: f32, height: f32, segments: u32,
+        color: Color,
+    ) -> &mut ShapeBatch {
+        self.add_oriented(
+            frustum(radius, radius, height, segments, color),
+            center,
+            orientation,
+        )
+    }
+
+    ///Add a cone with its base at the bottom and its tip at the top of the Y axis.
+    ///
+    /// ```
+    /// use glam::{Quat, Vec3};
+    /// use tridify_rs::{Color, Mesh, ShapeBatch};
+    ///
+    /// let mut batch = ShapeBatch::new();
+    /// batch.add_cone(Vec3::ZERO, Quat::IDENTITY, 1.0, 2.0, 8, Color::WHITE);
+    /// let mesh = Mesh::new(batch.vertices, batch.indices);
+    /// //The tip has no cap and a single triangle per side.
+    /// assert_eq!(mesh.tris.len() / 3, 8 + 8);
+    /// for (i, tri) in mesh.tris.chunks_exact(3).enumerate() {
+    ///     let pos = tri.iter().map(|x| Vec3::from(mesh.vertices[*x as usize].pos));
+    ///     let centroid = pos.sum::<Vec3>();
+    ///     assert!(mesh.face_normal(i).dot(centroid) > 0.0);
+    /// }
+    /// //Side normals lean up by the slope of the cone.
+    /// let side = Vec3::from(mesh.vertices[9].normal);
+    /// assert!(side.abs_diff_eq(Vec3::new(2.0, 1.0, 0.0).normalize(), 1e-6));
+    /// ```
+    pub fn add_cone(
+        &mut self, center: Vec3, orientation: Quat, radius: f32, height: f32, segments: u32,
+        color: Color,
+    ) -> &mut ShapeBatch {
+        self.add_oriented(
+            frustum(radius, 0.0, height, segments, color),
+            center,
+            orientation,
+        )
+    }
+
+    ///Add a cylinder of the given height along the Y axis capped with hemispheres of `rings`
+    ///parallels each. Total height is `height + radius * 2`.
+    ///
+    /// ```
+    /// use glam::{Quat, Vec3};
+    /// use tridify_rs::{Color, Mesh, ShapeBatch};
+    ///
+    /// let mut batch = ShapeBatch::new();
+    /// batch.add_capsule(Vec3::ZERO, Quat::IDENTITY, 0.5, 2.0, 8, 4, Color::WHITE);
+    /// let mesh = Mesh::new(batch.vertices, batch.indices);
+    /// let y = mesh.vertices.iter().map(|x| x.pos[1]);
+    /// let (min, max) = y.fold((f32::MAX, f32::MIN), |(a, b), x| (a.min(x), b.max(x)));
+    /// assert_eq!((min, max), (-1.5, 1.5));
+    /// for (i, tri) in mesh.tris.chunks_exact(3).enumerate() {
+    ///     let pos = tri.iter().map(|x| Vec3::from(mesh.vertices[*x as usize].pos));
+    ///     let centroid = pos.sum::<Vec3>();
+    ///     assert!(mesh.face_normal(i).dot(centroid) > 0.0);
+    /// }
+    /// let v = mesh.vertices.iter().map(|x| x.uv[1]);
+    /// assert_eq!(v.fold((f32::MAX, f32::MIN), |(a, b), x| (a.min(x), b.max(x))), (0.0, 1.0));
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_capsule(
+        &mut self, center: Vec3, orientation: Quat, radius: f32, height: f32, segments: u32,
+        rings: u32, color: Color,
+    ) -> &mut ShapeBatch {
+        assert!(
+            segments >= 3 && rings >= 1,
+            "Capsule needs 3 segments and 1 ring."
+        );
+        //V follows the profile length so the texture is not stretched on the cylinder.
+        let length = PI * radius + height;
+        let row = |j: u32, y: f32, offset: f32| {
+            let angle = j as f32 / rings as f32 * FRAC_PI_2;
+            let (sin, cos) = angle.sin_cos();
+            LatheRow {
+                radius: radius * sin,
+                y: y + radius * cos,
+                v: (angle * radius + offset) / length,
+                normal: Vec2::new(sin, cos),
+            }
+        };
+        let rows = (0..=rings)
+            .map(|j| row(j, height / 2.0, 0.0))
+            .chain((rings..=rings * 2).map(|j| row(j, -height / 2.0, height)))
+            .collect::<Vec<_>>();
+        self.add_oriented(lathe(&rows, segments, color), center, orientation)
+    }
+
+    ///Add a torus around the Y axis. `major_radius` is the distance from the center to the
+    ///middle of the tube and `minor_radius` is the tube radius.
+    ///
+    /// ```
+    /// use glam::{Quat, Vec3};
+    /// use tridify_rs::{Color, Mesh, ShapeBatch};
+    ///
+    /// let mut batch = ShapeBatch::new();
+    /// batch.add_torus(Vec3::ZERO, Quat::IDENTITY, 2.0, 0.5, 12, 8, Color::WHITE);
+    /// let mesh = Mesh::new(batch.vertices, batch.indices);
+    /// assert_eq!(mesh.tris.len() / 3, 12 * 8 * 2);
+    /// for (i, tri) in mesh.tris.chunks_exact(3).enumerate() {
+    ///     //Normals point away from the middle of the tube, not from the center of the torus.
+    ///     let centroid =
+    ///         tri.iter().map(|x| Vec3::from(mesh.vertices[*x as usize].pos)).sum::<Vec3>() / 3.0;
+    ///     let tube = Vec3::new(centroid.x, 0.0, centroid.z).normalize() * 2.0;
+    ///     assert!(mesh.face_normal(i).dot(centroid - tube) > 0.0);
+    /// }
+    /// assert!(mesh.vertices.iter().flat_map(|x| x.uv).all(|x| (0.0..=1.0).contains(&x)));
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_torus(
+        &mut self, center: Vec3, orientation: Quat, major_radius: f32, minor_radius: f32,
+        major_segments: u32, minor_segments: u32, color: Color,
+    ) -> &mut ShapeBatch {
+        assert!(
+            major_segments >= 3 && minor_segments >= 3,
+            "Torus needs 3 segments on each circle."
+        );
+        let rows = (0..=minor_segments)
+            .map(|j| {
+                let v = j as f32 / minor_segments as f32;
+                let (sin, cos) = (v * TAU).sin_cos();
+                LatheRow {
+                    radius: major_radius + minor_radius * cos,
+                    y: -minor_radius * sin,
+                    v,
+                    normal: Vec2::new(cos, -sin),
+                }
+            })
+            .collect::<Vec<_>>();
+        self.add_oriented(lathe(&rows, major_segments, color), center, orientation)
+    }
+
+    ///Add a flat grid split in `subdivisions` cells on each axis.
+    ///
+    /// ```
+    /// use glam::{Quat, UVec2, Vec2, Vec3};
+    /// use tridify_rs::{Color, Mesh, ShapeBatch};
+    ///
+    /// let mut batch = ShapeBatch::new();
+    /// let size = Vec2::new(4.0, 2.0);
+    /// batch.add_plane(Vec3::Z, Quat::IDENTITY, size, UVec2::new(2, 3), Color::WHITE);
+    /// let mesh = Mesh::new(batch.vertices, batch.indices);
+    /// assert_eq!(mesh.vertices.len(), 3 * 4);
+    /// assert_eq!(mesh.tris.len() / 3, 2 * 3 * 2);
+    /// assert!((0..12).all(|i| mesh.face_normal(i) == Vec3::NEG_Z));
+    /// assert_eq!(mesh.vertices[0].pos, [-2.0, -1.0, 1.0]);
+    /// assert_eq!(mesh.vertices[0].uv, [0.0, 0.0]);
+    /// assert_eq!(mesh.vertices[11].pos, [2.0, 1.0, 1.0]);
+    /// assert_eq!(mesh.vertices[11].uv, [1.0, 1.0]);
+    /// ```
+    pub fn add_plane(
+        &mut self, center: Vec3, orientation: Quat, size: Vec2, subdivisions: UVec2, color: Color,
+    ) -> &mut ShapeBatch {
+        let cells = subdivisions.max(UVec2::ONE);
+        let bounds = Rect::new(-size / 2.0, size);
+        let mut mesh = Mesh::default();
+        for y in 0..=cells.y {
+            for x in 0..=cells.x {
+                let t = UVec2::new(x, y).as_vec2() / cells.as_vec2();
+                mesh.vertices
+                    .push(flat_vertex(bounds.pos + t * size, &bounds, color));
+            }
+        }
+        let stride = cells.x + 1;
+        for y in 0..cells.y {
+            for x in 0..cells.x {
+                let a = y * stride + x;
+                mesh.tris
+                    .extend([a, a + 1, a + stride, a + stride, a + 1, a + stride + 1]);
+            }
+        }
+        self.add_oriented(mesh, center, orientation)
+    }
+
+    ///Add a filled circle.
+    ///
+    /// ```
+    /// use glam::{Quat, Vec3};
+    /// use tridify_rs::{Color, Mesh, ShapeBatch};
+    ///
+    /// let mut batch = ShapeBatch::new();
+    /// batch.add_disc(Vec3::ZERO, Quat::IDENTITY, 2.0, 16, Color::WHITE);
+    /// let mesh = Mesh::new(batch.vertices, batch.indices);
+    /// assert_eq!(mesh.tris.len() / 3, 16);
+    /// assert!((0..16).all(|i| mesh.face_normal(i).abs_diff_eq(Vec3::NEG_Z, 1e-6)));
+    /// assert_eq!(mesh.vertices[0].uv, [0.5, 0.5]);
+    /// assert!(mesh.vertices.iter().flat_map(|x| x.uv).all(|x| (0.0..=1.0).contains(&x)));
+    /// ```
+    pub fn add_disc(
+        &mut self, center: Vec3, orientation: Quat, radius: f32, segments: u32, color: Color,
+    ) -> &mut ShapeBatch {
+        self.add_oriented(disc(radius, segments, color), center, orientation)
+    }
+
+    ///Add a section of a ring between two angles in radians, measured counterclockwise from the
+    ///X axis. An `inner_radius` of 0 creates a pie slice.
+    ///
+    /// ```
+    /// use std::f32::consts::{FRAC_PI_2, PI};
+    ///
+    /// use glam::{Quat, Vec3};
+    /// use tridify_rs::{Color, Mesh, ShapeBatch};
+    ///
+    /// //Reversed angles and pie slices still face -Z.
+    /// let mut batch = ShapeBatch::new();
+    /// batch.add_arc(Vec3::ZERO, Quat::IDENTITY, 0.0, 1.0, PI..FRAC_PI_2, 4, Color::WHITE);
+    /// let mesh = Mesh::new(batch.vertices, batch.indices);
+    /// assert_eq!(mesh.tris.len() / 3, 4);
+    /// assert!((0..4).all(|i| mesh.face_normal(i).abs_diff_eq(Vec3::NEG_Z, 1e-6)));
+    /// //A quarter in the top left corner covers the top left quarter of the UVs.
+    /// assert!(mesh.vertices.iter().all(|x| x.pos[0] <= 1e-6 && x.pos[1] >= -1e-6));
+    /// assert!(mesh.vertices.iter().all(|x| x.uv[0] <= 0.5 + 1e-6 && x.uv[1] >= 0.5 - 1e-6));
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_arc(
+        &mut self, center: Vec3, orientation: Quat, inner_radius: f32, outer_radius: f32,
+        angles: Range<f32>, segments: u32, color: Color,
+    ) -> &mut ShapeBatch {
+        assert!(segments >= 1, "Arc needs at least 1 segment.");
+        //Going counterclockwise keeps the triangles facing -Z.
+        let (start, end) = match angles.start <= angles.end {
+            true => (angles.start, angles.end),
+            false => (angles.end, angles.start),
+        };
+        let bounds = Rect::new(Vec2::splat(-outer_radius), Vec2::splat(outer_radius * 2.0));
+        let mut mesh = Mesh::default();
+        for i in 0..=segments {
+            let angle = start + (end - start) * i as f32 / segments as f32;
+            let dir = Vec2::from_angle(angle);
+            mesh.vertices
+                .push(flat_vertex(dir * inner_radius, &bounds, color));
+            mesh.vertices
+                .push(flat_vertex(dir * outer_radius, &bounds, color));
+        }
+        for i in 0..segments {
+            let (inner, outer) = (i * 2, i * 2 + 1);
+            mesh.tris.extend([inner, outer, outer + 2]);
+            if inner_radius > 0.0 {
+                mesh.tris.extend([inner, outer + 2, inner + 2]);
+            }
+        }
+        self.add_oriented(mesh, center, orientation)
+    }
+
+    ///Add a full ring between two radii.
+    ///
+    /// ```
+    /// use glam::{Quat, Vec3};
+    /// use tridify_rs::{Color, Mesh, ShapeBatch};
+    ///
+    /// let mut batch = ShapeBatch::new();
+    /// batch.add_ring(Vec3::ZERO, Quat::IDENTITY, 1.0, 2.0, 12, Color::WHITE);
+    /// let mesh = Mesh::new(batch.vertices, batch.indices);
+    /// assert_eq!(mesh.tris.len() / 3, 12 * 2);
+    /// assert!((0..24).all(|i| mesh.face_normal(i).abs_diff_eq(Vec3::NEG_Z, 1e-6)));
+    /// for vertex in &mesh.vertices {
+    ///     let radius = Vec3::from(vertex.pos).length();
+    ///     assert!((radius - 1.0).abs() < 1e-6 || (radius - 2.0).abs() < 1e-6);
+    /// }
+    /// ```
+    pub fn add_ring(
+        &mut self, center: Vec3, orientation: Quat, inner_radius: f32, outer_radius: f32,
+        segments: u32, color: Color,
+    ) -> &mut ShapeBatch {
+        self.add_arc(
+            center,
+            orientation,
+            inner_radius,
+            outer_radius,
+            0.0..TAU,
+            segments,
+            color,
+        )
+    }
+
+    ///Add a rectangle with its corners rounded by `corner_radius` using `corner_segments`
+    ///segments each.
+    ///
+    /// ```
+    /// use glam::{Quat, Vec2, Vec3};
+    /// use tridify_rs::{Color, Mesh, ShapeBatch};
+    ///
+    /// let mut batch = ShapeBatch::new();
+    /// let size = Vec2::new(4.0, 2.0);
+    /// batch.add_rounded_rect(Vec3::ZERO, Quat::IDENTITY, size, 0.5, 3, Color::WHITE);
+    /// let mesh = Mesh::new(batch.vertices, batch.indices);
+    /// //Four corners of 3 segments, each starting a new fan triangle for the straight edge.
+    /// assert_eq!(mesh.tris.len() / 3, 4 * 4);
+    /// assert!((0..16).all(|i| mesh.face_normal(i).abs_diff_eq(Vec3::NEG_Z, 1e-6)));
+    /// let bounds = (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN));
+    /// let (min, max) = mesh.vertices.iter().fold(bounds, |(a, b), x| {
+    ///     (a.min(x.pos.into()), b.max(x.pos.into()))
+    /// });
+    /// assert!(min.abs_diff_eq(Vec3::new(-2.0, -1.0, 0.0), 1e-6));
+    /// assert!(max.abs_diff_eq(Vec3::new(2.0, 1.0, 0.0), 1e-6));
+    /// assert!(mesh.vertices.iter().flat_map(|x| x.uv).all(|x| (-1e-6..=1.0 + 1e-6).contains(&x)));
+    /// ```
+    pub fn add_rounded_rect(
+        &mut self, center: Vec3, orientation: Quat, size: Vec2, corner_radius: f32,
+        corner_segments: u32, color: Color,
+    ) -> &mut ShapeBatch {
+        let half = size / 2.0;
+        let radius = corner_radius.clamp(0.0, half.min_element());
+        let segments = corner_segments.max(1);
+        let inner = half - radius;
+        let corners = [
+            Vec2::new(inner.x, -inner.y),
+            inner,
+            Vec2::new(-inner.x, inner.y),
+            -inner,
+        ];
+        let outline = corners
+            .iter()
+            .enumerate()
+            .flat_map(|(corner, center)| {
+                (0..=segments).map(move |i| {
+                    let angle = (corner as f32 - 1.0 + i as f32 / segments as f32) * FRAC_PI_2;
+                    *center + Vec2::from_angle(angle) * radius
+                })
+            })
+            .collect::<Vec<_>>();
+        let bounds = Rect::new(-half, size);
+        self.add_oriented(fan(&outline, &bounds, color), center, orientation)
+    }
+
+    ///Add a flat polygon from its outline points relative to `center`, in any winding order.
+    ///Concave polygons are supported as long as edges don't intersect.
+    ///
+    /// ```
+    /// use glam::{Quat, Vec2, Vec3};
+    /// use tridify_rs::{Color, Mesh, ShapeBatch};
+    ///
+    /// //A concave L shape of area 3, clockwise and counterclockwise.
+    /// let clockwise = [(0.0, 0.0), (0.0, 2.0), (1.0, 2.0), (1.0, 1.0), (2.0, 1.0), (2.0, 0.0)]
+    ///     .map(|(x, y)| Vec2::new(x, y));
+    /// let mut counterclockwise = clockwise;
+    /// counterclockwise.reverse();
+    /// for points in [clockwise, counterclockwise] {
+    ///     let mut batch = ShapeBatch::new();
+    ///     batch.add_polygon(Vec3::ZERO, Quat::IDENTITY, &points, Color::WHITE);
+    ///     let mesh = Mesh::new(batch.vertices, batch.indices);
+    ///     assert_eq!(mesh.tris.len() / 3, points.len() - 2);
+    ///     let mut area = 0.0;
+    ///     for (i, tri) in mesh.tris.chunks_exact(3).enumerate() {
+    ///         assert_eq!(mesh.face_normal(i), Vec3::NEG_Z);
+    ///         let [a, b, c] = [0, 1, 2].map(|x| Vec3::from(mesh.vertices[tri[x] as usize].pos));
+    ///         area += (b - a).cross(c - a).length() / 2.0;
+    ///     }
+    ///     //No triangle covers the notch, which would add to the area.
+    ///     assert_eq!(area, 3.0);
+    ///     //UVs map the bounds of the outline.
+    ///     assert!(mesh.vertices.iter().all(|x| x.uv == [x.pos[0] / 2.0, x.pos[1] / 2.0]));
+    /// }
+    /// ```
+    pub fn add_polygon(
+        &mut self, center: Vec3, orientation: Quat, points: &[Vec2], color: Color,
+    ) -> &mut ShapeBatch {
+        assert!(points.len() >= 3, "Polygon needs at least 3 points.");
+        let min = points
+            .iter()
+            .fold(Vec2::splat(f32::MAX), |acc, x| acc.min(*x));
+        let max = points
+            .iter()
+            .fold(Vec2::splat(f32::MIN), |acc, x| acc.max(*x));
+        let bounds = Rect::from_min_max(min, max);
+        let mesh = Mesh::new(
+            points
+                .iter()
+                .map(|x| flat_vertex(*x, &bounds, color))
+                .collect(),
+            triangulate_polygon(points),
+        );
+        self.add_oriented(mesh, center, orientation)
+    }
+
+    /// Add a mesh built around the origin, rotating it and moving it to `center`.
+    fn add_oriented(&mut self, mut mesh: Mesh, center: Vec3, orientation: Quat) -> &mut ShapeBatch {
+        for vertex in mesh.vertices.iter_mut() {
+            vertex.pos = (center + orientation * Vec3::from(vertex.pos)).to_array();
+            vertex.normal = (orientation * Vec3::from(vertex.normal)).to_array();
+        }
+        self.add_mesh(mesh)
+    }
+}
+
+/// Circle of vertices around the Y axis. `normal` is the surface direction in the radius (X) and
+/// height (Y) plane.
+struct LatheRow {
+    radius: f32,
+    y: f32,
+    v: f32,
+    normal: Vec2,
+}
+
+/// Revolve profile rows around the Y axis. Rows must go from top to bottom on the outer side of
+/// the shape for triangles to face outwards. Triangles between vertices of rows with no radius
+/// are skipped.
+fn lathe(rows: &[LatheRow], segments: u32, color: Color) -> Mesh {
+    let mut mesh = Mesh::default();
+    for row in rows {
+        for i in 0..=segments {
+            let u = i as f32 / segments as f32;
+            let (sin, cos) = (u * TAU).sin_cos();
+            let pos = Vec3::new(row.radius * cos, row.y, row.radius * sin);
+            let normal = Vec3::new(row.normal.x * cos, row.normal.y, row.normal.x * sin);
+            mesh.vertices
+                .push(Vertex::from_vec(pos, Some(color), Some([u, row.v])).with_normal(normal));
+        }
+    }
+    let stride = segments + 1;
+    for (j, pair) in rows.windows(2).enumerate() {
+        for i in 0..segments {
+            let top = j as u32 * stride + i;
+            let bottom = top + stride;
+            if pair[0].radius > 0.0 {
+                mesh.tris.extend([top, bottom, top + 1]);
+            }
+            if pair[1].radius > 0.0 {
+                mesh.tris.extend([top + 1, bottom, bottom + 1]);
+            }
+        }
+    }
+    mesh
+}
+
+/// Cylinder with different top and bottom radius. Caps with no radius are skipped.
+fn frustum(bottom: f32, top: f32, height: f32, segments: u32, color: Color) -> Mesh {
+    assert!(segments >= 3, "Needs at least 3 segments.");
+    let normal = Vec2::new(height, bottom - top).normalize();
+    let rows =
+        [(top, height / 2.0, 0.0), (bottom, -height / 2.0, 1.0)].map(|(radius, y, v)| LatheRow {
+            radius,
+            y,
+            v,
+            normal,
+        });
+    let mut mesh = lathe(&rows, segments, color);
+    //Discs face -Z, so they are rotated to face up and down.
+    let caps = [
+        (top, height / 2.0, Quat::from_rotation_x(FRAC_PI_2)),
+        (bottom, -height / 2.0, Quat::from_rotation_x(-FRAC_PI_2)),
+    ];
+    for (radius, y, rotation) in caps.into_iter().filter(|x| x.0 > 0.0) {
+        let mut cap = disc(radius, segments, color);
+        let offset = mesh.vertices.len() as u32;
+        mesh.vertices.extend(cap.vertices.iter_mut().map(|x| {
+            x.pos = (rotation * Vec3::from(x.pos) + Vec3::Y * y).to_array();
+            x.normal = (rotation * Vec3::from(x.normal)).to_array();
+            *x
+        }));
+        mesh.tris.extend(cap.tris.iter().map(|x| x + offset));
+    }
+    mesh
+}
+
+fn disc(radius: f32, segments: u32, color: Color) -> Mesh {
+    assert!(segments >= 3, "Disc needs at least 3 segments.");
+    let outline = (0..segments)
+        .map(|i| Vec2::from_angle(i as f32 / segments as f32 * TAU) * radius)
+        .collect::<Vec<_>>();
+    let bounds = Rect::new(Vec2::splat(-radius), Vec2::splat(radius * 2.0));
+    fan(&outline, &bounds, color)
+}
+
+/// Flat shape made of triangles from the origin to each edge of a counterclockwise outline.
+fn fan(outline: &[Vec2], bounds: &Rect, color: Color) -> Mesh {
+    let mut mesh = Mesh::default();
+    mesh.vertices.push(flat_vertex(Vec2::ZERO, bounds, color));
+    mesh.vertices
+        .extend(outline.iter().map(|x| flat_vertex(*x, bounds, color)));
+    let len = outline.len() as u32;
+    for i in 0..len {
+        mesh.tris.extend([0, i + 1, (i + 1) % len + 1]);
+    }
+    mesh
+}
+
+/// Vertex on the XY plane facing -Z. UVs map the bounds like [`ShapeBatch::add_2d_square`].
+fn flat_vertex(pos: Vec2, bounds: &Rect, color: Color) -> Vertex {
+    let uv = (pos - bounds.min()) / bounds.size;
+    Vertex::new(pos.x, pos.y, 0.0, Some(color), Some(uv.to_array())).with_normal(Vec3::NEG_Z)
+}
+
+fn icosphere(radius: f32, subdivisions: u32, color: Color) -> Mesh {
+    let t = (1.0 + 5f32.sqrt()) / 2.0;
+    let mut points = [
+        (-1.0, t, 0.0),
+        (1.0, t, 0.0),
+        (-1.0, -t, 0.0),
+        (1.0, -t, 0.0),
+        (0.0, -1.0, t),
+        (0.0, 1.0, t),
+        (0.0, -1.0, -t),
+        (0.0, 1.0, -t),
+        (t, 0.0, -1.0),
+        (t, 0.0, 1.0),
+        (-t, 0.0, -1.0),
+        (-t, 0.0, 1.0),
+    ]
+    .map(|(x, y, z)| Vec3::new(x, y, z).normalize())
+    .to_vec();
+    let mut faces: Vec<[u32; 3]> = vec![
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ];
+    for face in faces.iter_mut() {
+        let [a, b, c] = face.map(|x| points[x as usize]);
+        if face_normal([a, b, c]).dot(a + b + c) < 0.0 {
+            face.swap(1, 2);
+        }
+    }
+
+    for _ in 0..subdivisions {
+        let mut midpoints = HashMap::new();
+        let mut midpoint = |a: u32, b: u32| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                points.push((points[a as usize] + points[b as usize]).normalize());
+                points.len() as u32 - 1
+            })
+        };
+        faces = faces
+            .iter()
+            .flat_map(|&[a, b, c]| {
+                let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+                [[a, ab, ca], [ab, b, bc], [ca, bc, c], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    let vertices = points
+        .iter()
+        .map(|p| {
+            let uv = [
+                (p.z.atan2(p.x) / TAU).rem_euclid(1.0),
+                p.y.clamp(-1.0, 1.0).acos() / PI,
+            ];
+            Vertex::from_vec(*p * radius, Some(color), Some(uv)).with_normal(*p)
+        })
+        .collect();
+    let mut mesh = Mesh::new(vertices, Vec::new());
+    //Triangles crossing the U seam get their low U vertices duplicated past 1, and pole vertices
+    //get a U between the other two vertices of each triangle.
+    let is_pole = |mesh: &Mesh, i: u32| {
+        let normal = mesh.vertices[i as usize].normal;
+        normal[0].abs() < 1e-6 && normal[2].abs() < 1e-6
+    };
+    let mut wrapped = HashMap::new();
+    for mut face in faces {
+        let u = |mesh: &Mesh, i: u32| mesh.vertices[i as usize].uv[0];
+        let us = face
+            .iter()
+            .filter(|x| !is_pole(&mesh, **x))
+            .map(|x| u(&mesh, *x));
+        let (min, max) = us.fold((f32::MAX, f32::MIN), |(min, max), x| {
+            (min.min(x), max.max(x))
+        });
+        if max - min > 0.5 {
+            for i in face.iter_mut() {
+                if is_pole(&mesh, *i) || u(&mesh, *i) >= 0.5 {
+                    continue;
+                }
+                *i = *wrapped.entry(*i).or_insert_with(|| {
+                    let mut vertex = mesh.vertices[*i as usize];
+                    vertex.uv[0] += 1.0;
+                    mesh.vertices.push(vertex);
+                    mesh.vertices.len() as u32 - 1
+                });
+            }
+        }
+        for k in 0..3 {
+            if is_pole(&mesh, face[k]) {
+                let mut vertex = mesh.vertices[face[k] as usize];
+                vertex.uv[0] = (u(&mesh, face[(k + 1) % 3]) + u(&mesh, face[(k + 2) % 3])) / 2.0;
+                mesh.vertices.push(vertex);
+                face[k] = mesh.vertices.len() as u32 - 1;
+            }
+        }
+        mesh.tris.extend(face);
+    }
+    mesh
+}
+
+/// Ear clipping triangulation of a simple polygon. Triangles are counterclockwise regardless of
+/// the outline winding.
+fn triangulate_polygon(points: &[Vec2]) -> Vec<u32> {
+    let area: f32 = (0..points.len())
+        .map(|i| points[i].perp_dot(points[(i + 1) % points.len()]))
+        .sum();
+    let mut remaining = (0..points.len() as u32).collect::<Vec<_>>();
+    if area < 0.0 {
+        remaining.reverse();
+    }
+    let mut tris = Vec::new();
+    while remaining.len() > 3 {
+        let len = remaining.len();
+        let corner = |i: usize| [(i + len - 1) % len, i, (i + 1) % len].map(|x| remaining[x]);
+        let is_ear = |i: usize| {
+            let ids = corner(i);
+            let [a, b, c] = ids.map(|x| points[x as usize]);
+            if (b - a).perp_dot(c - b) <= 0.0 {
+                return false;
+            }
+            !remaining.iter().filter(|x| !ids.contains(x)).any(|x| {
+                let p = points[*x as usize];
+                (b - a).perp_dot(p - a) >= 0.0
+                    && (c - b).perp_dot(p - b) >= 0.0
+                    && (a - c).perp_dot(p - c) >= 0.0
+            })
+        };
+        //Self intersecting outlines have no ears left, so any corner is clipped.
+        let ear = (0..len).find(|x| is_ear(*x)).unwrap_or(0);
+        tris.extend(corner(ear));
+        remaining.remove(ear);
+    }
+    tris.extend(remaining);
+    tris
+}
@@ -0,0 +1,359 @@
+use std::collections::HashMap;
+
+use ab_glyph::{Font as _, GlyphId};
+use glam::{UVec2, Vec2, Vec3};
+use wgpu::TextureFormat;
+
+use crate::{
+    Color, Font, GpuCtx, Rect, ShapeBatch, ShelfPacker, TextLayout, Texture, TextureDesc,
+    TextureSize, TextureUsage, Transform, Vertex,
+};
+
+/// Size in pixels of new glyph atlases.
+const INITIAL_ATLAS_SIZE: u32 = 256;
+
+/// How glyphs are stored in a [`GlyphAtlas`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum GlyphMode {
+    /// Glyph coverage rasterized for each text size, crisp when drawn at the same size. Drawn
+    /// by the default [`crate::Canvas`] shader.
+    #[default]
+    Bitmap,
+    /// Signed distance to the glyph outline rasterized once at `size` pixels, storing distances
+    /// up to `spread` pixels. Stays crisp at any scale, useful for text in 3D space. Drawn with
+    /// [`crate::SDF_TEXT_SHADER_TEMPLATE`]. A size of 48 and a spread of 6 work well for most
+    /// fonts.
+    Sdf { size: f32, spread: f32 },
+}
+
+/// Glyph quad returned by [`GlyphAtlas::glyph_quads`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphQuad {
+    /// Area covered in pixels from the top left corner of the text, with Y pointing down.
+    pub rect: Rect,
+    /// Normalized UVs in the atlas texture.
+    pub uv: Rect,
+}
+
+/// Glyph stored in the atlas.
+#[derive(Debug, Clone, Copy)]
+struct AtlasGlyph {
+    /// Area in the atlas, in pixels.
+    pixel_rect: Rect,
+    /// Area relative to the pen position when rasterized, in pixels with Y pointing down.
+    bounds: Rect,
+    /// Text size the glyph was rasterized with.
+    size: f32,
+}
+
+/// Texture holding the glyphs of a font, rasterized when first used. The texture grows when
+/// full, so [`GlyphAtlas::texture`] needs to be bound again after adding text if it changed.
+///
+/// Glyphs are stored in the alpha channel of a white `Rgba8Unorm` texture.
+pub struct GlyphAtlas {
+    font: Font,
+    mode: GlyphMode,
+    texture: Texture,
+    packer: ShelfPacker,
+    /// Copy of the texture alpha channel, used to upload it again after growing.
+    pixels: Vec<u8>,
+    /// Glyphs by id and rasterized size. Glyphs without outline, like spaces, are `None`.
+    glyphs: HashMap<(u16, u32), Option<AtlasGlyph>>,
+}
+
+impl GlyphAtlas {
+    pub fn new(gpu: &GpuCtx, font: Font, mode: GlyphMode) -> Self {
+        let size = UVec2::splat(INITIAL_ATLAS_SIZE);
+        Self {
+            font,
+            mode,
+            texture: Self::create_texture(gpu, size),
+            packer: ShelfPacker::new(size, 1),
+            pixels: vec![0; (size.x * size.y) as usize],
+            glyphs: HashMap::new(),
+        }
+    }
+
+    pub fn font(&self) -> &Font { &self.font }
+    pub fn mode(&self) -> GlyphMode { self.mode }
+    pub fn size(&self) -> UVec2 { self.packer.size() }
+
+    /// Texture holding all glyphs. Cloning it is cheap and can be binded to brushes.
+    pub fn texture(&self) -> &Texture { &self.texture }
+
+    /// Rasterize the glyphs of the layout missing from the atlas.
+    pub fn cache_layout(&mut self, gpu: &GpuCtx, layout: &TextLayout) {
+        for glyph in layout.glyphs.iter() {
+            let key = self.key(glyph.id, layout.font_size);
+            if !self.glyphs.contains_key(&key) {
+                let entry = self.insert(gpu, glyph.id, f32::from_bits(key.1));
+                self.glyphs.insert(key, entry);
+            }
+        }
+    }
+
+    /// Quads drawing each glyph of the layout, rasterizing the missing ones.
+    pub fn glyph_quads(&mut self, gpu: &GpuCtx, layout: &TextLayout) -> Vec<GlyphQuad> {
+        //Cache first, the atlas size may change while inserting.
+        self.cache_layout(gpu, layout);
+        let atlas_size = self.size().as_vec2();
+        layout
+            .glyphs
+            .iter()
+            .filter_map(|glyph| {
+                let entry = self.glyphs[&self.key(glyph.id, layout.font_size)]?;
+                let scale = layout.font_size / entry.size;
+                Some(GlyphQuad {
+                    rect: Rect::new(
+                        glyph.pos + entry.bounds.pos * scale,
+                        entry.bounds.size * scale,
+                    ),
+                    uv: Rect::new(
+                        entry.pixel_rect.pos / atlas_size,
+                        entry.pixel_rect.size / atlas_size,
+                    ),
+                })
+            })
+            .collect()
+    }
+
+    /// Remove all glyphs. Pixels are not cleared but will be overwritten by new glyphs.
+    pub fn clear(&mut self) {
+        self.packer.clear();
+        self.glyphs.clear();
+    }
+
+    /// Glyph id and rasterized size bits. SDF glyphs are rasterized once for all sizes.
+    fn key(&self, id: u16, size: f32) -> (u16, u32) {
+        match self.mode {
+            GlyphMode::Bitmap => (id, size.to_bits()),
+            GlyphMode::Sdf { size: sdf_size, .. } => (id, sdf_size.to_bits()),
+        }
+    }
+
+    /// Rasterize a glyph and pack it into the atlas, growing it if needed.
+    fn insert(&mut self, gpu: &GpuCtx, id: u16, size: f32) -> Option<AtlasGlyph> {
+        let glyph = GlyphId(id).with_scale(size);
+        let outline = self.font.inner().outline_glyph(glyph)?;
+        let bounds = outline.px_bounds();
+        let padding = match self.mode {
+            GlyphMode::Bitmap => 0,
+            GlyphMode::Sdf { spread, .. } => spread.ceil() as u32,
+        };
+        if bounds.width() < 1.0 || bounds.height() < 1.0 {
+            return None;
+        }
+        let glyph_size = UVec2::new(bounds.width() as u32, bounds.height() as u32) + padding * 2;
+        let mut pixels = vec![0.0; (glyph_size.x * glyph_size.y) as usize];
+        outline.draw(|x, y, coverage| {
+            let i = (y + padding) * glyph_size.x + x + padding;
+            pixels[i as usize] = coverage;
+        });
+        let pixels = match self.mode {
+            GlyphMode::Bitmap => pixels.iter().map(|x| (x * 255.0).round() as u8).collect(),
+            GlyphMode::Sdf { spread, .. } => signed_distance_field(&pixels, glyph_size, spread),
+        };
+
+        let pos = loop {
+            if let Some(pos) = self.packer.pack(glyph_size) {
+                break pos;
+            }
+            if !self.grow(gpu) {
+                log::warn!("Glyph atlas is full, glyph {} won't be drawn.", id);
+                return None;
+            }
+        };
+        let atlas_width = self.size().x;
+        for (y, row) in pixels.chunks(glyph_size.x as usize).enumerate() {
+            let start = ((pos.y + y as u32) * atlas_width + pos.x) as usize;
+            self.pixels[start..start + row.len()].copy_from_slice(row);
+        }
+        self.texture.write_region_pixels(
+            gpu,
+            &to_rgba(&pixels),
+            pos.extend(0),
+            glyph_size.extend(1),
+        );
+        Some(AtlasGlyph {
+            pixel_rect: Rect::new(pos.as_vec2(), glyph_size.as_vec2()),
+            bounds: Rect::new(
+                Vec2::new(bounds.min.x, bounds.min.y) - padding as f32,
+                glyph_size.as_vec2(),
+            ),
+            size,
+        })
+    }
+
+    /// Double the atlas size keeping the glyphs in place. Returns false if the texture can't
+    /// be any bigger.
+    fn grow(&mut self, gpu: &GpuCtx) -> bool {
+        let max_size = gpu.device.limits().max_texture_dimension_2d;
+        let size = self.size();
+        if size.x >= max_size && size.y >= max_size {
+            return false;
+        }
+        let new_size = (size * 2).min(UVec2::splat(max_size));
+        let mut pixels = vec![0; (new_size.x * new_size.y) as usize];
+        for (y, row) in self.pixels.chunks(size.x as usize).enumerate() {
+            let start = y * new_size.x as usize;
+            pixels[start..start + row.len()].copy_from_slice(row);
+        }
+        self.pixels = pixels;
+        self.packer.grow(new_size);
+        self.texture = Self::create_texture(gpu, new_size);
+        self.texture.write_pixels(gpu, &to_rgba(&self.pixels));
+        true
+    }
+
+    fn create_texture(gpu: &GpuCtx, size: UVec2) -> Texture {
+        let desc = TextureDesc {
+            size: TextureSize::D2(size),
+            usage: TextureUsage::TEXTURE_BIND | TextureUsage::DESTINATION,
+            format: TextureFormat::Rgba8Unorm,
+            mip_level_count: 1,
+        };
+        Texture::new(gpu, desc, Some("Glyph atlas"))
+    }
+}
+
+impl ShapeBatch {
+    /// Add a quad for each glyph of the layout on axis XY, facing the camera when looking towards
+    /// +Z like [`ShapeBatch::add_2d_square`]. One unit is a pixel of the layout, with the top left
+    /// corner of the text at the origin, and the transform places and scales the text.
+    ///
+    /// UVs are only valid for the current [`GlyphAtlas::texture`], which may change when new
+    /// glyphs are added.
+    pub fn add_text(
+        &mut self, gpu: &GpuCtx, atlas: &mut GlyphAtlas, layout: &TextLayout,
+        transform: &Transform, color: Color,
+    ) -> &mut ShapeBatch {
+        let matrix = transform.build_matrix();
+        let normal = matrix.transform_vector3(-Vec3::Z).normalize_or_zero();
+        for quad in atlas.glyph_quads(gpu, layout) {
+            let (min, max) = (quad.rect.min(), quad.rect.max());
+            let (uv_min, uv_max) = (quad.uv.min(), quad.uv.max());
+            //Layout Y points down, the text is flipped to have Y up.
+            let corners = [
+                (Vec2::new(min.x, max.y), [uv_min.x, uv_max.y]),
+                (Vec2::new(max.x, max.y), [uv_max.x, uv_max.y]),
+                (Vec2::new(min.x, min.y), [uv_min.x, uv_min.y]),
+                (Vec2::new(max.x, min.y), [uv_max.x, uv_min.y]),
+            ];
+            for (pos, uv) in corners {
+                let pos = matrix.transform_point3(Vec3::new(pos.x, -pos.y, 0.0));
+                self.vertices
+                    .push(Vertex::from_vec(pos, Some(color), Some(uv)).with_normal(normal));
+            }
+            let index = self.index_id_counter;
+            self.indices
+                .extend([index, index + 1, index + 2, index + 2, index + 1, index + 3]);
+            self.index_id_counter += 4;
+        }
+        self
+    }
+}
+
+/// Expand alpha values into white RGBA pixels.
+fn to_rgba(alpha: &[u8]) -> Vec<u8> { alpha.iter().flat_map(|x| [255, 255, 255, *x]).collect() }
+
+/// Encode the distance to the edge of the coverage, 0.5 on the edge and higher inside. Distances
+/// are clamped to `spread` pixels. Coverage is stored by rows of `size.x` pixels, and pixels with
+/// a coverage of at least 0.5 are inside.
+///
+/// ```
+/// use glam::UVec2;
+/// use tridify_rs::signed_distance_field;
+///
+/// //Square of 4 pixels in the middle of an 8 pixel grid.
+/// let coverage = (0..64)
+///     .map(|i| ((2..6).contains(&(i % 8)) && (2..6).contains(&(i / 8))) as u8 as f32)
+///     .collect::<Vec<_>>();
+/// let sdf = signed_distance_field(&coverage, UVec2::splat(8), 2.0);
+/// //Pixels on each side of the edge are half a spread from 0.5, and the rest are clamped.
+/// assert_eq!(sdf[3 * 8..4 * 8], [0, 64, 191, 255, 255, 191, 64, 0]);
+/// assert_eq!(sdf[..8], [0; 8]);
+/// //Distances are euclidean, the pixel diagonal to the corner is further than the edges.
+/// assert_eq!(sdf[8 + 1], ((0.5 - 2f32.sqrt() / 4.0) * 255.0).round() as u8);
+/// assert_eq!(sdf[8 + 2], 64);
+///
+/// assert_eq!(signed_distance_field(&[0.0; 4], UVec2::splat(2), 2.0), [0; 4]);
+/// assert_eq!(signed_distance_field(&[1.0; 4], UVec2::splat(2), 2.0), [255; 4]);
+/// ```
+pub fn signed_distance_field(coverage: &[f32], size: UVec2, spread: f32) -> Vec<u8> {
+    let inside = coverage.iter().map(|x| *x >= 0.5).collect::<Vec<_>>();
+    let to_inside = squared_distance_transform(&inside, size);
+    let outside = inside.iter().map(|x| !x).collect::<Vec<_>>();
+    let to_outside = squared_distance_transform(&outside, size);
+    to_inside
+        .iter()
+        .zip(to_outside)
+        .map(|(to_inside, to_outside)| {
+            let distance = to_outside.sqrt() - to_inside.sqrt();
+            ((0.5 + distance / (2.0 * spread)).clamp(0.0, 1.0) * 255.0).round() as u8
+        })
+        .collect()
+}
+
+/// Squared distance from each pixel to the closest `true` pixel, using the separable algorithm
+/// from Felzenszwalb and Huttenlocher.
+fn squared_distance_transform(features: &[bool], size: UVec2) -> Vec<f32> {
+    let (w, h) = (size.x as usize, size.y as usize);
+    let mut grid = features
+        .iter()
+        .map(|x| if *x { 0.0 } else { f32::INFINITY })
+        .collect::<Vec<_>>();
+    let mut column = vec![0.0; h];
+    for x in 0..w {
+        for y in 0..h {
+            column[y] = grid[y * w + x];
+        }
+        let column = distance_transform_1d(&column);
+        for y in 0..h {
+            grid[y * w + x] = column[y];
+        }
+    }
+    for row in grid.chunks_mut(w.max(1)) {
+        let transformed = distance_transform_1d(row);
+        row.copy_from_slice(&transformed);
+    }
+    grid
+}
+
+/// Lower envelope of the parabolas rooted at each sample.
+fn distance_transform_1d(f: &[f32]) -> Vec<f32> {
+    let n = f.len();
+    let mut distances = vec![f32::INFINITY; n];
+    //Parabola roots and the boundaries between them.
+    let mut roots = Vec::with_capacity(n);
+    let mut bounds = Vec::with_capacity(n + 1);
+    for q in (0..n).filter(|q| f[*q].is_finite()) {
+        let intersection = |p: usize| {
+            ((f[q] + (q * q) as f32) - (f[p] + (p * p) as f32)) / (2.0 * (q as f32 - p as f32))
+        };
+        let mut s = f32::NEG_INFINITY;
+        while let Some(p) = roots.last().copied() {
+            s = intersection(p);
+            if s > *bounds.last().unwrap() {
+                break;
+            }
+            roots.pop();
+            bounds.pop();
+            s = f32::NEG_INFINITY;
+        }
+        roots.push(q);
+        bounds.push(s);
+    }
+    if roots.is_empty() {
+        return distances;
+    }
+    bounds.push(f32::INFINITY);
+    let mut k = 0;
+    for (q, distance) in distances.iter_mut().enumerate() {
+        while bounds[k + 1] < q as f32 {
+            k += 1;
+        }
+        let p = roots[k];
+        *distance = (q as f32 - p as f32).powi(2) + f[p];
+    }
+    distances
+}
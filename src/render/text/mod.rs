@@ -0,0 +1,244 @@
+mod glyph_atlas;
+
+pub use glyph_atlas::*;
+
+use std::{error::Error, fs, path::Path};
+
+use ab_glyph::{Font as _, FontArc, GlyphId, PxScale, ScaleFont};
+use glam::Vec2;
+
+/// WGSL source of a scene brush drawing text from a [`GlyphAtlas`] using [`GlyphMode::Sdf`].
+/// Bind the atlas texture to group 1, binding 0 and a sampler with linear filtering to group 1,
+/// binding 1.
+pub const SDF_TEXT_SHADER_TEMPLATE: &str = include_str!("sdf.wgsl");
+
+/// TrueType or OpenType font. Cloning it is cheap, font data is shared.
+#[derive(Clone)]
+pub struct Font {
+    inner: FontArc,
+}
+
+impl Font {
+    /// Parse a TTF or OTF font. Collections load their first font.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, Box<dyn Error>> {
+        let inner = FontArc::try_from_vec(data).map_err(|e| format!("Invalid font: {}", e))?;
+        Ok(Self { inner })
+    }
+
+    /// Load a TTF or OTF font file.
+    pub fn from_path(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let data = fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::from_bytes(data).map_err(|e| format!("{}: {}", path.display(), e).into())
+    }
+
+    /// Distance in pixels between the baselines of two lines with the given text size.
+    pub fn line_height(&self, size: f32) -> f32 {
+        let font = self.inner.as_scaled(PxScale::from(size));
+        font.height() + font.line_gap()
+    }
+
+    /// Place the glyphs of the text. Lines are broken at `\n` and, when the style has a max width,
+    /// at the last whitespace that fits. Words wider than the max width are broken anywhere.
+    ///
+    /// Positions are in pixels from the top left corner of the text, with Y pointing down.
+    ///
+    /// ```
+    /// use std::path::Path;
+    ///
+    /// use glam::Vec2;
+    /// use tridify_rs::{Font, TextAlign, TextLayout, TextStyle};
+    ///
+    /// //At size 10 the test font has an ascent of 8 and advances of 3 for spaces, 6 for A and V,
+    /// //8 for W and 5 for o. A and V are kerned by -1.
+    /// let path = concat!(env!("CARGO_MANIFEST_DIR"), "/src/render/text/test_font.ttf");
+    /// let font = Font::from_path(Path::new(path)).unwrap();
+    /// let style = |max_width, align| TextStyle {
+    ///     size: 10.0,
+    ///     max_width,
+    ///     align,
+    ///     ..Default::default()
+    /// };
+    /// let glyphs = |layout: &TextLayout| {
+    ///     let glyphs = layout.glyphs.iter();
+    ///     glyphs.map(|x| (x.character, x.pos.x, x.line)).collect::<Vec<_>>()
+    /// };
+    ///
+    /// let layout = font.layout("AVo", &style(None, TextAlign::Left));
+    /// assert_eq!(glyphs(&layout), [('A', 0.0, 0), ('V', 5.0, 0), ('o', 11.0, 0)]);
+    /// assert_eq!(layout.glyphs[0].pos.y, 8.0);
+    /// assert_eq!(layout.size, Vec2::new(16.0, 10.0));
+    ///
+    /// //Lines break after the last space that fits, which doesn't count towards their width.
+    /// let layout = font.layout("oo oo oo", &style(Some(24.0), TextAlign::Right));
+    /// assert_eq!(
+    ///     glyphs(&layout),
+    ///     [
+    ///         ('o', 1.0, 0), ('o', 6.0, 0), ('o', 14.0, 0), ('o', 19.0, 0),
+    ///         ('o', 14.0, 1), ('o', 19.0, 1),
+    ///     ]
+    /// );
+    /// assert_eq!(layout.size, Vec2::new(24.0, 20.0));
+    ///
+    /// //Words wider than the max width are split anywhere.
+    /// let layout = font.layout("WWWW", &style(Some(20.0), TextAlign::Left));
+    /// assert_eq!(
+    ///     glyphs(&layout),
+    ///     [('W', 0.0, 0), ('W', 8.0, 0), ('W', 0.0, 1), ('W', 8.0, 1)]
+    /// );
+    ///
+    /// //Without a max width lines are centered on the widest one.
+    /// let layout = font.layout("W \no", &style(None, TextAlign::Center));
+    /// assert_eq!(glyphs(&layout), [('W', 0.0, 0), ('o', 1.5, 1)]);
+    /// assert_eq!(layout.glyphs[1].pos.y, 18.0);
+    /// assert_eq!(layout.size, Vec2::new(8.0, 20.0));
+    /// ```
+    pub fn layout(&self, text: &str, style: &TextStyle) -> TextLayout {
+        let font = self.inner.as_scaled(PxScale::from(style.size));
+        let mut lines: Vec<Vec<LineGlyph>> = Vec::new();
+        for paragraph in text.split('\n') {
+            let mut line: Vec<LineGlyph> = Vec::new();
+            let mut pen = 0.0;
+            let mut previous = None;
+            //Index of the first glyph after the last whitespace of the line.
+            let mut break_at = None;
+            for character in paragraph.chars().filter(|c| *c != '\r') {
+                let id = font.glyph_id(character);
+                let advance = match character {
+                    '\t' => font.h_advance(font.glyph_id(' ')) * 4.0,
+                    _ => font.h_advance(id),
+                };
+                if let Some(previous) = previous {
+                    pen += font.kern(previous, id);
+                }
+                previous = Some(id);
+
+                let overflows = style.max_width.is_some_and(|max| pen + advance > max);
+                if overflows && !character.is_whitespace() && !line.is_empty() {
+                    let rest = line.split_off(break_at.unwrap_or(line.len()));
+                    lines.push(std::mem::replace(&mut line, rest));
+                    let shift = line.first().map_or(pen, |x| x.x);
+                    line.iter_mut().for_each(|x| x.x -= shift);
+                    pen -= shift;
+                    break_at = None;
+                }
+                line.push(LineGlyph {
+                    id,
+                    character,
+                    x: pen,
+                    advance,
+                });
+                pen += advance;
+                if character.is_whitespace() {
+                    break_at = Some(line.len());
+                }
+            }
+            lines.push(line);
+        }
+
+        //Trailing whitespace doesn't count towards the line width.
+        let widths = lines
+            .iter()
+            .map(|line| {
+                line.iter()
+                    .rev()
+                    .find(|x| !x.character.is_whitespace())
+                    .map_or(0.0, |x| x.x + x.advance)
+            })
+            .collect::<Vec<_>>();
+        let width = style
+            .max_width
+            .unwrap_or_else(|| widths.iter().copied().fold(0.0, f32::max));
+        let line_advance = (font.height() + font.line_gap()) * style.line_spacing;
+        let mut glyphs = Vec::new();
+        for (i, line) in lines.iter().enumerate() {
+            let offset = match style.align {
+                TextAlign::Left => 0.0,
+                TextAlign::Center => (width - widths[i]) / 2.0,
+                TextAlign::Right => width - widths[i],
+            };
+            let baseline = font.ascent() + line_advance * i as f32;
+            glyphs.extend(
+                line.iter()
+                    .filter(|x| !x.character.is_whitespace())
+                    .map(|x| LayoutGlyph {
+                        id: x.id.0,
+                        character: x.character,
+                        pos: Vec2::new(offset + x.x, baseline),
+                        line: i,
+                    }),
+            );
+        }
+        TextLayout {
+            glyphs,
+            size: Vec2::new(
+                width,
+                line_advance * (lines.len() - 1) as f32 + font.height(),
+            ),
+            font_size: style.size,
+        }
+    }
+
+    pub(crate) fn inner(&self) -> &FontArc { &self.inner }
+}
+
+/// Horizontal alignment of each line of text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    /// Align to the max width, or to the widest line if there is no max width.
+    Right,
+}
+
+/// How text is placed by [`Font::layout`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextStyle {
+    /// Height in pixels from the lowest descender to the highest ascender.
+    pub size: f32,
+    /// Factor applied to the font line height.
+    pub line_spacing: f32,
+    /// Width in pixels where lines are broken. `None` only breaks lines at `\n`.
+    pub max_width: Option<f32>,
+    pub align: TextAlign,
+}
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self {
+            size: 16.0,
+            line_spacing: 1.0,
+            max_width: None,
+            align: TextAlign::Left,
+        }
+    }
+}
+
+/// Glyph placed by [`Font::layout`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayoutGlyph {
+    /// Glyph index in the font.
+    pub id: u16,
+    pub character: char,
+    /// Pen position on the baseline, in pixels from the top left corner of the text.
+    pub pos: Vec2,
+    /// Line the glyph is in, starting at 0.
+    pub line: usize,
+}
+
+/// Text placed by [`Font::layout`]. Whitespace is not included in the glyphs.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TextLayout {
+    pub glyphs: Vec<LayoutGlyph>,
+    /// Size in pixels of the area covered by the text lines.
+    pub size: Vec2,
+    /// Text size the layout was made with.
+    pub font_size: f32,
+}
+
+/// Glyph of a line being laid out.
+struct LineGlyph {
+    id: GlyphId,
+    character: char,
+    x: f32,
+    advance: f32,
+}
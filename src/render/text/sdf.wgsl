@@ -0,0 +1,42 @@
+// Scene brush drawing text from a glyph atlas storing signed distance fields. Scenes bind the
+// camera and node matrices to group 0, binding 0. The atlas texture and its sampler are bound to
+// group 1, bindings 0 and 1.
+
+struct Draw {
+    view_proj: mat4x4<f32>,
+    model: mat4x4<f32>,
+};
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) uv: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) uv: vec2<f32>,
+};
+
+@group(0) @binding(0) var<uniform> u_draw: Draw;
+@group(1) @binding(0) var t_atlas: texture_2d<f32>;
+@group(1) @binding(1) var s_atlas: sampler;
+
+@vertex
+fn vs_main(model: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = u_draw.view_proj * u_draw.model * vec4<f32>(model.position, 1.0);
+    out.color = model.color;
+    out.uv = model.uv;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Distance is 0.5 on the glyph edge, smoothed over a screen pixel.
+    let distance = textureSample(t_atlas, s_atlas, in.uv).a;
+    let width = max(fwidth(distance) * 0.7, 0.0001);
+    let alpha = smoothstep(0.5 - width, 0.5 + width, distance);
+    return vec4<f32>(in.color.rgb, in.color.a * alpha);
+}
@@ -1,10 +1,11 @@
-use std::{num::NonZeroU32, path::Path, rc::Rc};
+use std::{error::Error, fs::File, io::BufReader, path::Path, rc::Rc};
 
-use egui::Vec2;
 use glam::{UVec2, UVec3};
+use half::f16;
+use image::{codecs::hdr::HdrDecoder, DynamicImage, ImageFormat, Rgb32FImage};
 use wgpu::{
-    ImageCopyTexture, ImageDataLayout, ShaderStages, TextureAspect, TextureDescriptor,
-    TextureFormat, TextureUsages, TextureViewDescriptor,
+    ImageCopyTexture, ImageDataLayout, ShaderStages, StorageTextureAccess, TextureAspect,
+    TextureDescriptor, TextureFormat, TextureUsages, TextureViewDescriptor,
 };
 
 use crate::{GpuCtx, ToBinder};
@@ -20,11 +21,15 @@ bitflags::bitflags! {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum TextureSize {
     D1(u32),
     D2(UVec2),
     D3(UVec3),
+    /// 2D texture with multiple layers. Z is used as the layer count.
+    D2Array(UVec3),
+    /// 2D texture with 6 layers, one for each face of the cube.
+    Cube(UVec2),
 }
 impl TextureSize {
     pub fn get_size(&self) -> UVec3 {
@@ -32,12 +37,16 @@ impl TextureSize {
             TextureSize::D1(x) => UVec3::new(*x, 1, 1),
             TextureSize::D2(size) => UVec3::new(size.x, size.y, 1),
             TextureSize::D3(size) => UVec3::new(size.x, size.y, size.z),
+            TextureSize::D2Array(size) => UVec3::new(size.x, size.y, size.z),
+            TextureSize::Cube(size) => UVec3::new(size.x, size.y, 6),
         }
     }
     pub fn get_wgpu_dimension(&self) -> wgpu::TextureDimension {
         match self {
             TextureSize::D1(_) => wgpu::TextureDimension::D1,
-            TextureSize::D2(_) => wgpu::TextureDimension::D2,
+            TextureSize::D2(_) | TextureSize::D2Array(_) | TextureSize::Cube(_) => {
+                wgpu::TextureDimension::D2
+            }
             TextureSize::D3(_) => wgpu::TextureDimension::D3,
         }
     }
@@ -46,16 +55,52 @@ impl TextureSize {
             TextureSize::D1(_) => wgpu::TextureViewDimension::D1,
             TextureSize::D2(_) => wgpu::TextureViewDimension::D2,
             TextureSize::D3(_) => wgpu::TextureViewDimension::D3,
+            TextureSize::D2Array(_) => wgpu::TextureViewDimension::D2Array,
+            TextureSize::Cube(_) => wgpu::TextureViewDimension::Cube,
+        }
+    }
+    /// Size of the given mip level. Array layers are not affected by mip levels.
+    pub fn get_mip_size(&self, mip_level: u32) -> UVec3 {
+        let size = self.get_size();
+        let mip = |x: u32| (x >> mip_level).max(1);
+        match self {
+            TextureSize::D3(_) => UVec3::new(mip(size.x), mip(size.y), mip(size.z)),
+            _ => UVec3::new(mip(size.x), mip(size.y), size.z),
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TextureDesc {
     pub size: TextureSize,
     pub usage: TextureUsage,
+    /// Pixel format stored in the GPU. Use `Rgba8UnormSrgb` for regular color textures and float
+    /// formats to keep HDR data.
+    pub format: TextureFormat,
+    pub mip_level_count: u32,
 }
 impl TextureDesc {
+    /// Round size up to whole compression blocks. Uncompressed formats are not modified.
+    fn get_physical_size(&self, size: UVec3) -> UVec3 {
+        let (bw, bh) = self.format.block_dimensions();
+        UVec3::new(size.x.div_ceil(bw) * bw, size.y.div_ceil(bh) * bh, size.z)
+    }
+
+    /// Memory layout of a region of pixels with the given size. Compressed formats are laid out
+    /// in rows of blocks instead of rows of pixels.
+    fn get_data_layout(&self, size: UVec3) -> ImageDataLayout {
+        let (bw, bh) = self.format.block_dimensions();
+        let block_size = self
+            .format
+            .block_size(None)
+            .expect("Texture format does not have a single block size.");
+        ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(size.x.div_ceil(bw) * block_size),
+            rows_per_image: Some(size.y.div_ceil(bh)),
+        }
+    }
+
     fn get_wgpu_usage(&self) -> TextureUsages {
         let mut usage = TextureUsages::empty();
         if self.usage.contains(TextureUsage::DESTINATION) {
@@ -82,20 +127,121 @@ impl TextureDesc {
 pub struct Texture {
     pub desc: TextureDesc,
     texture: Rc<wgpu::Texture>,
-    view: wgpu::TextureView,
+    view: Rc<wgpu::TextureView>,
 }
 
 impl Texture {
+    /// Load texture from an image file. HDR, EXR and 16 bit images are loaded into `Rgba16Float`
+    /// textures to keep their dynamic range while still being filterable by a [`crate::Sampler`].
+    /// Any other image is loaded as `Rgba8UnormSrgb`. Like 8 bit images, the color of 16 bit
+    /// images is treated as sRGB and converted to linear.
     pub fn from_path(gpu: &GpuCtx, path: &Path) -> Self {
         let image =
-            image::open(path).unwrap_or_else(|_| panic!("Error loading image at {:?}", path));
+            Self::load_image(path).unwrap_or_else(|_| panic!("Error loading image at {:?}", path));
+        Self::from_image(gpu, &image)
+    }
+
+    /// Decode an image file. Unlike `image::open`, HDR images are decoded into floats instead of
+    /// being tone mapped to 8 bits.
+    pub fn load_image(path: &Path) -> Result<DynamicImage, Box<dyn Error>> {
+        if ImageFormat::from_path(path)? != ImageFormat::Hdr {
+            return Ok(image::open(path)?);
+        }
+        let decoder = HdrDecoder::new(BufReader::new(File::open(path)?))?;
+        let meta = decoder.metadata();
+        let pixels = decoder
+            .read_image_hdr()?
+            .into_iter()
+            .flat_map(|x| x.0)
+            .collect::<Vec<_>>();
+        let image = Rgb32FImage::from_raw(meta.width, meta.height, pixels)
+            .ok_or("HDR image data does not match its size.")?;
+        Ok(DynamicImage::ImageRgb32F(image))
+    }
+
+    /// Create texture from an already decoded image. See [`Texture::from_path`] for the formats
+    /// used.
+    pub fn from_image(gpu: &GpuCtx, image: &DynamicImage) -> Self {
+        let (format, data) = Self::image_to_pixels(image);
         let desc = TextureDesc {
             size: TextureSize::D2(UVec2::new(image.width(), image.height())),
             usage: TextureUsage::TEXTURE_BIND | TextureUsage::DESTINATION,
+            format,
+            mip_level_count: 1,
         };
-        let texture = Self::new(gpu, desc, None);
-        texture.write_pixels(gpu, &image.to_rgba8());
-        texture
+        Self::init(gpu, desc, &data, None)
+    }
+
+    /// Convert image into raw pixel data, choosing the texture format that keeps its precision.
+    ///
+    /// ```
+    /// use image::{codecs::hdr::HdrEncoder, Rgb};
+    /// use tridify_rs::Texture;
+    /// use wgpu::{TextureFormat, TextureSampleType};
+    ///
+    /// let path = std::env::temp_dir().join("tridify_image_to_pixels.hdr");
+    /// let file = std::fs::File::create(&path).unwrap();
+    /// HdrEncoder::new(file)
+    ///     .encode(&[Rgb([0.5, 2.0, 40.0]); 4], 2, 2)
+    ///     .unwrap();
+    /// let image = Texture::load_image(&path).unwrap();
+    ///
+    /// let (format, data) = Texture::image_to_pixels(&image);
+    /// assert_eq!(format, TextureFormat::Rgba16Float);
+    /// assert_eq!(data.len(), 2 * 2 * 8);
+    /// //Values above 1 are kept.
+    /// let pixel: &[half::f16] = bytemuck::cast_slice(&data[..8]);
+    /// assert_eq!(pixel[2].to_f32(), 40.0);
+    /// //Can be bound together with a filtering sampler like `Sampler::new_default`.
+    /// assert_eq!(
+    ///     format.sample_type(None),
+    ///     Some(TextureSampleType::Float { filterable: true })
+    /// );
+    ///
+    /// //16 bit PNGs are sRGB encoded like 8 bit ones, only the alpha channel is linear.
+    /// let image = image::ImageBuffer::from_pixel(1, 1, image::Rgba([u16::MAX / 2; 4]));
+    /// let (format, data) = Texture::image_to_pixels(&image.into());
+    /// assert_eq!(format, TextureFormat::Rgba16Float);
+    /// let pixel: &[half::f16] = bytemuck::cast_slice(&data);
+    /// assert!((pixel[0].to_f32() - 0.214).abs() < 1e-3);
+    /// assert!((pixel[3].to_f32() - 0.5).abs() < 1e-3);
+    /// ```
+    pub fn image_to_pixels(image: &DynamicImage) -> (TextureFormat, Vec<u8>) {
+        let to_f16 = |pixels: &[f32]| {
+            let pixels = pixels.iter().map(|x| f16::from_f32(*x)).collect::<Vec<_>>();
+            (
+                TextureFormat::Rgba16Float,
+                bytemuck::cast_slice(&pixels).to_vec(),
+            )
+        };
+        match image {
+            //Rgba32Float can't be filtered, so float images are stored with half precision.
+            DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) => {
+                to_f16(image.to_rgba32f().as_raw())
+            }
+            DynamicImage::ImageLuma16(_)
+            | DynamicImage::ImageLumaA16(_)
+            | DynamicImage::ImageRgb16(_)
+            | DynamicImage::ImageRgba16(_) => {
+                //Float formats are sampled as is, so color channels are decoded from sRGB here
+                //like the 8 bit `Rgba8UnormSrgb` does on the GPU. Alpha is always linear.
+                let pixels = image
+                    .to_rgba16()
+                    .as_raw()
+                    .iter()
+                    .enumerate()
+                    .map(|(i, x)| {
+                        let value = *x as f32 / u16::MAX as f32;
+                        match i % 4 {
+                            3 => value,
+                            _ => srgb_to_linear(value),
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                to_f16(&pixels)
+            }
+            _ => (TextureFormat::Rgba8UnormSrgb, image.to_rgba8().into_raw()),
+        }
     }
 
     pub fn init(gpu: &GpuCtx, desc: TextureDesc, data: &[u8], label: Option<&str>) -> Self {
@@ -113,37 +259,69 @@ impl Texture {
                 height: size.y,
                 depth_or_array_layers: size.z,
             },
-            mip_level_count: 1,
+            mip_level_count: desc.mip_level_count,
             sample_count: 1,
             dimension: desc.size.get_wgpu_dimension(),
-            format: TextureFormat::Rgba8UnormSrgb,
+            format: desc.format,
             usage: desc.get_wgpu_usage(),
-            view_formats: &[TextureFormat::Rgba8UnormSrgb],
+            view_formats: &[desc.format],
+        });
+        let view = texture.create_view(&TextureViewDescriptor {
+            dimension: Some(desc.size.get_wgpu_view_dimension()),
+            ..Default::default()
         });
-        let view = texture.create_view(&TextureViewDescriptor::default());
         Self {
             desc,
             texture: Rc::new(texture),
-            view,
+            view: Rc::new(view),
         }
     }
 
+    pub(crate) fn view(&self) -> &wgpu::TextureView { &self.view }
+
+    /// Identifier shared by all clones of the same texture.
+    pub(crate) fn id(&self) -> usize { Rc::as_ptr(&self.texture) as usize }
+
     ///Queues a write into the texture
-    pub fn write_pixels(&self, gpu: &GpuCtx, data: &[u8]) {
-        let size = self.desc.size.get_size();
+    pub fn write_pixels(&self, gpu: &GpuCtx, data: &[u8]) { self.write_mip_pixels(gpu, data, 0); }
+
+    ///Queues a write into a mip level of the texture. Data must contain all layers of the level.
+    pub fn write_mip_pixels(&self, gpu: &GpuCtx, data: &[u8], mip_level: u32) {
+        let size = self.desc.size.get_mip_size(mip_level);
+        let physical_size = self.desc.get_physical_size(size);
         gpu.queue.write_texture(
             ImageCopyTexture {
                 texture: &self.texture,
-                mip_level: 0,
+                mip_level,
                 origin: wgpu::Origin3d::ZERO,
                 aspect: TextureAspect::All,
             },
             data,
-            ImageDataLayout {
-                offset: 0,
-                bytes_per_row: Some(size.x * 4),
-                rows_per_image: Some(size.y),
+            self.desc.get_data_layout(size),
+            wgpu::Extent3d {
+                width: physical_size.x,
+                height: physical_size.y,
+                depth_or_array_layers: physical_size.z,
             },
+        );
+    }
+
+    ///Queues a write into the texture updating only a subset of it. Data must contain exactly the
+    ///pixels of the region.
+    pub fn write_region_pixels(&self, gpu: &GpuCtx, data: &[u8], origin: UVec3, size: UVec3) {
+        gpu.queue.write_texture(
+            ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: origin.x,
+                    y: origin.y,
+                    z: origin.z,
+                },
+                aspect: TextureAspect::All,
+            },
+            data,
+            self.desc.get_data_layout(size),
             wgpu::Extent3d {
                 width: size.x,
                 height: size.y,
@@ -151,11 +329,34 @@ impl Texture {
             },
         );
     }
+}
 
-    ///Queues a write into the texture updating only a subset of it
-    pub fn write_region_pixels(&self, gpu: &GpuCtx, data: &[u8], origin: UVec3) {
-        let size = self.desc.size.get_size();
-        gpu.queue.write_texture(
+impl Texture {
+    /// Copy a region of the first mip level back to the CPU, waiting for the GPU to finish all
+    /// submitted work. Only uncompressed formats can be read and the texture needs `SOURCE` usage.
+    ///
+    /// Commands recorded in a [`crate::RenderPassBuilder`] are only visible after
+    /// [`crate::RenderPassBuilder::finish_render`].
+    pub fn read_pixels(&self, gpu: &GpuCtx, origin: UVec3, size: UVec3) -> Vec<u8> {
+        assert!(
+            self.desc.usage.contains(TextureUsage::SOURCE),
+            "Texture needs SOURCE usage to be read."
+        );
+        let layout = self.desc.get_data_layout(size);
+        let row_size = layout.bytes_per_row.unwrap();
+        //Rows copied into buffers need to be aligned.
+        let padded_row_size = row_size.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+            * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Texture readback"),
+            size: padded_row_size as u64 * size.y as u64 * size.z as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = gpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        encoder.copy_texture_to_buffer(
             ImageCopyTexture {
                 texture: &self.texture,
                 mip_level: 0,
@@ -166,11 +367,12 @@ impl Texture {
                 },
                 aspect: TextureAspect::All,
             },
-            data,
-            ImageDataLayout {
-                offset: 0,
-                bytes_per_row: Some(size.x * 4),
-                rows_per_image: Some(size.y),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: ImageDataLayout {
+                    bytes_per_row: Some(padded_row_size),
+                    ..layout
+                },
             },
             wgpu::Extent3d {
                 width: size.x,
@@ -178,6 +380,23 @@ impl Texture {
                 depth_or_array_layers: size.z,
             },
         );
+        gpu.queue.submit(Some(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        gpu.device.poll(wgpu::Maintain::Wait);
+        receiver
+            .recv()
+            .expect("Texture readback was not completed.")
+            .expect("Error mapping texture readback buffer.");
+        let data = slice.get_mapped_range();
+        data.chunks_exact(padded_row_size as usize)
+            .flat_map(|row| &row[..row_size as usize])
+            .copied()
+            .collect()
     }
 }
 
@@ -187,7 +406,11 @@ impl ToBinder for Texture {
             binding: index,
             visibility: ShaderStages::FRAGMENT,
             ty: wgpu::BindingType::Texture {
-                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                sample_type: self
+                    .desc
+                    .format
+                    .sample_type(None)
+                    .expect("Texture format can't be sampled."),
                 view_dimension: self.desc.size.get_wgpu_view_dimension(),
                 multisampled: false,
             },
@@ -195,7 +418,7 @@ impl ToBinder for Texture {
         }
     }
 
-    fn get_group(&self, index: u32) -> wgpu::BindGroupEntry {
+    fn get_group(&self, index: u32) -> wgpu::BindGroupEntry<'_> {
         wgpu::BindGroupEntry {
             binding: index,
             resource: wgpu::BindingResource::TextureView(&self.view),
@@ -204,3 +427,61 @@ impl ToBinder for Texture {
 
     fn debug_name(&self) -> &'static str { "Texture" }
 }
+
+/// Texture binded as a storage texture, allowing shaders to write into it. Created with
+/// [`Texture::as_storage`]. The texture requires `STORAGE_BIND` usage and a single mip level.
+pub struct StorageTexture {
+    texture: Texture,
+    access: StorageTextureAccess,
+}
+
+impl Texture {
+    /// Bind texture as a storage texture with the given access instead of as a sampled texture.
+    pub fn as_storage(&self, access: StorageTextureAccess) -> StorageTexture {
+        assert!(
+            self.desc.usage.contains(TextureUsage::STORAGE_BIND),
+            "Texture needs STORAGE_BIND usage to be binded as storage."
+        );
+        StorageTexture {
+            texture: self.clone(),
+            access,
+        }
+    }
+}
+
+impl ToBinder for StorageTexture {
+    fn get_layout(&self, index: u32) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding: index,
+            visibility: ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::StorageTexture {
+                access: self.access,
+                format: self.texture.desc.format,
+                view_dimension: self.texture.desc.size.get_wgpu_view_dimension(),
+            },
+            count: None,
+        }
+    }
+
+    fn get_group(&self, index: u32) -> wgpu::BindGroupEntry<'_> { self.texture.get_group(index) }
+
+    fn debug_name(&self) -> &'static str { "Storage Texture" }
+}
+
+impl Clone for Texture {
+    fn clone(&self) -> Self {
+        Self {
+            desc: self.desc.clone(),
+            texture: Rc::clone(&self.texture),
+            view: Rc::clone(&self.view),
+        }
+    }
+}
+
+/// Decode an sRGB encoded channel in the 0 to 1 range.
+fn srgb_to_linear(value: f32) -> f32 {
+    match value <= 0.04045 {
+        true => value / 12.92,
+        false => ((value + 0.055) / 1.055).powf(2.4),
+    }
+}
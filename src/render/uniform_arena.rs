@@ -0,0 +1,114 @@
+use std::{marker::PhantomData, mem::size_of, num::NonZeroU64, rc::Rc};
+
+use bytemuck::Pod;
+use wgpu::{Buffer, ShaderStages};
+
+use crate::{GpuCtx, ToBinder};
+
+/// Uniform buffer split into slots that are binded using dynamic offsets. Allows uploading
+/// different data for each draw (like model matrices) while using a single brush and bind group.
+///
+/// Slots are allocated as a ring: each [`UniformArena::push`] returns the offset to use when
+/// rendering and [`UniformArena::begin_frame`] must be called once per frame. All data pushed in a
+/// frame must fit in the arena capacity.
+pub struct UniformArena<T: Pod> {
+    buffer: Rc<Buffer>,
+    visibility: ShaderStages,
+    stride: u64,
+    capacity: u64,
+    head: u64,
+    frame_len: u64,
+    _data: PhantomData<T>,
+}
+
+impl<T: Pod> UniformArena<T> {
+    /// Create arena with space for `capacity` elements per frame.
+    pub fn new(gpu: &GpuCtx, capacity: u64) -> Self {
+        let align = gpu.device.limits().min_uniform_buffer_offset_alignment as u64;
+        let stride = (size_of::<T>() as u64).div_ceil(align) * align;
+        let buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Uniform arena"),
+            size: stride * capacity,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Self {
+            buffer: Rc::new(buffer),
+            visibility: ShaderStages::VERTEX_FRAGMENT,
+            stride,
+            capacity,
+            head: 0,
+            frame_len: 0,
+            _data: PhantomData,
+        }
+    }
+
+    /// Shader stages that can access the arena when binded.
+    pub fn with_visibility(mut self, visibility: ShaderStages) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
+    /// Start allocating slots for a new frame.
+    pub fn begin_frame(&mut self) { self.frame_len = 0; }
+
+    /// Upload value into the next slot and return its dynamic offset in bytes.
+    pub fn push(&mut self, gpu: &GpuCtx, value: &T) -> u32 {
+        assert!(
+            self.frame_len < self.capacity,
+            "Uniform arena is full. Increase its capacity or call begin_frame each frame."
+        );
+        let offset = self.head * self.stride;
+        gpu.queue
+            .write_buffer(&self.buffer, offset, bytemuck::bytes_of(value));
+        self.head = (self.head + 1) % self.capacity;
+        self.frame_len += 1;
+        offset as u32
+    }
+
+    /// Amount of slots used in the current frame.
+    pub fn frame_len(&self) -> u64 { self.frame_len }
+    pub fn capacity(&self) -> u64 { self.capacity }
+}
+
+impl<T: Pod> ToBinder for UniformArena<T> {
+    fn get_layout(&self, index: u32) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding: index,
+            visibility: self.visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: true,
+                min_binding_size: NonZeroU64::new(size_of::<T>() as u64),
+            },
+            count: None,
+        }
+    }
+
+    fn get_group(&self, index: u32) -> wgpu::BindGroupEntry<'_> {
+        wgpu::BindGroupEntry {
+            binding: index,
+            resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                buffer: &self.buffer,
+                offset: 0,
+                size: NonZeroU64::new(size_of::<T>() as u64),
+            }),
+        }
+    }
+
+    fn debug_name(&self) -> &'static str { "Uniform Arena" }
+}
+
+impl<T: Pod> Clone for UniformArena<T> {
+    fn clone(&self) -> Self {
+        Self {
+            buffer: Rc::clone(&self.buffer),
+            visibility: self.visibility,
+            stride: self.stride,
+            capacity: self.capacity,
+            head: self.head,
+            frame_len: self.frame_len,
+            _data: PhantomData,
+        }
+    }
+}
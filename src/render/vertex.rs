@@ -12,6 +12,7 @@ pub struct Vertex {
     pub pos: [f32; 3],
     pub color: Color,
     pub uv: [f32; 2],
+    pub normal: [f32; 3],
 }
 unsafe impl Pod for Vertex {
 }
@@ -36,6 +37,11 @@ impl Vertex {
                 shader_location: 2,
                 format: wgpu::VertexFormat::Float32x2,
             },
+            VertexAttribute {
+                offset: (size_of::<[f32; 5]>() + size_of::<Color>()) as BufferAddress,
+                shader_location: 3,
+                format: wgpu::VertexFormat::Float32x3,
+            },
         ],
     };
     pub fn new(x: f32, y: f32, z: f32, c: Option<Color>, uv: Option<[f32; 2]>) -> Self {
@@ -43,6 +49,7 @@ impl Vertex {
             pos: [x, y, z],
             color: c.unwrap_or(Color::WHITE),
             uv: uv.unwrap_or([0.0, 0.0]),
+            normal: [0.0; 3],
         }
     }
     pub fn from_vec(v: Vec3, c: Option<Color>, uv: Option<[f32; 2]>) -> Self {
@@ -50,8 +57,14 @@ impl Vertex {
             pos: [v.x, v.y, v.z],
             color: c.unwrap_or(Color::WHITE),
             uv: uv.unwrap_or([0.0, 0.0]),
+            normal: [0.0; 3],
         }
     }
+    /// Set the surface direction used for lighting.
+    pub fn with_normal(mut self, normal: Vec3) -> Self {
+        self.normal = normal.to_array();
+        self
+    }
     #[must_use]
     #[inline]
     pub fn x(&self) -> f32 { self.pos[0] }
@@ -62,12 +75,12 @@ impl Vertex {
 #[macro_export]
 macro_rules! vertex {
     ($a:expr, $b:expr, $c:expr) => {
-        nucley::Vertex::new($a, $b, $c, None, None)
+        $crate::Vertex::new($a, $b, $c, None, None)
     };
     ($a:expr, $b:expr, $c:expr, $col:expr) => {
-        crate::Vertex::new($a, $b, $c, Some($col), None)
+        $crate::Vertex::new($a, $b, $c, Some($col), None)
     };
     ($a:expr, $b:expr, $c:expr, $col:expr, $uv:expr) => {
-        crate::Vertex::new($a, $b, $c, Some($col), Some($uv))
+        $crate::Vertex::new($a, $b, $c, Some($col), Some($uv))
     };
 }
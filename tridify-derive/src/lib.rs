@@ -0,0 +1,61 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Fields, Index};
+
+/// Implement `ToGpuBuf` for a struct, packing its fields in declaration order with the padding
+/// required by the layout rules. All fields need to implement `GpuField`.
+#[proc_macro_derive(ToGpuBuf)]
+pub fn derive_to_gpu_buf(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_to_gpu_buf(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand_to_gpu_buf(input: DeriveInput) -> Result<proc_macro2::TokenStream, Error> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new(
+            input.span(),
+            "ToGpuBuf can only be derived for structs.",
+        ));
+    };
+    let accessors: Vec<_> = match &data.fields {
+        Fields::Named(fields) => fields
+            .named
+            .iter()
+            .map(|field| {
+                let name = field.ident.as_ref().unwrap();
+                quote!(#name)
+            })
+            .collect(),
+        Fields::Unnamed(fields) => (0..fields.unnamed.len())
+            .map(|i| {
+                let index = Index::from(i);
+                quote!(#index)
+            })
+            .collect(),
+        Fields::Unit => Vec::new(),
+    };
+
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    let where_clause = generics.make_where_clause();
+    for field in data.fields.iter() {
+        let ty = &field.ty;
+        where_clause
+            .predicates
+            .push(syn::parse_quote!(#ty: ::tridify_rs::GpuField));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::tridify_rs::ToGpuBuf for #name #ty_generics #where_clause {
+            fn to_gpu_bytes(&self, layout: ::tridify_rs::GpuLayout) -> ::std::vec::Vec<u8> {
+                let mut writer = ::tridify_rs::GpuStructWriter::new(layout);
+                #(writer.field(&self.#accessors);)*
+                writer.finish()
+            }
+        }
+    })
+}